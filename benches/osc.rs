@@ -0,0 +1,141 @@
+use async_osc::prelude::*;
+use async_osc::{codec, OscBundle, OscMessage, OscPacket, OscRouter, OscSocket, OscType};
+use async_std::stream::StreamExt;
+use async_std::task;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn message() -> OscPacket {
+    OscMessage::new("/mixer/channel/1/volume", (0.8f32, 3, "label")).into_osc_packet()
+}
+
+fn bundle() -> OscPacket {
+    let content = (0..16)
+        .map(|i| {
+            OscMessage::new(
+                format!("/mixer/channel/{}/volume", i),
+                vec![OscType::Float(i as f32), OscType::Blob(vec![0u8; 64])],
+            )
+            .into_osc_packet()
+        })
+        .collect();
+    OscPacket::Bundle(OscBundle {
+        timetag: (0, 1),
+        content,
+    })
+}
+
+fn encode(c: &mut Criterion) {
+    for (name, packet) in &[("message", message()), ("bundle", bundle())] {
+        c.bench_function(&format!("encode/{}/rosc", name), |b| {
+            b.iter(|| rosc::encoder::encode(black_box(packet)).unwrap())
+        });
+        c.bench_function(&format!("encode/{}/codec", name), |b| {
+            b.iter(|| codec::encode(black_box(packet)).unwrap())
+        });
+        c.bench_function(&format!("encode/{}/codec_small", name), |b| {
+            b.iter(|| codec::encode_small(black_box(packet)).unwrap())
+        });
+        let mut buf = Vec::with_capacity(codec::estimate_encoded_len(packet));
+        c.bench_function(&format!("encode/{}/codec_into", name), |b| {
+            b.iter(|| {
+                buf.clear();
+                codec::encode_into(black_box(packet), &mut buf).unwrap()
+            })
+        });
+    }
+}
+
+fn decode(c: &mut Criterion) {
+    for (name, packet) in &[("message", message()), ("bundle", bundle())] {
+        let buf = codec::encode(packet).unwrap();
+        c.bench_function(&format!("decode/{}/rosc", name), |b| {
+            b.iter(|| rosc::decoder::decode(black_box(&buf)).unwrap())
+        });
+        c.bench_function(&format!("decode/{}/safe", name), |b| {
+            b.iter(|| codec::decode_safe(black_box(&buf)).unwrap())
+        });
+    }
+    // Routers that filter by address only need the header.
+    let buf = codec::encode(&message()).unwrap();
+    c.bench_function("decode/message/header", |b| {
+        b.iter(|| codec::decode_header(black_box(&buf)).unwrap())
+    });
+}
+
+fn send_recv(c: &mut Criterion) {
+    let (sender, mut receiver) = task::block_on(async {
+        let receiver = OscSocket::bind("127.0.0.1:0").await.unwrap();
+        let sender = OscSocket::bind("127.0.0.1:0").await.unwrap();
+        sender
+            .connect(receiver.local_addr().unwrap())
+            .await
+            .unwrap();
+        (sender, receiver)
+    });
+    let packet = message();
+    c.bench_function("send_recv/message", |b| {
+        b.iter(|| {
+            task::block_on(async {
+                sender.send(packet.clone()).await.unwrap();
+                receiver.next().await.unwrap().unwrap()
+            })
+        })
+    });
+}
+
+fn send_compiled(c: &mut Criterion) {
+    let (sender, receiver) = task::block_on(async {
+        let receiver = OscSocket::bind("127.0.0.1:0").await.unwrap();
+        let sender = OscSocket::bind("127.0.0.1:0").await.unwrap();
+        sender
+            .connect(receiver.local_addr().unwrap())
+            .await
+            .unwrap();
+        (sender, receiver)
+    });
+    let addr = "/mixer/channel/1/volume";
+    let args = vec![OscType::Float(0.8), OscType::Int(3)];
+    let compiled = codec::CompiledMessage::new(addr, ",fi").unwrap();
+    // Both variants include the receive, so the difference is the encoding cost.
+    let mut buf = vec![0u8; 1024];
+    c.bench_function("send/fixed_shape/normal", |b| {
+        b.iter(|| {
+            task::block_on(async {
+                let message = OscMessage::new(addr, args.clone());
+                sender.send(black_box(message)).await.unwrap();
+                receiver.socket().recv(&mut buf).await.unwrap()
+            })
+        })
+    });
+    c.bench_function("send/fixed_shape/compiled", |b| {
+        b.iter(|| {
+            task::block_on(async {
+                sender
+                    .send_compiled(&compiled, black_box(&args))
+                    .await
+                    .unwrap();
+                receiver.socket().recv(&mut buf).await.unwrap()
+            })
+        })
+    });
+}
+
+fn dispatch(c: &mut Criterion) {
+    let peer_addr = "127.0.0.1:9000".parse().unwrap();
+    // With the literal fast path, the time per message should not grow with the route count.
+    for routes in &[10, 100, 1000] {
+        let mut router = OscRouter::new();
+        for i in 0..*routes {
+            router.route(format!("/mixer/channel/{}/volume", i), |_, _| {});
+        }
+        router.route("/transport/*", |_, _| {});
+        let packet = OscMessage::new(format!("/mixer/channel/{}/volume", routes - 1), (0.8f32,))
+            .into_osc_packet();
+        c.bench_function(&format!("dispatch/literal/{}", routes), |b| {
+            b.iter(|| router.dispatch(black_box(packet.clone()), peer_addr))
+        });
+    }
+}
+
+criterion_group!(benches, encode, decode, send_recv, send_compiled, dispatch);
+criterion_main!(benches);
@@ -0,0 +1,85 @@
+use arc_swap::ArcSwap;
+use async_osc::{OscSocket, TargetedSender};
+use async_std::task;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const THREADS: usize = 4;
+const LOADS: usize = 10_000;
+
+fn concurrent<F>(f: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+    let threads: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let f = f.clone();
+            thread::spawn(move || {
+                for _ in 0..LOADS {
+                    f();
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+fn target_load(c: &mut Criterion) {
+    let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+    let mutex = Arc::new(Mutex::new(addr));
+    c.bench_function("target_load/mutex", |b| {
+        b.iter(|| {
+            let mutex = mutex.clone();
+            concurrent(move || {
+                black_box(*mutex.lock().unwrap());
+            })
+        })
+    });
+    let swap = Arc::new(ArcSwap::from_pointee(addr));
+    c.bench_function("target_load/arc_swap", |b| {
+        b.iter(|| {
+            let swap = swap.clone();
+            concurrent(move || {
+                black_box(**swap.load());
+            })
+        })
+    });
+}
+
+fn targeted_send(c: &mut Criterion) {
+    // Measures the sender as compiled, run with and without `--features arc-swap` to compare.
+    let (receiver, sender) = task::block_on(async {
+        let receiver = OscSocket::bind("127.0.0.1:0").await.unwrap();
+        let socket = OscSocket::bind("127.0.0.1:0").await.unwrap();
+        let sender = TargetedSender::new(socket.sender(), receiver.local_addr().unwrap());
+        (receiver, sender)
+    });
+    c.bench_function("targeted_send/concurrent", |b| {
+        b.iter(|| {
+            task::block_on(async {
+                let tasks: Vec<_> = (0..THREADS)
+                    .map(|_| {
+                        let sender = sender.clone();
+                        task::spawn(async move {
+                            for _ in 0..100 {
+                                sender.send(("/bench", (1,))).await.unwrap();
+                            }
+                        })
+                    })
+                    .collect();
+                for task in tasks {
+                    task.await;
+                }
+            })
+        })
+    });
+    drop(receiver);
+}
+
+criterion_group!(benches, target_load, targeted_send);
+criterion_main!(benches);
@@ -0,0 +1,117 @@
+//! Derive macros for [async-osc](https://docs.rs/async-osc).
+//!
+//! Use the macros through the `derive` feature of `async-osc` instead of depending on this crate
+//! directly.
+
+#![forbid(unsafe_code, future_incompatible)]
+#![deny(rust_2018_idioms, missing_debug_implementations, nonstandard_style)]
+#![warn(missing_docs, unreachable_pub)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr, Variant};
+
+/// Implements `TryFrom<&OscMessage>` for an enum.
+///
+/// See the documentation of `async_osc::FromOscMessage` for details.
+#[proc_macro_derive(FromOscMessage, attributes(osc))]
+pub fn derive_from_osc_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let data = match input.data {
+        Data::Enum(data) => data,
+        _ => {
+            let message = "FromOscMessage can only be derived for enums";
+            return Err(Error::new_spanned(&input.ident, message));
+        }
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| expand_variant(name, variant))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(quote! {
+        impl #impl_generics ::std::convert::TryFrom<&::async_osc::OscMessage>
+            for #name #ty_generics #where_clause
+        {
+            type Error = ::async_osc::Error;
+
+            fn try_from(message: &::async_osc::OscMessage) -> ::std::result::Result<Self, Self::Error> {
+                let args = &message.args;
+                match message.addr.as_str() {
+                    #(#arms)*
+                    _ => ::std::result::Result::Err(::async_osc::Error::Osc(
+                        ::async_osc::OscError::BadMessage("unknown address"),
+                    )),
+                }
+            }
+        }
+    })
+}
+
+fn expand_variant(name: &syn::Ident, variant: &Variant) -> Result<TokenStream2, Error> {
+    let addr = variant_addr(variant)?;
+    let ident = &variant.ident;
+    let len = variant.fields.len();
+    let args = (0..len).map(|i| {
+        quote! {
+            ::async_osc::FromOscType::from_osc_type(&args[#i]).ok_or_else(|| {
+                ::async_osc::Error::Osc(::async_osc::OscError::BadArg(
+                    ::std::format!("unexpected type of argument {} for {}", #i, #addr),
+                ))
+            })?
+        }
+    });
+    let value = match &variant.fields {
+        Fields::Unit => quote!(#name::#ident),
+        Fields::Unnamed(_) => quote!(#name::#ident(#(#args),*)),
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote!(#name::#ident { #(#names: #args),* })
+        }
+    };
+    Ok(quote! {
+        #addr => {
+            if args.len() != #len {
+                let message = ::std::format!(
+                    "expected {} arguments for {}, got {}", #len, #addr, args.len()
+                );
+                return ::std::result::Result::Err(::async_osc::Error::Osc(
+                    ::async_osc::OscError::BadArg(message),
+                ));
+            }
+            ::std::result::Result::Ok(#value)
+        }
+    })
+}
+
+/// Returns the address from the `#[osc(addr = "...")]` attribute of a variant.
+fn variant_addr(variant: &Variant) -> Result<LitStr, Error> {
+    let mut addr = None;
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("osc"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("addr") {
+                addr = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `addr`"))
+            }
+        })?;
+    }
+    addr.ok_or_else(|| {
+        let message = "missing `#[osc(addr = \"...\")]` attribute";
+        Error::new_spanned(&variant.ident, message)
+    })
+}
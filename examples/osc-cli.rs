@@ -0,0 +1,141 @@
+//! A minimal command line tool to send and receive OSC messages.
+//!
+//! ```text
+//! cargo run --example osc-cli -- listen 127.0.0.1:9000
+//! cargo run --example osc-cli -- dump 127.0.0.1:9000
+//! cargo run --example osc-cli -- send 127.0.0.1:9000 /mixer/volume 1 0.5 main
+//! ```
+//!
+//! Arguments are parsed as integers, finite floats, booleans or strings, in that order, so `inf`
+//! and `nan` are sent as strings. The `dump` command prints a hex dump of each received datagram.
+
+use async_osc::{
+    codec, format_message_with, prelude::*, FormatOptions, OscMessage, OscPacket, OscSocket,
+    OscType, Result,
+};
+use async_std::stream::StreamExt;
+use std::net::SocketAddr;
+
+const USAGE: &str =
+    "usage: osc-cli listen <addr> | osc-cli dump <addr> | osc-cli send <addr> <osc-address> [args...]";
+
+#[async_std::main]
+async fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["listen", addr] => listen(addr).await,
+        ["dump", addr] => dump(addr).await,
+        ["send", addr, osc_addr, osc_args @ ..] => match addr.parse() {
+            Ok(addr) => send(addr, osc_addr, osc_args).await,
+            Err(_) => usage(&format!("invalid socket address: {}", addr)),
+        },
+        _ => usage(""),
+    }
+}
+
+/// Prints `error` and the usage and exits.
+fn usage(error: &str) -> ! {
+    if !error.is_empty() {
+        eprintln!("{}", error);
+    }
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+async fn listen(addr: &str) -> Result<()> {
+    let mut socket = OscSocket::bind(addr).await?;
+    eprintln!("Listening on {}", socket.local_addr()?);
+    while let Some(packet) = socket.next().await {
+        let (packet, peer_addr) = packet?;
+        print_packet(&packet, peer_addr);
+    }
+    Ok(())
+}
+
+async fn dump(addr: &str) -> Result<()> {
+    let socket = OscSocket::bind(addr).await?;
+    eprintln!("Listening on {}", socket.local_addr()?);
+    let mut datagrams = socket.raw_stream();
+    while let Some(datagram) = datagrams.next().await {
+        let (buf, peer_addr) = datagram?;
+        println!("{} ({} bytes)", peer_addr, buf.len());
+        println!("{}", codec::hexdump(&buf));
+    }
+    Ok(())
+}
+
+fn print_packet(packet: &OscPacket, peer_addr: SocketAddr) {
+    match packet {
+        OscPacket::Message(message) => {
+            let options = FormatOptions { blob_hex_len: 16 };
+            println!("{} {}", peer_addr, format_message_with(message, options));
+        }
+        OscPacket::Bundle(bundle) => {
+            for packet in &bundle.content {
+                print_packet(packet, peer_addr);
+            }
+        }
+    }
+}
+
+async fn send(addr: SocketAddr, osc_addr: &str, args: &[&str]) -> Result<()> {
+    let local_addr = if addr.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = OscSocket::bind(local_addr).await?;
+    let args: Vec<OscType> = args.iter().map(|arg| parse_arg(arg)).collect();
+    socket.send_to(OscMessage::new(osc_addr, args), addr).await
+}
+
+/// Parses a command line token into an OSC argument.
+fn parse_arg(token: &str) -> OscType {
+    if let Ok(value) = token.parse::<i32>() {
+        OscType::Int(value)
+    } else if let Some(value) = token.parse::<f32>().ok().filter(|value| value.is_finite()) {
+        OscType::Float(value)
+    } else if let Ok(value) = token.parse::<bool>() {
+        OscType::Bool(value)
+    } else {
+        OscType::String(token.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_osc::format_message;
+
+    #[test]
+    fn parse_args() {
+        assert_eq!(parse_arg("42"), OscType::Int(42));
+        assert_eq!(parse_arg("-1"), OscType::Int(-1));
+        assert_eq!(parse_arg("0.5"), OscType::Float(0.5));
+        assert_eq!(parse_arg("1e3"), OscType::Float(1000.0));
+        assert_eq!(parse_arg("true"), OscType::Bool(true));
+        assert_eq!(parse_arg("main"), OscType::String("main".into()));
+        assert_eq!(parse_arg("inf"), OscType::String("inf".into()));
+        assert_eq!(parse_arg("NaN"), OscType::String("NaN".into()));
+        assert_eq!(parse_arg("1e40"), OscType::String("1e40".into()));
+    }
+
+    #[async_std::test]
+    async fn send_to_listener() -> Result<()> {
+        let (mut listener, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+        send(addr, "/mixer/volume", &["1", "0.5", "main"]).await?;
+        let (packet, _) = listener.next().await.unwrap()?;
+        let message = packet.into_message().unwrap();
+        assert_eq!(
+            message,
+            OscMessage::new("/mixer/volume", (1, 0.5f32, "main"))
+        );
+        assert_eq!(format_message(&message), r#"/mixer/volume 1 0.5 "main""#);
+        Ok(())
+    }
+}
@@ -21,6 +21,7 @@ async fn main() -> Result<()> {
         eprintln!("Receive from {}: {:?}", peer_addr, packet);
         match packet {
             OscPacket::Bundle(_) => {}
+            #[allow(clippy::single_match)]
             OscPacket::Message(message) => match &message.as_tuple() {
                 ("/volume", &[OscType::Float(vol), OscType::String(ref s)]) => {
                     eprintln!("Set volume: {} {}", vol, s);
@@ -0,0 +1,9 @@
+#![no_main]
+use async_osc::codec::{decode, decode_packet, decode_safe, DecodeMode};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = decode_safe(data);
+    let _ = decode(data, DecodeMode::Lenient);
+    let _ = decode_packet(data);
+});
@@ -0,0 +1,266 @@
+use async_std::net::{ToSocketAddrs, UdpSocket};
+use async_std::task;
+use socket2::SockRef;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::time::Duration;
+
+use crate::error::Error;
+
+/// Parses a socket address, including IPv6 addresses with an interface scope.
+///
+/// The standard library only accepts numeric scope ids (`[fe80::1%2]:9000`). This function also
+/// accepts interface names (`[fe80::1%eth0]:9000`) and sets the scope id to the index of the
+/// named interface. Resolving interface names is only supported on Unix.
+///
+/// The returned address can be passed to [`OscSocket::bind`], [`OscSocket::connect`] and
+/// [`OscSocket::send_to`] and preserves the scope id.
+///
+/// [`OscSocket::bind`]: crate::OscSocket::bind
+/// [`OscSocket::connect`]: crate::OscSocket::connect
+/// [`OscSocket::send_to`]: crate::OscSocket::send_to
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> {
+/// let addr = async_osc::parse_scoped_addr("[fe80::1%eth0]:9000")?;
+/// # Ok(()) }
+/// ```
+pub fn parse_scoped_addr(addr: &str) -> Result<SocketAddr, Error> {
+    if let Ok(addr) = addr.parse() {
+        return Ok(addr);
+    }
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "invalid socket address");
+    let rest = addr.strip_prefix('[').ok_or_else(invalid)?;
+    let (host, port) = rest.split_once("]:").ok_or_else(invalid)?;
+    let (ip, scope) = host.split_once('%').ok_or_else(invalid)?;
+    let ip: Ipv6Addr = ip.parse().map_err(|_| invalid())?;
+    let port: u16 = port.parse().map_err(|_| invalid())?;
+    let scope_id = match scope.parse() {
+        Ok(scope_id) => scope_id,
+        Err(_) => interface_index(scope)?,
+    };
+    Ok(SocketAddrV6::new(ip, port, 0, scope_id).into())
+}
+
+#[cfg(unix)]
+fn interface_index(name: &str) -> io::Result<u32> {
+    nix::net::if_::if_nametoindex(name).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("unknown network interface: {}", name),
+        )
+    })
+}
+
+#[cfg(not(unix))]
+fn interface_index(name: &str) -> io::Result<u32> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "cannot resolve interface name {}, use a numeric scope id",
+            name
+        ),
+    ))
+}
+
+/// Returns the IP addresses of all local network interfaces.
+#[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+pub(crate) fn interface_ips() -> io::Result<Vec<IpAddr>> {
+    let addrs = nix::ifaddrs::getifaddrs().map_err(io::Error::from)?;
+    let ips = addrs
+        .filter_map(|ifaddr| {
+            let addr = ifaddr.address?;
+            if let Some(addr) = addr.as_sockaddr_in() {
+                Some(IpAddr::V4(addr.ip()))
+            } else {
+                addr.as_sockaddr_in6().map(|addr| IpAddr::V6(addr.ip()))
+            }
+        })
+        .collect();
+    Ok(ips)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple")))]
+pub(crate) fn interface_ips() -> io::Result<Vec<IpAddr>> {
+    Ok(Vec::new())
+}
+
+/// The local address of a socket and whether it is IPv6-only.
+///
+/// A socket cannot change its address family after binding, so this is read once when the
+/// socket is bound and used to check targets before sending.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Family {
+    local: SocketAddr,
+    only_v6: bool,
+}
+
+impl Family {
+    /// Reads the family of `socket`.
+    pub(crate) fn of(socket: &UdpSocket) -> io::Result<Self> {
+        let local = socket.local_addr()?;
+        let only_v6 = local.is_ipv6() && SockRef::from(socket).only_v6()?;
+        Ok(Self { local, only_v6 })
+    }
+
+    /// Returns the first of `targets` that the socket can send to. See [`check`](Self::check).
+    fn select(self, targets: &[SocketAddr]) -> Result<SocketAddr, Error> {
+        let mut mismatch = None;
+        for target in targets {
+            match self.check(*target) {
+                Err(err @ Error::AddressFamilyMismatch { .. }) => {
+                    mismatch.get_or_insert(err);
+                }
+                res => return res,
+            }
+        }
+        Err(mismatch.unwrap_or_else(|| {
+            let message = "no addresses to send data to";
+            io::Error::new(io::ErrorKind::InvalidInput, message).into()
+        }))
+    }
+
+    /// Checks that the socket can send to `target` and returns the address to send to.
+    ///
+    /// An IPv4 target of a dual-stack IPv6 socket is mapped to an IPv4-mapped IPv6 address, and
+    /// an IPv4-mapped IPv6 target of an IPv4 socket is mapped back to the IPv4 address. Sending
+    /// from an IPv4 socket to any other IPv6 target, or from an IPv6-only socket to an IPv4
+    /// target, fails with [`Error::AddressFamilyMismatch`].
+    fn check(self, target: SocketAddr) -> Result<SocketAddr, Error> {
+        let local = self.local;
+        match (local, target) {
+            (SocketAddr::V4(_), SocketAddr::V4(_)) | (SocketAddr::V6(_), SocketAddr::V6(_)) => {
+                Ok(target)
+            }
+            (SocketAddr::V6(_), SocketAddr::V4(v4)) if !self.only_v6 => {
+                let ip = v4.ip().to_ipv6_mapped();
+                Ok(SocketAddr::V6(SocketAddrV6::new(ip, v4.port(), 0, 0)))
+            }
+            (SocketAddr::V4(_), SocketAddr::V6(v6)) => match v6.ip().to_ipv4_mapped() {
+                Some(ip) => Ok(SocketAddr::V4(SocketAddrV4::new(ip, v6.port()))),
+                None => Err(Error::AddressFamilyMismatch { local, target }),
+            },
+            _ => Err(Error::AddressFamilyMismatch { local, target }),
+        }
+    }
+}
+
+/// Resolves `addrs` and sends `buf` to the first address that `socket` can send to.
+///
+/// See [`send_to_any`].
+pub(crate) async fn send_to_resolved<A: ToSocketAddrs>(
+    socket: &UdpSocket,
+    family: Option<Family>,
+    buf: &[u8],
+    addrs: A,
+) -> Result<usize, Error> {
+    let targets: Vec<_> = addrs.to_socket_addrs().await?.collect();
+    send_to_any(socket, family, buf, &targets).await
+}
+
+/// Sends `buf` to the first of `targets` that `socket` can send to.
+///
+/// The address families are checked against `family`, the cached family of `socket`, before
+/// sending. It is read from the socket if it is not known. Returns
+/// [`Error::AddressFamilyMismatch`] if the targets have no address family the socket can send to.
+pub(crate) async fn send_to_any(
+    socket: &UdpSocket,
+    family: Option<Family>,
+    buf: &[u8],
+    targets: &[SocketAddr],
+) -> Result<usize, Error> {
+    let family = match family {
+        Some(family) => family,
+        None => Family::of(socket)?,
+    };
+    let target = family.select(targets)?;
+    Ok(socket.send_to(buf, target).await?)
+}
+
+/// Resolves `addr`, retrying up to `attempts` times if resolution fails or yields no addresses.
+///
+/// The delay between attempts starts at `delay` and doubles after each attempt. A malformed
+/// address fails with [`io::ErrorKind::InvalidInput`] and is not retried.
+pub(crate) async fn resolve_retry<A: ToSocketAddrs>(
+    addr: &A,
+    attempts: u32,
+    delay: Duration,
+) -> io::Result<Vec<SocketAddr>> {
+    retry(attempts, delay, || async {
+        let addrs: Vec<_> = addr.to_socket_addrs().await?.collect();
+        if addrs.is_empty() {
+            let message = "could not resolve to any addresses";
+            return Err(io::Error::new(io::ErrorKind::NotFound, message));
+        }
+        Ok(addrs)
+    })
+    .await
+}
+
+async fn retry<T, F, Fut>(attempts: u32, mut delay: Duration, mut f: F) -> io::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = io::Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= attempts || err.kind() == io::ErrorKind::InvalidInput => {
+                return Err(err)
+            }
+            Err(err) => log::debug!("Resolving address failed (attempt {}): {}", attempt, err),
+        }
+        task::sleep(delay).await;
+        delay = delay.saturating_mul(2);
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[async_std::test]
+    async fn retry_until_resolvable() {
+        // A resolver that becomes ready on the third attempt.
+        let mut calls = 0;
+        let res = retry(3, Duration::from_millis(1), || {
+            calls += 1;
+            let res = match calls {
+                1 | 2 => Err(io::Error::other("resolver not ready")),
+                _ => Ok(calls),
+            };
+            async move { res }
+        })
+        .await;
+        assert_eq!(res.unwrap(), 3);
+
+        let mut calls = 0;
+        let res: io::Result<()> = retry(2, Duration::from_millis(1), || {
+            calls += 1;
+            async { Err(io::Error::other("resolver not ready")) }
+        })
+        .await;
+        assert!(res.is_err());
+        assert_eq!(calls, 2);
+
+        // A malformed address never becomes resolvable.
+        let mut calls = 0;
+        let res: io::Result<()> = retry(3, Duration::from_millis(1), || {
+            calls += 1;
+            async {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "invalid socket address",
+                ))
+            }
+        })
+        .await;
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(calls, 1);
+    }
+}
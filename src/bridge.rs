@@ -0,0 +1,126 @@
+use async_std::stream::StreamExt;
+use rosc::OscPacket;
+use std::fmt;
+use std::net::SocketAddr;
+
+use crate::codec;
+use crate::error::Error;
+use crate::osc::{OscSender, OscSocket};
+
+type Rewrite = Box<dyn FnMut(&str) -> String + Send>;
+
+/// Forwards packets from one socket to another.
+///
+/// By default, received datagrams are forwarded as is with [`OscSender::send_raw`], without
+/// decoding and re-encoding them. With an address rewrite hook, packets are decoded, the
+/// addresses of all messages are rewritten and the packets are encoded again. Datagrams that
+/// cannot be decoded are then dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{OscBridge, OscSocket};
+///
+/// let from = OscSocket::bind("0.0.0.0:9000").await?;
+/// let to = OscSocket::bind("0.0.0.0:0").await?;
+/// to.connect("192.168.1.10:9000").await?;
+/// OscBridge::run(from, to.sender()).await?;
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Default)]
+pub struct OscBridge {
+    rewrite: Option<Rewrite>,
+}
+
+impl fmt::Debug for OscBridge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OscBridge")
+            .field("rewrite", &self.rewrite.is_some())
+            .finish()
+    }
+}
+
+impl OscBridge {
+    /// Forwards all datagrams received on `from` unchanged to `to`.
+    ///
+    /// The `to` sender has to be connected. Runs until receiving or sending fails.
+    pub async fn run(from: OscSocket, to: OscSender) -> Result<(), Error> {
+        Self::default().forward(from, to).await
+    }
+
+    /// Creates a bridge that rewrites the address of every forwarded message with `f`.
+    ///
+    /// ```
+    /// # use async_osc::OscBridge;
+    /// let bridge = OscBridge::with_rewrite(|addr| format!("/remote{}", addr));
+    /// ```
+    pub fn with_rewrite<F>(f: F) -> Self
+    where
+        F: FnMut(&str) -> String + Send + 'static,
+    {
+        Self {
+            rewrite: Some(Box::new(f)),
+        }
+    }
+
+    /// Forwards all datagrams received on `from` to `to`.
+    ///
+    /// The `to` sender has to be connected. Transient errors, for example because the target is
+    /// not listening yet, are logged and the datagram is dropped. Runs until receiving or sending
+    /// fails with a persistent error.
+    pub async fn forward(mut self, from: OscSocket, to: OscSender) -> Result<(), Error> {
+        let mut stream = from.raw_stream();
+        while let Some(item) = stream.next().await {
+            let (buf, peer_addr) = match item {
+                Ok(item) => item,
+                Err(err) if err.is_recoverable() => {
+                    log::debug!("Skipping failed receive: {}", err);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            let rewrite = match self.rewrite.as_mut() {
+                Some(rewrite) => rewrite,
+                None => {
+                    skip_transient(to.send_raw(&buf).await, peer_addr)?;
+                    continue;
+                }
+            };
+            match codec::decode_safe(&buf) {
+                Ok(mut packet) => {
+                    rewrite_addrs(&mut packet, rewrite);
+                    let buf = codec::encode_small(&packet)?;
+                    let res = to.send_raw(&buf).await;
+                    skip_transient(res, peer_addr)?;
+                }
+                Err(err) => log::debug!("Dropping invalid packet from {}: {}", peer_addr, err),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Logs and drops a datagram that could not be forwarded because of a transient error.
+fn skip_transient(res: Result<(), Error>, peer_addr: SocketAddr) -> Result<(), Error> {
+    match res {
+        Err(err) if err.is_recoverable() => {
+            log::debug!("Dropping packet from {}: {}", peer_addr, err);
+            Ok(())
+        }
+        res => res,
+    }
+}
+
+fn rewrite_addrs(packet: &mut OscPacket, rewrite: &mut Rewrite) {
+    match packet {
+        OscPacket::Message(message) => message.addr = rewrite(&message.addr),
+        OscPacket::Bundle(bundle) => {
+            for packet in &mut bundle.content {
+                rewrite_addrs(packet, rewrite);
+            }
+        }
+    }
+}
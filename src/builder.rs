@@ -0,0 +1,106 @@
+use async_std::net::{ToSocketAddrs, UdpSocket};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io;
+use std::net::SocketAddr;
+
+use crate::codec::DecodeMode;
+use crate::error::Error;
+use crate::osc::OscSocket;
+
+/// A builder to configure an [`OscSocket`] before binding it.
+///
+/// Some socket options have to be set before the socket is bound. Use this builder to set them.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::OscSocket;
+///
+/// // Accept both IPv6 and IPv4 packets on all interfaces.
+/// let socket = OscSocket::builder().only_v6(false).bind("[::]:9000").await?;
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OscSocketBuilder {
+    only_v6: Option<bool>,
+    reuse_address: bool,
+    decode_mode: DecodeMode,
+}
+
+impl OscSocketBuilder {
+    /// Creates a new builder with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether an IPv6 socket only accepts IPv6 traffic (`IPV6_V6ONLY`).
+    ///
+    /// If set to `false`, a socket bound to an IPv6 address also sends and receives IPv4 packets
+    /// using IPv4-mapped IPv6 addresses (dual-stack). If not set, the OS default is used, which
+    /// differs between platforms. The option is ignored for IPv4 addresses.
+    pub fn only_v6(mut self, only_v6: bool) -> Self {
+        self.only_v6 = Some(only_v6);
+        self
+    }
+
+    /// Sets whether the socket may bind to an address that is already in use (`SO_REUSEADDR`).
+    ///
+    /// This allows several sockets, possibly in different processes, to listen on the same
+    /// multicast port. All of them have to set this option. Defaults to `false`.
+    pub fn reuse_address(mut self, reuse: bool) -> Self {
+        self.reuse_address = reuse;
+        self
+    }
+
+    /// Sets how received datagrams are decoded.
+    ///
+    /// See [`OscSocket::set_decode_mode`].
+    pub fn decode_mode(mut self, mode: DecodeMode) -> Self {
+        self.decode_mode = mode;
+        self
+    }
+
+    /// Creates the socket and binds it to the given address.
+    ///
+    /// If `addr` yields multiple addresses, binding will be attempted with each of the addresses
+    /// until one succeeds.
+    pub async fn bind<A: ToSocketAddrs>(self, addr: A) -> Result<OscSocket, Error> {
+        let mut socket = OscSocket::new(self.bind_socket(addr).await?);
+        socket.set_decode_mode(self.decode_mode);
+        Ok(socket)
+    }
+
+    /// Creates a UDP socket with the options of this builder and binds it to the given address.
+    pub(crate) async fn bind_socket<A: ToSocketAddrs>(&self, addr: A) -> Result<UdpSocket, Error> {
+        let mut last_err = None;
+        for addr in addr.to_socket_addrs().await? {
+            match self.bind_addr(addr) {
+                Ok(socket) => return Ok(UdpSocket::from(socket)),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err
+            .unwrap_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "could not resolve to any addresses",
+                )
+            })
+            .into())
+    }
+
+    fn bind_addr(&self, addr: SocketAddr) -> io::Result<std::net::UdpSocket> {
+        let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+        if let (true, Some(only_v6)) = (addr.is_ipv6(), self.only_v6) {
+            socket.set_only_v6(only_v6)?;
+        }
+        if self.reuse_address {
+            socket.set_reuse_address(true)?;
+        }
+        socket.bind(&addr.into())?;
+        Ok(socket.into())
+    }
+}
@@ -0,0 +1,88 @@
+use rosc::{OscBundle, OscError, OscPacket, OscTime};
+
+use crate::error::Error;
+use crate::prelude::IntoOscPacket;
+
+/// The OSC timetag for "immediately".
+const IMMEDIATELY: OscTime = (0, 1);
+
+/// A builder for bundles with nested, scheduled contents.
+///
+/// Items pushed to the builder can be messages or bundles. Nested bundles can have their own
+/// timetags, which allows to build a schedule within a schedule. [`build`](Self::build) checks
+/// that no nested bundle is scheduled before the bundle that contains it, which receivers would
+/// not be able to honor.
+///
+/// ```
+/// # use async_osc::{*, prelude::*};
+/// let cue = OscBundleBuilder::at((100, 0))
+///     .push(OscMessage::new("/lights/go", ()))
+///     .push(OscBundleBuilder::at((101, 0)).push(OscMessage::new("/sound/go", ())).build()?)
+///     .build()?;
+/// # Ok::<(), async_osc::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct OscBundleBuilder {
+    timetag: OscTime,
+    content: Vec<OscPacket>,
+}
+
+impl OscBundleBuilder {
+    /// Creates a builder for a bundle that is executed immediately.
+    pub fn new() -> Self {
+        Self::at(IMMEDIATELY)
+    }
+
+    /// Creates a builder for a bundle with the given timetag.
+    pub fn at(timetag: OscTime) -> Self {
+        Self {
+            timetag,
+            content: Vec::new(),
+        }
+    }
+
+    /// Adds a message or a nested bundle.
+    pub fn push<P: IntoOscPacket>(mut self, packet: P) -> Self {
+        self.content.push(packet.into_osc_packet());
+        self
+    }
+
+    /// Builds the bundle.
+    ///
+    /// Returns an error if a nested bundle, at any depth, has a timetag before the timetag of
+    /// its parent. Nested bundles with the timetag "immediately" are executed together with
+    /// their parent and are always accepted.
+    pub fn build(self) -> Result<OscBundle, Error> {
+        let bundle = OscBundle {
+            timetag: self.timetag,
+            content: self.content,
+        };
+        check_timetags(&bundle)?;
+        Ok(bundle)
+    }
+}
+
+impl Default for OscBundleBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check_timetags(bundle: &OscBundle) -> Result<(), Error> {
+    for packet in &bundle.content {
+        if let OscPacket::Bundle(child) = packet {
+            if bundle.timetag != IMMEDIATELY
+                && child.timetag != IMMEDIATELY
+                && child.timetag < bundle.timetag
+            {
+                let message = format!(
+                    "Nested bundle timetag {:?} is before its parent's timetag {:?}",
+                    child.timetag, bundle.timetag
+                );
+                return Err(OscError::BadBundle(message).into());
+            }
+            check_timetags(child)?;
+        }
+    }
+    Ok(())
+}
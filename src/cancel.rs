@@ -0,0 +1,86 @@
+use async_std::channel::{bounded, Receiver, Sender};
+use async_std::stream::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A token to cancel receive streams.
+///
+/// Clones of a token share the same state: cancelling one cancels all of them. See
+/// [`OscSocket::with_cancellation`](crate::OscSocket::with_cancellation).
+#[derive(Clone, Debug)]
+pub struct CancellationToken {
+    sender: Sender<()>,
+    receiver: Receiver<()>,
+}
+
+impl CancellationToken {
+    /// Creates a new token that is not cancelled.
+    pub fn new() -> Self {
+        let (sender, receiver) = bounded(1);
+        Self { sender, receiver }
+    }
+
+    /// Cancels the token and wakes up all streams that use it.
+    pub fn cancel(&self) {
+        self.sender.close();
+    }
+
+    /// Returns `true` if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// Waits until the token is cancelled.
+    pub(crate) async fn cancelled(&self) {
+        // Nothing is ever sent on the channel, so this only returns once it is closed.
+        let _ = self.receiver.recv().await;
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A stream that ends when a [`CancellationToken`] is cancelled.
+///
+/// See [`OscSocket::with_cancellation`](crate::OscSocket::with_cancellation).
+#[derive(Debug)]
+pub struct Cancellable<S> {
+    stream: S,
+    // Holding the token keeps its channel open until the token is explicitly cancelled, even if
+    // the caller dropped all other clones.
+    token: CancellationToken,
+}
+
+impl<S> Cancellable<S> {
+    pub(crate) fn new(stream: S, token: CancellationToken) -> Self {
+        Self { stream, token }
+    }
+
+    /// Get a reference to the wrapped stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Get a mutable reference to the wrapped stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes the adapter, returning the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: Stream + Unpin> Stream for Cancellable<S> {
+    type Item = S::Item;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Poll::Ready(None) = Pin::new(&mut self.token.receiver).poll_next(cx) {
+            return Poll::Ready(None);
+        }
+        Pin::new(&mut self.stream).poll_next(cx)
+    }
+}
@@ -0,0 +1,260 @@
+use futures_lite::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use rosc::OscPacket;
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::codec;
+use crate::error::Error;
+
+const MAGIC: &[u8; 6] = b"OSCCAP";
+
+/// Maximum length of a captured packet, the largest payload of a UDP datagram.
+const MAX_PACKET_LEN: usize = 65_535;
+
+/// Metadata at the start of a capture file.
+///
+/// The header makes captures self-describing: record times are stored relative to
+/// [`start_time`](Self::start_time), so replay can reconstruct the absolute timing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CaptureHeader {
+    /// The format version the capture was written with.
+    pub version: u16,
+    /// The time the capture session started.
+    pub start_time: SystemTime,
+    /// The local address of the socket the packets were captured on, if known.
+    pub local_addr: Option<SocketAddr>,
+}
+
+impl CaptureHeader {
+    /// The format version written by this version of the crate.
+    pub const VERSION: u16 = 1;
+
+    /// Creates a header for a session starting now.
+    pub fn new(local_addr: Option<SocketAddr>) -> Self {
+        Self {
+            version: Self::VERSION,
+            start_time: SystemTime::now(),
+            local_addr,
+        }
+    }
+}
+
+/// A packet read from a capture.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaptureRecord {
+    /// The time the packet was captured.
+    pub time: SystemTime,
+    /// The address of the peer that sent the packet.
+    pub peer_addr: SocketAddr,
+    /// The captured packet.
+    pub packet: OscPacket,
+}
+
+/// Writes received packets to a capture file.
+///
+/// A capture starts with a [`CaptureHeader`], followed by one record per packet with the time it
+/// was received, the peer address and the encoded packet. Read captures with [`CaptureReader`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{CaptureHeader, CaptureWriter, OscSocket};
+/// use async_std::fs::File;
+/// use async_std::stream::StreamExt;
+/// use std::time::SystemTime;
+///
+/// let mut socket = OscSocket::bind("127.0.0.1:9000").await?;
+/// let file = File::create("session.osc").await?;
+/// let header = CaptureHeader::new(Some(socket.local_addr()?));
+/// let mut capture = CaptureWriter::new(file, header).await?;
+/// while let Some(packet) = socket.next().await {
+///     let (packet, peer_addr) = packet?;
+///     capture.write(&packet, peer_addr, SystemTime::now()).await?;
+/// }
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Debug)]
+pub struct CaptureWriter<W> {
+    io: W,
+    header: CaptureHeader,
+    buf: Vec<u8>,
+}
+
+impl<W: AsyncWrite + Unpin> CaptureWriter<W> {
+    /// Writes `header` to `io` and returns a writer for the records.
+    pub async fn new(mut io: W, header: CaptureHeader) -> Result<Self, Error> {
+        let mut buf = MAGIC.to_vec();
+        buf.extend_from_slice(&header.version.to_be_bytes());
+        let start = header
+            .start_time
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| invalid_data("capture start time is before 1970"))?;
+        buf.extend_from_slice(&start.as_secs().to_be_bytes());
+        buf.extend_from_slice(&start.subsec_nanos().to_be_bytes());
+        let local_addr = header.local_addr.map(|addr| addr.to_string());
+        write_str(local_addr.as_deref().unwrap_or(""), &mut buf);
+        io.write_all(&buf).await?;
+        Ok(Self {
+            io,
+            header,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Returns the header of the capture.
+    pub fn header(&self) -> &CaptureHeader {
+        &self.header
+    }
+
+    /// Appends a packet received from `peer_addr` at `time`.
+    ///
+    /// Times before the start of the session are stored as the start time. Returns an error of
+    /// kind [`io::ErrorKind::InvalidInput`] if the encoded packet is longer than the largest UDP
+    /// datagram.
+    pub async fn write(
+        &mut self,
+        packet: &OscPacket,
+        peer_addr: SocketAddr,
+        time: SystemTime,
+    ) -> Result<(), Error> {
+        let offset = time
+            .duration_since(self.header.start_time)
+            .unwrap_or_default();
+        let packet = codec::encode_small(packet)?;
+        if packet.len() > MAX_PACKET_LEN {
+            let message = "packet is too long for a capture";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        }
+        self.buf.clear();
+        self.buf
+            .extend_from_slice(&(offset.as_micros() as u64).to_be_bytes());
+        write_str(&peer_addr.to_string(), &mut self.buf);
+        self.buf
+            .extend_from_slice(&(packet.len() as u32).to_be_bytes());
+        self.buf.extend_from_slice(&packet);
+        self.io.write_all(&self.buf).await?;
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.io.flush().await?;
+        Ok(())
+    }
+
+    /// Consumes the writer, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.io
+    }
+}
+
+/// Reads a capture written with [`CaptureWriter`].
+#[derive(Debug)]
+pub struct CaptureReader<R> {
+    io: R,
+    header: CaptureHeader,
+}
+
+impl<R: AsyncRead + Unpin> CaptureReader<R> {
+    /// Reads the header from `io` and returns a reader for the records.
+    ///
+    /// Returns [`Error::Unsupported`] if the capture was written with a newer format version, and
+    /// an error of kind [`io::ErrorKind::InvalidData`] if the header is corrupt.
+    pub async fn new(mut io: R) -> Result<Self, Error> {
+        let mut magic = [0u8; 6];
+        io.read_exact(&mut magic).await?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not an OSC capture").into());
+        }
+        let version = u16::from_be_bytes(read_array(&mut io).await?);
+        if version > CaptureHeader::VERSION {
+            return Err(Error::Unsupported("this capture format version"));
+        }
+        let secs = u64::from_be_bytes(read_array(&mut io).await?);
+        let nanos = u32::from_be_bytes(read_array(&mut io).await?);
+        if nanos >= 1_000_000_000 {
+            return Err(invalid_data("invalid start time in capture").into());
+        }
+        let start_time = UNIX_EPOCH
+            .checked_add(Duration::new(secs, nanos))
+            .ok_or_else(|| invalid_data("invalid start time in capture"))?;
+        let local_addr = read_str(&mut io).await?;
+        let local_addr = if local_addr.is_empty() {
+            None
+        } else {
+            Some(parse_addr(&local_addr)?)
+        };
+        let header = CaptureHeader {
+            version,
+            start_time,
+            local_addr,
+        };
+        Ok(Self { io, header })
+    }
+
+    /// Returns the header of the capture.
+    pub fn header(&self) -> &CaptureHeader {
+        &self.header
+    }
+
+    /// Reads the next record, or returns `None` at the end of the capture.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the record is corrupt.
+    pub async fn next_record(&mut self) -> Result<Option<CaptureRecord>, Error> {
+        let mut offset = [0u8; 8];
+        // A capture may only end between records.
+        match self.io.read(&mut offset[..1]).await? {
+            0 => return Ok(None),
+            _ => self.io.read_exact(&mut offset[1..]).await?,
+        }
+        let time = self
+            .header
+            .start_time
+            .checked_add(Duration::from_micros(u64::from_be_bytes(offset)))
+            .ok_or_else(|| invalid_data("invalid record time in capture"))?;
+        let peer_addr = parse_addr(&read_str(&mut self.io).await?)?;
+        let len = u32::from_be_bytes(read_array(&mut self.io).await?) as usize;
+        if len > MAX_PACKET_LEN {
+            return Err(invalid_data("invalid packet length in capture").into());
+        }
+        let mut buf = vec![0u8; len];
+        self.io.read_exact(&mut buf).await?;
+        let packet = codec::decode_safe(&buf)?;
+        Ok(Some(CaptureRecord {
+            time,
+            peer_addr,
+            packet,
+        }))
+    }
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_str(value: &str, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+async fn read_array<R: AsyncRead + Unpin, const N: usize>(io: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    io.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+async fn read_str<R: AsyncRead + Unpin>(io: &mut R) -> io::Result<String> {
+    let len = u16::from_be_bytes(read_array(io).await?) as usize;
+    let mut buf = vec![0u8; len];
+    io.read_exact(&mut buf).await?;
+    String::from_utf8(buf).map_err(|_| invalid_data("invalid address in capture"))
+}
+
+fn parse_addr(addr: &str) -> io::Result<SocketAddr> {
+    addr.parse()
+        .map_err(|_| invalid_data("invalid address in capture"))
+}
@@ -0,0 +1,183 @@
+use async_std::task;
+use rosc::{OscBundle, OscPacket};
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::codec;
+use crate::error::Error;
+use crate::osc::OscSender;
+use crate::prelude::IntoOscPacket;
+
+/// Maximum length of a coalesced bundle, the largest UDP payload over IPv4.
+const MAX_BUNDLE_LEN: usize = 65_507;
+
+/// Length of the `#bundle` tag and the timetag at the start of a bundle.
+const BUNDLE_HEADER_LEN: usize = 16;
+
+/// Batches packets sent within a short window into a single bundle.
+///
+/// The first packet sent after a flush starts the window. All packets sent until the window
+/// elapses, to any OSC address, are sent together as one bundle with the timetag "immediately".
+/// If only a single packet was sent in the window, it is sent as is. This reduces the number of
+/// datagrams for bursty senders at the cost of up to one window of latency. Packets that do not
+/// fit into one datagram together are split into several bundles.
+///
+/// Packets are sent with [`OscSender::send`], so the socket has to be connected. Errors while
+/// flushing on the background task are logged; use [`CoalesceWindow::flush`] to send pending
+/// packets and observe errors.
+///
+/// A coalescing sender created with [`CoalesceWindow::with_capacity`] holds at most `capacity`
+/// packets per window and drops further packets until the next flush. Use
+/// [`queue_len`](CoalesceWindow::queue_len) and [`dropped_count`](CoalesceWindow::dropped_count)
+/// to tune the capacity.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{CoalesceWindow, OscSocket};
+/// use std::time::Duration;
+///
+/// let socket = OscSocket::bind("127.0.0.1:0").await?;
+/// socket.connect("127.0.0.1:8080").await?;
+/// let coalesce = CoalesceWindow::new(socket.sender(), Duration::from_millis(1));
+/// coalesce.send(("/fader/1", (0.5f32,)));
+/// coalesce.send(("/fader/2", (0.7f32,)));
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CoalesceWindow {
+    sender: OscSender,
+    window: Duration,
+    capacity: usize,
+    pending: Arc<Mutex<Pending>>,
+    dropped: Arc<AtomicU64>,
+}
+
+#[derive(Debug, Default)]
+struct Pending {
+    packets: Vec<OscPacket>,
+    /// Counts the flushed windows, so that the timer of a window that was flushed manually does
+    /// not flush the next window early.
+    generation: u64,
+}
+
+impl CoalesceWindow {
+    /// Creates a new coalescing sender that batches packets sent within `window`.
+    pub fn new(sender: OscSender, window: Duration) -> Self {
+        Self::with_capacity(sender, window, usize::MAX)
+    }
+
+    /// Creates a new coalescing sender that batches at most `capacity` packets per window.
+    ///
+    /// Packets sent while `capacity` packets are pending are dropped.
+    pub fn with_capacity(sender: OscSender, window: Duration, capacity: usize) -> Self {
+        Self {
+            sender,
+            window,
+            capacity,
+            pending: Arc::new(Mutex::new(Pending::default())),
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Returns the duration of the batching window.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Returns the number of packets waiting for the end of the current window.
+    pub fn queue_len(&self) -> usize {
+        self.pending.lock().unwrap().packets.len()
+    }
+
+    /// Returns the number of packets dropped because the queue was full.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Queues a packet to be sent at the end of the current window.
+    ///
+    /// Starts a new window if none is open. Drops the packet if the queue is full.
+    pub fn send<P: IntoOscPacket>(&self, packet: P) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.packets.len() >= self.capacity {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        pending.packets.push(packet.into_osc_packet());
+        if pending.packets.len() == 1 {
+            let this = self.clone();
+            let generation = pending.generation;
+            task::spawn(async move {
+                task::sleep(this.window).await;
+                if let Err(err) = this.flush_window(Some(generation)).await {
+                    log::debug!("Failed to send coalesced packets: {}", err);
+                }
+            });
+        }
+    }
+
+    /// Sends all pending packets right away.
+    ///
+    /// If sending fails, the remaining packets are still sent and the first error is returned.
+    pub async fn flush(&self) -> Result<(), Error> {
+        self.flush_window(None).await
+    }
+
+    /// Sends the pending packets if `generation` is `None` or the generation of the open window.
+    async fn flush_window(&self, generation: Option<u64>) -> Result<(), Error> {
+        let packets = {
+            let mut pending = self.pending.lock().unwrap();
+            if matches!(generation, Some(generation) if generation != pending.generation) {
+                return Ok(());
+            }
+            pending.generation = pending.generation.wrapping_add(1);
+            mem::take(&mut pending.packets)
+        };
+        let mut res = Ok(());
+        for mut batch in batches(packets) {
+            let sent = match batch.len() {
+                1 => self.sender.send(batch.remove(0)).await,
+                _ => {
+                    let bundle = OscBundle {
+                        timetag: (0, 1),
+                        content: batch,
+                    };
+                    self.sender.send(bundle).await
+                }
+            };
+            if res.is_ok() {
+                res = sent;
+            }
+        }
+        res
+    }
+}
+
+/// Splits `packets` into batches that fit into a datagram as a bundle.
+///
+/// A packet that is too long on its own is put into a batch by itself.
+fn batches(packets: Vec<OscPacket>) -> Vec<Vec<OscPacket>> {
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+    let mut len = BUNDLE_HEADER_LEN;
+    for packet in packets {
+        // Each element of a bundle is prefixed with its length.
+        let packet_len = 4 + codec::estimate_encoded_len(&packet);
+        if !batch.is_empty() && len + packet_len > MAX_BUNDLE_LEN {
+            batches.push(mem::take(&mut batch));
+            len = BUNDLE_HEADER_LEN;
+        }
+        len += packet_len;
+        batch.push(packet);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
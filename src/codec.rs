@@ -0,0 +1,877 @@
+use std::ops::{Deref, Range};
+
+use rosc::{OscBundle, OscError, OscMessage, OscPacket, OscType};
+
+use crate::error::Error;
+
+/// How received datagrams are decoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecodeMode {
+    /// Decode the whole datagram with [`rosc::decoder::decode`]. This is the default.
+    #[default]
+    Strict,
+    /// Decode the leading packet of the datagram and ignore any trailing bytes.
+    ///
+    /// This improves interoperability with senders that pad their datagrams. In a bundle,
+    /// elements are decoded until the first element that does not fit into the datagram or fails
+    /// to decode.
+    ///
+    /// Messages that consist of only an address, with an empty type tag string (`,`) or without
+    /// a type tag string at all, decode to messages without arguments. If the bytes after the
+    /// address do not start with `,`, they are not treated as type tags but ignored.
+    Lenient,
+}
+
+/// Maximum nesting depth of bundles accepted by [`decode_safe`].
+pub const MAX_BUNDLE_DEPTH: usize = 32;
+
+/// Maximum encoded size of packets that [`encode_small`] stores inline.
+pub const INLINE_PACKET_LEN: usize = 256;
+
+/// Maximum length of a packet that can be encoded.
+///
+/// Blob lengths are encoded as 32-bit signed integers, so longer packets cannot be represented.
+pub const MAX_ENCODED_LEN: usize = i32::MAX as usize;
+
+/// Decodes a datagram into an OSC packet with the given [`DecodeMode`].
+///
+/// Like [`decode_safe`], this never panics on malformed input.
+pub fn decode(buf: &[u8], mode: DecodeMode) -> Result<OscPacket, Error> {
+    match mode {
+        DecodeMode::Strict => decode_safe(buf),
+        DecodeMode::Lenient => decode_packet(buf).map(|(packet, _len)| packet),
+    }
+}
+
+/// Decodes an OSC packet without panicking on malformed input.
+///
+/// [`rosc::decoder::decode`] trusts the lengths and type tags in the packet, which can lead to
+/// panics or huge allocations for malformed input. This function first checks that all
+/// arguments fit into the buffer and that bundles are nested at most [`MAX_BUNDLE_DEPTH`] levels
+/// deep, and returns an error otherwise. Use it for any data received from the network.
+pub fn decode_safe(buf: &[u8]) -> Result<OscPacket, Error> {
+    validate(buf, 0)?;
+    Ok(rosc::decoder::decode(buf)?)
+}
+
+/// Decodes the OSC packet at the start of `buf`.
+///
+/// Returns the packet and the number of bytes it takes up in `buf`. Bytes after the packet are
+/// ignored.
+pub fn decode_packet(buf: &[u8]) -> Result<(OscPacket, usize), Error> {
+    match buf.first() {
+        Some(b'/') => {
+            let addr_len = skip_string(buf, 0);
+            let len = if addr_len < buf.len() && buf[addr_len] != b',' {
+                // A message without type tags, followed by other data. Some minimal senders
+                // omit the type tags, which is not conformant.
+                addr_len
+            } else {
+                measure_message(buf, None)?
+            };
+            let packet = rosc::decoder::decode(&buf[..len])?;
+            Ok((packet, len))
+        }
+        Some(b'#') => decode_bundle(buf),
+        Some(_) => Err(OscError::BadPacket("Unknown message format.").into()),
+        None => Err(OscError::BadPacket("Empty packet.").into()),
+    }
+}
+
+/// Decodes all OSC packets in a buffer of back-to-back packets.
+///
+/// Packets are decoded with [`decode_packet`] until the buffer is exhausted. After the first
+/// error, the iterator ends. Since bundles do not carry their own length, a bundle extends over
+/// all following bytes that decode as size-prefixed bundle elements.
+///
+/// ```
+/// # use async_osc::{codec, prelude::*, OscMessage};
+/// let mut buf = codec::encode(&OscMessage::new("/a", (1,)).into_osc_packet()).unwrap();
+/// buf.extend(codec::encode(&OscMessage::new("/b", (2,)).into_osc_packet()).unwrap());
+/// let packets = codec::decode_all(&buf).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(packets.len(), 2);
+/// # Ok::<(), async_osc::Error>(())
+/// ```
+pub fn decode_all(buf: &[u8]) -> impl Iterator<Item = Result<OscPacket, Error>> + '_ {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        if pos >= buf.len() {
+            return None;
+        }
+        match decode_packet(&buf[pos..]) {
+            Ok((packet, len)) => {
+                pos += len;
+                Some(Ok(packet))
+            }
+            Err(err) => {
+                pos = buf.len();
+                Some(Err(err))
+            }
+        }
+    })
+}
+
+/// Decodes an OSC message and returns the byte range of each of its arguments in `buf`.
+///
+/// This is useful for tools like packet viewers that highlight the bytes of each argument. The
+/// range of an array spans all of its elements. Arguments without data, like booleans, have an
+/// empty range at their position.
+///
+/// ```
+/// # use async_osc::{codec, prelude::*, OscMessage};
+/// let buf = codec::encode(&OscMessage::new("/a", (1, 2.0f32)).into_osc_packet()).unwrap();
+/// let (message, spans) = codec::decode_with_spans(&buf)?;
+/// assert_eq!(spans, vec![8..12, 12..16]);
+/// # Ok::<(), async_osc::Error>(())
+/// ```
+pub fn decode_with_spans(buf: &[u8]) -> Result<(OscMessage, Vec<Range<usize>>), Error> {
+    let message = match decode_safe(buf)? {
+        OscPacket::Message(message) => message,
+        OscPacket::Bundle(_) => {
+            return Err(OscError::BadMessage("Expected a message, got a bundle").into())
+        }
+    };
+    let mut spans = Vec::with_capacity(message.args.len());
+    measure_message(buf, Some(&mut spans))?;
+    Ok((message, spans))
+}
+
+/// Decodes only the address and the type tags of an OSC message.
+///
+/// The type tags are returned without the leading comma, like in
+/// [`OscMessageExt::matches`](crate::prelude::OscMessageExt::matches). Argument values are not
+/// decoded or validated, which makes this much cheaper than a full decode. Routers can use it to
+/// skip messages they have no route for before decoding them with [`decode`]. A message without a
+/// type tag string has empty type tags. Returns an error for bundles.
+///
+/// ```
+/// # use async_osc::{codec, prelude::*, OscMessage};
+/// let packet = OscMessage::new("/label", (0.5f32, "main")).into_osc_packet();
+/// let buf = codec::encode(&packet).unwrap();
+/// let (addr, tags) = codec::decode_header(&buf)?;
+/// assert_eq!(addr, "/label");
+/// assert_eq!(tags, "fs");
+/// # Ok::<(), async_osc::Error>(())
+/// ```
+pub fn decode_header(buf: &[u8]) -> Result<(String, String), Error> {
+    match buf.first() {
+        Some(b'/') => {}
+        Some(b'#') => return Err(OscError::BadMessage("Expected a message, got a bundle").into()),
+        Some(_) => return Err(OscError::BadPacket("Unknown message format.").into()),
+        None => return Err(OscError::BadPacket("Empty packet.").into()),
+    }
+    let addr = read_header_string(buf, 0)?;
+    let tags_start = skip_string(buf, 0);
+    let tags = match buf.get(tags_start) {
+        Some(b',') => read_header_string(buf, tags_start + 1)?,
+        _ => String::new(),
+    };
+    Ok((addr, tags))
+}
+
+/// Reads the nul-terminated string starting at `pos`.
+fn read_header_string(buf: &[u8], pos: usize) -> Result<String, Error> {
+    let bytes = &buf[pos..];
+    let len = bytes
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or(OscError::BadString("String is not nul-terminated"))?;
+    String::from_utf8(bytes[..len].to_vec()).map_err(|err| OscError::StringError(err).into())
+}
+
+/// Returns the number of bytes `packet` takes up when encoded.
+///
+/// The returned length is exact for the encoding produced by [`encode_into`] (which is the same
+/// as the one produced by [`rosc::encoder::encode`]). It can be used to pre-allocate buffers.
+pub fn estimate_encoded_len(packet: &OscPacket) -> usize {
+    match packet {
+        OscPacket::Message(message) => message_len(message),
+        OscPacket::Bundle(bundle) => bundle_len(bundle),
+    }
+}
+
+/// Encodes an OSC packet into a newly allocated buffer.
+///
+/// The buffer is allocated with the exact size of the encoded packet. Returns
+/// [`Error::DatagramTooLarge`] if the packet is longer than [`MAX_ENCODED_LEN`].
+pub fn encode(packet: &OscPacket) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::with_capacity(checked_len(packet)?);
+    write_packet(packet, &mut buf);
+    Ok(buf)
+}
+
+/// Encodes an OSC packet by appending it to `buf`.
+///
+/// The buffer is grown at most once, by the exact number of bytes needed. If the buffer already
+/// has enough spare capacity it is not reallocated. Returns [`Error::DatagramTooLarge`] and
+/// leaves `buf` unchanged if the packet is longer than [`MAX_ENCODED_LEN`].
+pub fn encode_into(packet: &OscPacket, buf: &mut Vec<u8>) -> Result<(), Error> {
+    buf.reserve(checked_len(packet)?);
+    write_packet(packet, buf);
+    Ok(())
+}
+
+/// Encodes an OSC packet without allocating if it is small.
+///
+/// Packets of up to [`INLINE_PACKET_LEN`] bytes are encoded into an inline buffer. Larger packets
+/// are encoded into a heap buffer like with [`encode`]. This is used on the send path, where most
+/// packets are small control messages.
+pub fn encode_small(packet: &OscPacket) -> Result<EncodedPacket, Error> {
+    let len = checked_len(packet)?;
+    if len <= INLINE_PACKET_LEN {
+        let mut buf = InlineBuf {
+            bytes: [0; INLINE_PACKET_LEN],
+            len: 0,
+        };
+        write_packet(packet, &mut buf);
+        Ok(EncodedPacket(Repr::Inline(buf)))
+    } else {
+        let mut buf = Vec::with_capacity(len);
+        write_packet(packet, &mut buf);
+        Ok(EncodedPacket(Repr::Heap(buf)))
+    }
+}
+
+/// Returns the encoded length of `packet`, or an error if it cannot be encoded.
+fn checked_len(packet: &OscPacket) -> Result<usize, Error> {
+    let len = estimate_encoded_len(packet);
+    if len > MAX_ENCODED_LEN {
+        return Err(Error::DatagramTooLarge { sent: 0, len });
+    }
+    Ok(len)
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes an OSC packet as a base64 string.
+///
+/// Uses the standard alphabet with padding. This is useful to embed packets in text formats like
+/// logs, JSON or URLs.
+///
+/// ```
+/// # use async_osc::{codec, prelude::*, OscMessage};
+/// let packet = OscMessage::new("/ping", ()).into_osc_packet();
+/// assert_eq!(codec::encode_base64(&packet).unwrap(), "L3BpbmcAAAAsAAAA");
+/// ```
+pub fn encode_base64(packet: &OscPacket) -> Result<String, Error> {
+    let buf = encode_small(packet)?;
+    let mut out = String::with_capacity(buf.len().div_ceil(3) * 4);
+    for chunk in buf.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as usize) << 16 | (b[1] as usize) << 8 | b[2] as usize;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[n >> (18 - 6 * i) & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Decodes an OSC packet from a base64 string created with [`encode_base64`].
+///
+/// Padding is optional. The packet is decoded with [`decode_safe`].
+pub fn decode_base64(text: &str) -> Result<OscPacket, Error> {
+    let invalid = || Error::Osc(OscError::BadPacket("invalid base64"));
+    let text = text.trim_end_matches('=').as_bytes();
+    if text.len() % 4 == 1 {
+        return Err(invalid());
+    }
+    let mut buf = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.chunks(4) {
+        let mut n = 0;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = BASE64_ALPHABET
+                .iter()
+                .position(|a| a == c)
+                .ok_or_else(invalid)?;
+            n |= value << (18 - 6 * i);
+        }
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        buf.extend_from_slice(&bytes[..chunk.len() - 1]);
+    }
+    decode_safe(&buf)
+}
+
+/// Encodes an OSC packet as a lowercase hex string.
+///
+/// ```
+/// # use async_osc::{codec, prelude::*, OscMessage};
+/// let packet = OscMessage::new("/a", ()).into_osc_packet();
+/// assert_eq!(codec::encode_hex(&packet).unwrap(), "2f6100002c000000");
+/// ```
+pub fn encode_hex(packet: &OscPacket) -> Result<String, Error> {
+    let hex = encode_small(packet)?
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Ok(hex)
+}
+
+/// Decodes an OSC packet from a hex string created with [`encode_hex`].
+///
+/// Both lowercase and uppercase digits are accepted. The packet is decoded with [`decode_safe`].
+pub fn decode_hex(text: &str) -> Result<OscPacket, Error> {
+    let invalid = || Error::Osc(OscError::BadPacket("invalid hex"));
+    if text.len() % 2 == 1 || !text.is_ascii() {
+        return Err(invalid());
+    }
+    let buf = (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    decode_safe(&buf)
+}
+
+/// Formats bytes as an annotated hex and ASCII dump.
+///
+/// Each line shows the offset, 16 bytes in hex and the same bytes as ASCII, with `.` for
+/// non-printable bytes. The hex bytes are grouped by four, which makes the 4-byte alignment of OSC
+/// strings and arguments visible.
+///
+/// ```
+/// # use async_osc::{codec, prelude::*, OscMessage};
+/// let buf = codec::encode(&OscMessage::new("/ping", (7,)).into_osc_packet()).unwrap();
+/// assert_eq!(
+///     codec::hexdump(&buf),
+///     "0000  2f 70 69 6e  67 00 00 00  2c 69 00 00  00 00 00 07  |/ping...,i......|"
+/// );
+/// ```
+pub fn hexdump(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (i, line) in bytes.chunks(16).enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("{:04x} ", i * 16));
+        for j in 0..16 {
+            if j % 4 == 0 {
+                out.push(' ');
+            }
+            match line.get(j) {
+                Some(byte) => out.push_str(&format!("{:02x} ", byte)),
+                None => out.push_str("   "),
+            }
+        }
+        out.push_str(" |");
+        for byte in line {
+            let c = *byte as char;
+            out.push(if c.is_ascii_graphic() || c == ' ' {
+                c
+            } else {
+                '.'
+            });
+        }
+        out.push('|');
+    }
+    out
+}
+
+/// An encoded OSC packet returned from [`encode_small`].
+///
+/// Dereferences to the encoded bytes.
+#[derive(Clone, Debug)]
+pub struct EncodedPacket(Repr);
+
+// Boxing the inline buffer would defeat its purpose.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+enum Repr {
+    Inline(InlineBuf),
+    Heap(Vec<u8>),
+}
+
+impl EncodedPacket {
+    /// Returns `true` if the packet is stored inline, without a heap allocation.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline(_))
+    }
+}
+
+impl Deref for EncodedPacket {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        match &self.0 {
+            Repr::Inline(buf) => &buf.bytes[..buf.len],
+            Repr::Heap(buf) => buf,
+        }
+    }
+}
+
+impl AsRef<[u8]> for EncodedPacket {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+/// An OSC message with a precomputed address and type tag string.
+///
+/// For messages with a fixed shape, the encoded address and type tags are stored once, so that
+/// only the arguments are written for each send. See [`OscSocket::send_compiled`].
+///
+/// ```
+/// # use async_osc::{codec::{self, CompiledMessage}, prelude::*, OscMessage, OscType};
+/// let compiled = CompiledMessage::new("/volume", ",if").unwrap();
+/// let args = [OscType::Int(1), OscType::Float(0.5)];
+/// let encoded = compiled.encode(&args).unwrap();
+/// let packet = OscMessage::new("/volume", (1, 0.5f32)).into_osc_packet();
+/// let expected = codec::encode(&packet).unwrap();
+/// assert_eq!(&encoded[..], &expected[..]);
+/// ```
+///
+/// [`OscSocket::send_compiled`]: crate::OscSocket::send_compiled
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompiledMessage {
+    prefix: Vec<u8>,
+    tags: Vec<u8>,
+}
+
+impl CompiledMessage {
+    /// Compiles a message with address `addr` and the type tags `tags`.
+    ///
+    /// The leading comma of `tags` is optional. Returns an error if `tags` contains unknown type
+    /// tags or unbalanced array brackets.
+    pub fn new(addr: &str, tags: &str) -> Result<Self, Error> {
+        let tags = tags.strip_prefix(',').unwrap_or(tags).as_bytes();
+        let mut depth = 0usize;
+        for tag in tags {
+            match tag {
+                b'[' => depth += 1,
+                b']' if depth > 0 => depth -= 1,
+                b'i' | b'f' | b's' | b'b' | b't' | b'h' | b'd' | b'c' | b'r' | b'm' | b'T'
+                | b'F' | b'N' | b'I' => {}
+                _ => {
+                    let message = format!("Invalid type tag {:?}", *tag as char);
+                    return Err(OscError::BadArg(message).into());
+                }
+            }
+        }
+        if depth > 0 {
+            return Err(OscError::BadArg("Unclosed array type tag".to_string()).into());
+        }
+        let mut prefix = Vec::with_capacity(string_len(addr.len()) + string_len(tags.len() + 1));
+        write_string(addr.as_bytes(), &mut prefix);
+        let start = prefix.len();
+        prefix.push(b',');
+        prefix.extend_from_slice(tags);
+        terminate_string(start, &mut prefix);
+        Ok(Self {
+            prefix,
+            tags: tags.to_vec(),
+        })
+    }
+
+    /// Returns the encoded address and type tags.
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// Encodes a message with the given arguments.
+    ///
+    /// Returns an error if the types of `args` do not match the compiled type tags.
+    pub fn encode(&self, args: &[OscType]) -> Result<EncodedPacket, Error> {
+        let mut pos = 0;
+        if !match_tags(args, &self.tags, &mut pos) || pos != self.tags.len() {
+            let message = "Arguments do not match the compiled type tags".to_string();
+            return Err(OscError::BadArg(message).into());
+        }
+        let len = self.prefix.len() + args.iter().map(arg_len).sum::<usize>();
+        if len > MAX_ENCODED_LEN {
+            return Err(Error::DatagramTooLarge { sent: 0, len });
+        }
+        let repr = if len <= INLINE_PACKET_LEN {
+            let mut buf = InlineBuf {
+                bytes: [0; INLINE_PACKET_LEN],
+                len: 0,
+            };
+            write_compiled(&self.prefix, args, &mut buf);
+            Repr::Inline(buf)
+        } else {
+            let mut buf = Vec::with_capacity(len);
+            write_compiled(&self.prefix, args, &mut buf);
+            Repr::Heap(buf)
+        };
+        Ok(EncodedPacket(repr))
+    }
+}
+
+fn write_compiled<B: WriteBuf>(prefix: &[u8], args: &[OscType], buf: &mut B) {
+    buf.extend_from_slice(prefix);
+    for arg in args {
+        write_arg(arg, buf);
+    }
+}
+
+/// Checks that `args` have the type tags in `tags`, starting at `pos`.
+fn match_tags(args: &[OscType], tags: &[u8], pos: &mut usize) -> bool {
+    for arg in args {
+        let ok = match arg {
+            OscType::Array(array) => {
+                tags.get(*pos) == Some(&b'[') && {
+                    *pos += 1;
+                    match_tags(&array.content, tags, pos) && tags.get(*pos) == Some(&b']')
+                }
+            }
+            arg => tags.get(*pos) == Some(&tag(arg)),
+        };
+        if !ok {
+            return false;
+        }
+        *pos += 1;
+    }
+    true
+}
+
+#[derive(Clone, Debug)]
+struct InlineBuf {
+    bytes: [u8; INLINE_PACKET_LEN],
+    len: usize,
+}
+
+/// A growable byte buffer the encoder writes into.
+trait WriteBuf {
+    fn len(&self) -> usize;
+    fn extend_from_slice(&mut self, bytes: &[u8]);
+    /// Truncates or zero-extends the buffer to `len` bytes.
+    fn resize(&mut self, len: usize);
+    fn as_mut_slice(&mut self) -> &mut [u8];
+
+    fn push(&mut self, byte: u8) {
+        self.extend_from_slice(&[byte]);
+    }
+}
+
+impl WriteBuf for Vec<u8> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        Vec::extend_from_slice(self, bytes)
+    }
+    fn resize(&mut self, len: usize) {
+        Vec::resize(self, len, 0)
+    }
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+    fn push(&mut self, byte: u8) {
+        Vec::push(self, byte)
+    }
+}
+
+impl WriteBuf for InlineBuf {
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        let end = self.len + bytes.len();
+        self.bytes[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+    }
+    fn resize(&mut self, len: usize) {
+        if len > self.len {
+            self.bytes[self.len..len].fill(0);
+        }
+        self.len = len;
+    }
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.bytes[..self.len]
+    }
+}
+
+fn decode_bundle(buf: &[u8]) -> Result<(OscPacket, usize), Error> {
+    if buf.len() < 16 || &buf[..8] != b"#bundle\0" {
+        return Err(OscError::BadBundle("Invalid bundle header".to_string()).into());
+    }
+    let timetag = (read_u32(buf, 8)?, read_u32(buf, 12)?);
+    let mut content = vec![];
+    let mut pos = 16;
+    while let Ok(size) = read_u32(buf, pos) {
+        let start = pos + 4;
+        let end = start + size as usize;
+        if end > buf.len() {
+            break;
+        }
+        match decode_safe(&buf[start..end]) {
+            Ok(packet) => content.push(packet),
+            Err(_) => break,
+        }
+        pos = end;
+    }
+    Ok((OscPacket::Bundle(OscBundle { timetag, content }), pos))
+}
+
+/// Checks that decoding `buf` with rosc cannot panic.
+///
+/// Inputs that rosc rejects with an error on its own are passed through.
+fn validate(buf: &[u8], depth: usize) -> Result<(), Error> {
+    match buf.first() {
+        Some(b'/') => measure_message(buf, None).map(|_len| ()),
+        Some(b'#') => validate_bundle(buf, depth),
+        _ => Ok(()),
+    }
+}
+
+fn validate_bundle(buf: &[u8], depth: usize) -> Result<(), Error> {
+    if depth >= MAX_BUNDLE_DEPTH {
+        return Err(OscError::BadBundle("Bundles nested too deeply".to_string()).into());
+    }
+    if buf.len() < 16 || &buf[..8] != b"#bundle\0" {
+        return Ok(());
+    }
+    // Mirror the element walk of rosc's bundle decoder.
+    let mut pos = 16;
+    while pos < buf.len() {
+        let size = match read_u32(buf, pos) {
+            Ok(size) => size as usize,
+            Err(_) => return Ok(()),
+        };
+        let start = pos + 4;
+        let end = start + size;
+        if end > buf.len() {
+            break;
+        }
+        validate(&buf[start..end], depth + 1)?;
+        pos = end;
+    }
+    Ok(())
+}
+
+/// Returns the length of the OSC message at the start of `buf`.
+///
+/// If `spans` is set, the byte range of each top-level argument is pushed to it.
+fn measure_message(buf: &[u8], mut spans: Option<&mut Vec<Range<usize>>>) -> Result<usize, Error> {
+    let mut pos = skip_string(buf, 0);
+    if pos >= buf.len() {
+        // A message without type tags.
+        return Ok(buf.len());
+    }
+    let tags_start = pos;
+    pos = skip_string(buf, pos);
+    let tags = &buf[tags_start..];
+    let tags = &tags[..tags.iter().position(|b| *b == 0).unwrap_or(tags.len())];
+    let mut depth = 0usize;
+    let mut array_start = pos;
+    for tag in tags.iter().skip(1) {
+        let start = pos;
+        pos = match tag {
+            b'i' | b'f' | b'c' | b'r' | b'm' => pos + 4,
+            b'h' | b'd' | b't' => pos + 8,
+            b's' => skip_string(buf, pos),
+            b'b' => pos + 4 + pad(read_u32(buf, pos)? as usize),
+            b'T' | b'F' | b'N' | b'I' | b'[' | b']' => pos,
+            tag => {
+                let message = format!("Type tag \"{}\" is not implemented!", *tag as char);
+                return Err(OscError::BadArg(message).into());
+            }
+        };
+        if pos > buf.len() {
+            return Err(OscError::BadMessage("Message shorter than its type tags").into());
+        }
+        if let Some(spans) = spans.as_mut() {
+            match tag {
+                b'[' => {
+                    if depth == 0 {
+                        array_start = start;
+                    }
+                    depth += 1;
+                }
+                b']' if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 {
+                        spans.push(array_start..pos);
+                    }
+                }
+                _ if depth == 0 => spans.push(start..pos),
+                _ => {}
+            }
+        }
+    }
+    Ok(pos)
+}
+
+/// Returns the position after the padded, nul-terminated string starting at `pos`.
+///
+/// Like rosc, a string that is not nul-terminated extends to the end of the buffer.
+fn skip_string(buf: &[u8], pos: usize) -> usize {
+    match buf[pos..].iter().position(|b| *b == 0) {
+        Some(nul) => buf.len().min(pos + string_len(nul)),
+        None => buf.len(),
+    }
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> Result<u32, Error> {
+    match buf.get(pos..pos + 4) {
+        Some(bytes) => Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        None => Err(OscError::BadPacket("Unexpected end of packet").into()),
+    }
+}
+
+fn pad(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn string_len(len: usize) -> usize {
+    pad(len + 1)
+}
+
+fn message_len(message: &OscMessage) -> usize {
+    let tags_len: usize = message.args.iter().map(tag_len).sum();
+    let args_len: usize = message.args.iter().map(arg_len).sum();
+    string_len(message.addr.len()) + string_len(1 + tags_len) + args_len
+}
+
+fn bundle_len(bundle: &OscBundle) -> usize {
+    let content_len: usize = bundle
+        .content
+        .iter()
+        .map(|packet| 4 + estimate_encoded_len(packet))
+        .sum();
+    // "#bundle\0" and the timetag.
+    8 + 8 + content_len
+}
+
+fn tag_len(arg: &OscType) -> usize {
+    match arg {
+        OscType::Array(array) => 2 + array.content.iter().map(tag_len).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+fn arg_len(arg: &OscType) -> usize {
+    match arg {
+        OscType::Int(_)
+        | OscType::Float(_)
+        | OscType::Char(_)
+        | OscType::Midi(_)
+        | OscType::Color(_) => 4,
+        OscType::Long(_) | OscType::Double(_) | OscType::Time(_) => 8,
+        OscType::String(string) => string_len(string.len()),
+        OscType::Blob(blob) => 4 + pad(blob.len()),
+        OscType::Bool(_) | OscType::Nil | OscType::Inf => 0,
+        OscType::Array(array) => array.content.iter().map(arg_len).sum(),
+    }
+}
+
+fn write_packet<B: WriteBuf>(packet: &OscPacket, buf: &mut B) {
+    match packet {
+        OscPacket::Message(message) => write_message(message, buf),
+        OscPacket::Bundle(bundle) => write_bundle(bundle, buf),
+    }
+}
+
+fn write_message<B: WriteBuf>(message: &OscMessage, buf: &mut B) {
+    write_string(message.addr.as_bytes(), buf);
+    let start = buf.len();
+    buf.push(b',');
+    for arg in &message.args {
+        write_tag(arg, buf);
+    }
+    terminate_string(start, buf);
+    for arg in &message.args {
+        write_arg(arg, buf);
+    }
+}
+
+fn write_bundle<B: WriteBuf>(bundle: &OscBundle, buf: &mut B) {
+    write_string(b"#bundle", buf);
+    buf.extend_from_slice(&bundle.timetag.0.to_be_bytes());
+    buf.extend_from_slice(&bundle.timetag.1.to_be_bytes());
+    for packet in &bundle.content {
+        let start = buf.len();
+        buf.extend_from_slice(&[0u8; 4]);
+        write_packet(packet, buf);
+        let len = (buf.len() - start - 4) as u32;
+        buf.as_mut_slice()[start..start + 4].copy_from_slice(&len.to_be_bytes());
+    }
+}
+
+fn write_string<B: WriteBuf>(string: &[u8], buf: &mut B) {
+    let start = buf.len();
+    buf.extend_from_slice(string);
+    terminate_string(start, buf);
+}
+
+fn terminate_string<B: WriteBuf>(start: usize, buf: &mut B) {
+    let len = buf.len() - start;
+    buf.resize(start + string_len(len));
+}
+
+/// Returns the type tags of `args`, without the leading comma.
+pub(crate) fn type_tags(args: &[OscType]) -> String {
+    let mut tags = Vec::with_capacity(args.len());
+    for arg in args {
+        write_tag(arg, &mut tags);
+    }
+    // Type tags are always ASCII.
+    String::from_utf8(tags).unwrap()
+}
+
+fn write_tag<B: WriteBuf>(arg: &OscType, buf: &mut B) {
+    if let OscType::Array(array) = arg {
+        buf.push(b'[');
+        for arg in &array.content {
+            write_tag(arg, buf);
+        }
+        buf.push(b']');
+    } else {
+        buf.push(tag(arg));
+    }
+}
+
+/// Returns the type tag of a non-array argument.
+fn tag(arg: &OscType) -> u8 {
+    match arg {
+        OscType::Int(_) => b'i',
+        OscType::Float(_) => b'f',
+        OscType::String(_) => b's',
+        OscType::Blob(_) => b'b',
+        OscType::Time(_) => b't',
+        OscType::Long(_) => b'h',
+        OscType::Double(_) => b'd',
+        OscType::Char(_) => b'c',
+        OscType::Color(_) => b'r',
+        OscType::Midi(_) => b'm',
+        OscType::Bool(true) => b'T',
+        OscType::Bool(false) => b'F',
+        OscType::Nil => b'N',
+        OscType::Inf => b'I',
+        OscType::Array(_) => unreachable!("arrays have no single type tag"),
+    }
+}
+
+fn write_arg<B: WriteBuf>(arg: &OscType, buf: &mut B) {
+    match arg {
+        OscType::Int(x) => buf.extend_from_slice(&x.to_be_bytes()),
+        OscType::Float(x) => buf.extend_from_slice(&x.to_be_bytes()),
+        OscType::Long(x) => buf.extend_from_slice(&x.to_be_bytes()),
+        OscType::Double(x) => buf.extend_from_slice(&x.to_be_bytes()),
+        OscType::Char(x) => buf.extend_from_slice(&(*x as u32).to_be_bytes()),
+        OscType::String(x) => write_string(x.as_bytes(), buf),
+        OscType::Blob(x) => {
+            buf.extend_from_slice(&(x.len() as i32).to_be_bytes());
+            let start = buf.len();
+            buf.extend_from_slice(x);
+            buf.resize(start + pad(x.len()));
+        }
+        OscType::Time((sec, frac)) => {
+            buf.extend_from_slice(&sec.to_be_bytes());
+            buf.extend_from_slice(&frac.to_be_bytes());
+        }
+        OscType::Midi(x) => buf.extend_from_slice(&[x.port, x.status, x.data1, x.data2]),
+        OscType::Color(x) => buf.extend_from_slice(&[x.red, x.green, x.blue, x.alpha]),
+        OscType::Bool(_) | OscType::Nil | OscType::Inf => {}
+        OscType::Array(array) => {
+            for arg in &array.content {
+                write_arg(arg, buf);
+            }
+        }
+    }
+}
@@ -10,6 +10,51 @@ pub enum Error {
     /// OSC decode error
     #[error("Decode OSC packet failed")]
     Osc(rosc::OscError),
+    /// A packet sent on a connected socket was only partially written.
+    #[error("UDP packet not fully sent: {sent} of {len} bytes written")]
+    ShortSend {
+        /// Number of bytes that were sent.
+        sent: usize,
+        /// Length of the encoded packet.
+        len: usize,
+    },
+    /// A packet sent to an explicit address was truncated.
+    ///
+    /// This usually means that the packet exceeds the maximum datagram size for the target.
+    #[error("UDP datagram truncated: {sent} of {len} bytes sent, packet may be too large")]
+    DatagramTooLarge {
+        /// Number of bytes that were sent.
+        sent: usize,
+        /// Length of the encoded packet.
+        len: usize,
+    },
+    /// The target address has an address family the socket cannot send to.
+    ///
+    /// This happens when sending from an IPv4 socket to an IPv6 address, or from an IPv6-only
+    /// socket to an IPv4 address.
+    #[error("cannot send from {local} to {target}: address family mismatch")]
+    AddressFamilyMismatch {
+        /// Local address of the socket.
+        local: std::net::SocketAddr,
+        /// Address the packet was sent to.
+        target: std::net::SocketAddr,
+    },
+    /// The arguments of a message do not have the expected type tags.
+    ///
+    /// Returned by
+    /// [`OscMessageExt::validate_signature`](crate::prelude::OscMessageExt::validate_signature).
+    #[error("argument types of {addr} do not match: expected \",{expected}\", got \",{actual}\"")]
+    SignatureMismatch {
+        /// Address of the message.
+        addr: String,
+        /// Expected type tags, without the leading comma.
+        expected: String,
+        /// Type tags of the message arguments, without the leading comma.
+        actual: String,
+    },
+    /// The operation is not supported by this transport or on this platform.
+    #[error("{0} is not supported")]
+    Unsupported(&'static str),
 }
 
 impl From<rosc::OscError> for Error {
@@ -18,5 +63,47 @@ impl From<rosc::OscError> for Error {
     }
 }
 
+impl Error {
+    /// Returns `true` if this error indicates that the peer refused the connection.
+    ///
+    /// On some platforms, sending on a connected UDP socket to a port where nobody is listening
+    /// makes a subsequent send or receive fail with [`std::io::ErrorKind::ConnectionRefused`].
+    /// This usually means the peer is down and can be used, for example, to mark a device as
+    /// offline.
+    pub fn is_connection_refused(&self) -> bool {
+        self.io_kind() == Some(std::io::ErrorKind::ConnectionRefused)
+    }
+
+    /// Returns `true` if receiving can continue after this error.
+    ///
+    /// This is the case for packets that failed to decode and for transient IO errors, like ICMP
+    /// errors for earlier sends. Loops that receive from a socket skip these errors and only stop
+    /// on persistent socket errors.
+    #[cfg(feature = "net")]
+    pub(crate) fn is_recoverable(&self) -> bool {
+        match self {
+            Error::Osc(_) => true,
+            Error::Io(err) => crate::udp::is_transient(err),
+            _ => false,
+        }
+    }
+
+    /// Returns the kind of the underlying IO error, if this error wraps one.
+    ///
+    /// ```
+    /// # use async_osc::Error;
+    /// use std::io;
+    ///
+    /// let err = Error::from(io::Error::from(io::ErrorKind::TimedOut));
+    /// assert_eq!(err.io_kind(), Some(io::ErrorKind::TimedOut));
+    /// ```
+    pub fn io_kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            Error::Io(err) => Some(err.kind()),
+            _ => None,
+        }
+    }
+}
+
 /// Result type for OSC operations.
 pub type Result<T> = std::result::Result<T, Error>;
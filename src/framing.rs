@@ -0,0 +1,288 @@
+use async_std::stream::Stream;
+use futures_lite::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use futures_lite::ready;
+use rosc::{OscError, OscPacket};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::codec;
+use crate::error::Error;
+use crate::prelude::IntoOscPacket;
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+/// Default maximum length of a received frame, see [`OscStream::set_max_frame_len`].
+pub const DEFAULT_MAX_FRAME_LEN: usize = 1024 * 1024;
+
+/// How OSC packets are delimited on a byte stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Framing {
+    /// Each packet is prefixed with its length as a big-endian 32 bit integer, as specified by
+    /// OSC 1.0 for stream-based transports.
+    LengthPrefixed,
+    /// Packets are delimited with SLIP (RFC 1055) double-ended framing, as specified by OSC 1.1.
+    Slip,
+}
+
+impl Framing {
+    /// Appends `packet` to `buf` in this framing.
+    fn write_frame(self, packet: &[u8], buf: &mut Vec<u8>) {
+        match self {
+            Framing::LengthPrefixed => {
+                buf.extend_from_slice(&(packet.len() as u32).to_be_bytes());
+                buf.extend_from_slice(packet);
+            }
+            Framing::Slip => {
+                buf.push(SLIP_END);
+                for byte in packet {
+                    match *byte {
+                        SLIP_END => buf.extend_from_slice(&[SLIP_ESC, SLIP_ESC_END]),
+                        SLIP_ESC => buf.extend_from_slice(&[SLIP_ESC, SLIP_ESC_ESC]),
+                        byte => buf.push(byte),
+                    }
+                }
+                buf.push(SLIP_END);
+            }
+        }
+    }
+
+    /// Removes the first complete frame from `buf` and returns its payload.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the frame is longer than
+    /// `max_len`, or if `buf` holds more than `max_len` bytes of an unterminated SLIP frame. The
+    /// oversized frame is left at the start of `buf`.
+    fn take_frame(self, buf: &mut Vec<u8>, max_len: usize) -> Result<Option<Vec<u8>>, Error> {
+        match self {
+            Framing::LengthPrefixed => {
+                if buf.len() < 4 {
+                    return Ok(None);
+                }
+                let len = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+                if len > max_len {
+                    return Err(frame_too_long(len, max_len));
+                }
+                if buf.len() < 4 + len {
+                    return Ok(None);
+                }
+                let frame = buf[4..4 + len].to_vec();
+                buf.drain(..4 + len);
+                Ok(Some(frame))
+            }
+            Framing::Slip => loop {
+                let end = match position_of_end(buf) {
+                    Some(end) if end > max_len => return Err(frame_too_long(end, max_len)),
+                    Some(end) => end,
+                    None if buf.len() > max_len => return Err(frame_too_long(buf.len(), max_len)),
+                    None => return Ok(None),
+                };
+                let frame: Vec<u8> = buf.drain(..=end).take(end).collect();
+                // Empty frames occur between back-to-back END bytes.
+                if !frame.is_empty() {
+                    return slip_unescape(&frame).map(Some);
+                }
+            },
+        }
+    }
+}
+
+fn position_of_end(buf: &[u8]) -> Option<usize> {
+    buf.iter().position(|byte| *byte == SLIP_END)
+}
+
+fn frame_too_long(len: usize, max_len: usize) -> Error {
+    let message = format!(
+        "frame of {} bytes exceeds the maximum of {} bytes",
+        len, max_len
+    );
+    io::Error::new(io::ErrorKind::InvalidData, message).into()
+}
+
+fn slip_unescape(frame: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut packet = Vec::with_capacity(frame.len());
+    let mut bytes = frame.iter();
+    while let Some(byte) = bytes.next() {
+        match *byte {
+            SLIP_ESC => match bytes.next() {
+                Some(&SLIP_ESC_END) => packet.push(SLIP_END),
+                Some(&SLIP_ESC_ESC) => packet.push(SLIP_ESC),
+                _ => return Err(OscError::BadPacket("Invalid SLIP escape sequence").into()),
+            },
+            byte => packet.push(byte),
+        }
+    }
+    Ok(packet)
+}
+
+/// An OSC endpoint over any duplex byte stream.
+///
+/// `OscStream` turns anything that implements [`AsyncRead`] and [`AsyncWrite`] (a TCP stream, a
+/// serial port, a pipe or an in-memory buffer) into an endpoint that sends and receives OSC
+/// packets, delimited with the given [`Framing`]. Received packets are yielded from the
+/// [`Stream`] implementation.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{Framing, OscStream};
+/// use async_std::net::TcpStream;
+/// use async_std::stream::StreamExt;
+///
+/// let tcp = TcpStream::connect("127.0.0.1:9000").await?;
+/// let mut stream = OscStream::new(tcp, Framing::LengthPrefixed);
+/// stream.send(("/status", ())).await?;
+/// let reply = stream.next().await;
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Debug)]
+pub struct OscStream<T> {
+    io: T,
+    framing: Framing,
+    read_buf: Vec<u8>,
+    write_buf: Vec<u8>,
+    max_frame_len: usize,
+    state: ReadState,
+}
+
+/// How received bytes are handled after an oversized frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ReadState {
+    Reading,
+    /// The rest of an oversized SLIP frame is dropped up to the next END byte.
+    SkipFrame,
+    /// The length prefix of an oversized frame was read, the stream cannot be resynchronized.
+    Failed,
+}
+
+impl<T> OscStream<T> {
+    /// Creates a new OSC endpoint over `io` with the given framing.
+    pub fn new(io: T, framing: Framing) -> Self {
+        Self {
+            io,
+            framing,
+            read_buf: Vec::new(),
+            write_buf: Vec::new(),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            state: ReadState::Reading,
+        }
+    }
+
+    /// Sets the maximum length of a received frame in bytes.
+    ///
+    /// Frames are buffered until they are complete, so without a limit a peer could make the
+    /// stream buffer arbitrary amounts of data. A longer frame is yielded as an error of kind
+    /// [`io::ErrorKind::InvalidData`]. With [`Framing::Slip`], the limit applies to the escaped
+    /// frame, and the rest of the frame is dropped. With [`Framing::LengthPrefixed`], the stream
+    /// cannot find the next frame and ends after the error. Defaults to
+    /// [`DEFAULT_MAX_FRAME_LEN`].
+    pub fn set_max_frame_len(&mut self, max_frame_len: usize) {
+        self.max_frame_len = max_frame_len;
+    }
+
+    /// Returns the maximum length of a received frame in bytes.
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Returns the framing used on this stream.
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Get a reference to the underlying byte stream.
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    /// Get a mutable reference to the underlying byte stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io
+    }
+
+    /// Consumes the endpoint, returning the underlying byte stream.
+    ///
+    /// Any buffered bytes of partially received packets are lost.
+    pub fn into_inner(self) -> T {
+        self.io
+    }
+}
+
+impl<T: AsyncWrite + Unpin> OscStream<T> {
+    /// Sends a packet on the stream.
+    ///
+    /// The framed packet is written completely and the stream is flushed.
+    pub async fn send<P: IntoOscPacket>(&mut self, packet: P) -> Result<(), Error> {
+        let packet = codec::encode_small(&packet.into_osc_packet())?;
+        self.write_buf.clear();
+        self.framing.write_frame(&packet, &mut self.write_buf);
+        self.io.write_all(&self.write_buf).await?;
+        self.io.flush().await?;
+        Ok(())
+    }
+}
+
+impl<T: AsyncRead + Unpin> Stream for OscStream<T> {
+    type Item = Result<OscPacket, Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if this.state == ReadState::Failed {
+                return Poll::Ready(None);
+            }
+            if this.state == ReadState::SkipFrame {
+                match position_of_end(&this.read_buf) {
+                    Some(end) => {
+                        this.read_buf.drain(..=end);
+                        this.state = ReadState::Reading;
+                    }
+                    None => this.read_buf.clear(),
+                }
+            }
+            if this.state == ReadState::Reading {
+                match this
+                    .framing
+                    .take_frame(&mut this.read_buf, this.max_frame_len)
+                {
+                    Ok(Some(frame)) => return Poll::Ready(Some(codec::decode_safe(&frame))),
+                    Ok(None) => {}
+                    Err(err) => {
+                        this.state = match this.framing {
+                            Framing::LengthPrefixed => {
+                                this.read_buf.clear();
+                                ReadState::Failed
+                            }
+                            // The oversized frame is at the start of the buffer. If it is
+                            // terminated, the frames after it are kept.
+                            Framing::Slip => match position_of_end(&this.read_buf) {
+                                Some(end) => {
+                                    this.read_buf.drain(..=end);
+                                    ReadState::Reading
+                                }
+                                None => {
+                                    this.read_buf.clear();
+                                    ReadState::SkipFrame
+                                }
+                            },
+                        };
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+            }
+            let mut chunk = [0u8; 4096];
+            let n = match ready!(Pin::new(&mut this.io).poll_read(cx, &mut chunk)) {
+                Ok(n) => n,
+                Err(err) => return Poll::Ready(Some(Err(err.into()))),
+            };
+            if n == 0 {
+                return Poll::Ready(None);
+            }
+            this.read_buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use async_std::channel::{bounded, Receiver, Sender};
+use async_std::task::{self, JoinHandle};
+use futures_lite::future;
+use rosc::OscPacket;
+use std::time::Duration;
+
+use crate::osc::OscSender;
+use crate::prelude::IntoOscPacket;
+
+/// Periodically sends an OSC packet on a background task.
+///
+/// This is useful to keep NAT bindings alive or to signal liveness to peers. The packet is sent
+/// with [`OscSender::send`], so the socket has to be connected. The first packet is sent right
+/// away.
+///
+/// The background task is stopped when [`Keepalive::cancel`] is called or when the handle is
+/// dropped. Send errors are logged and do not stop the task.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{prelude::*, Keepalive, OscSocket};
+/// use std::time::Duration;
+///
+/// let socket = OscSocket::bind("127.0.0.1:0").await?;
+/// socket.connect("127.0.0.1:8080").await?;
+/// let keepalive = Keepalive::spawn(socket.sender(), ("/ping", ()), Duration::from_secs(1));
+/// // ...
+/// keepalive.cancel().await;
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Debug)]
+pub struct Keepalive {
+    stop: Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl Keepalive {
+    /// Spawns a task that sends `packet` over `sender` every `interval`.
+    pub fn spawn<P: IntoOscPacket>(sender: OscSender, packet: P, interval: Duration) -> Self {
+        let packet = packet.into_osc_packet();
+        let (stop, stopped) = bounded(1);
+        let task = task::spawn(run(sender, packet, interval, stopped));
+        Self { stop, task }
+    }
+
+    /// Stops sending and waits for the background task to finish.
+    pub async fn cancel(self) {
+        drop(self.stop);
+        self.task.await;
+    }
+}
+
+async fn run(sender: OscSender, packet: OscPacket, interval: Duration, stopped: Receiver<()>) {
+    loop {
+        if let Err(err) = sender.send(packet.clone()).await {
+            log::debug!("Failed to send keepalive packet: {}", err);
+        }
+        let stop = async {
+            let _ = stopped.recv().await;
+            true
+        };
+        let tick = async {
+            task::sleep(interval).await;
+            false
+        };
+        if future::or(stop, tick).await {
+            break;
+        }
+    }
+}
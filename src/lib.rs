@@ -1,12 +1,18 @@
-#![forbid(unsafe_code, future_incompatible, rust_2018_idioms)]
-#![deny(missing_debug_implementations, nonstandard_style)]
-#![warn(missing_docs, missing_doc_code_examples, unreachable_pub)]
+#![forbid(unsafe_code, future_incompatible)]
+// Not forbidden because serde's derive macros allow `unused_extern_crates`.
+#![deny(rust_2018_idioms, missing_debug_implementations, nonstandard_style)]
+#![warn(missing_docs, unreachable_pub)]
 
 //! Async library for the Open Sound Control (OSC) protocol
 //!
+//! The sockets and everything else that needs the async runtime are behind the default `net`
+//! feature. With default features disabled, only the codec and the message, bundle, pattern and
+//! router types are built, without depending on `async-std`.
+//!
 //! # Examples
 //!
 //! ```
+//! # #[cfg(feature = "net")]
 //! # #[async_std::main]
 //! # async fn main() -> async_osc::Result<()> {
 //! use async_std::stream::StreamExt;
@@ -42,23 +48,151 @@
 //! }
 //! # Ok(())
 //! # }
+//! # #[cfg(not(feature = "net"))]
+//! # fn main() {}
 //! // tbi
 //! ```
 
 /// Re-export the main OSC types from the [`rosc`] crate.
 pub mod rosc {
-    pub use ::rosc::{OscBundle, OscMessage, OscPacket, OscType};
+    pub use ::rosc::{
+        OscArray, OscBundle, OscColor, OscError, OscMessage, OscMidiMessage, OscPacket, OscTime,
+        OscType,
+    };
 }
 
 pub use crate::rosc::*;
 
+#[cfg(feature = "net")]
+mod addr;
+#[cfg(feature = "net")]
+mod bridge;
+#[cfg(feature = "net")]
+mod builder;
+mod bundle;
+#[cfg(feature = "net")]
+mod cancel;
+#[cfg(feature = "net")]
+mod capture;
+#[cfg(feature = "net")]
+mod coalesce;
+/// Encoding helpers for OSC packets.
+pub mod codec;
 mod error;
+#[cfg(feature = "net")]
+mod framing;
+#[cfg(feature = "net")]
+mod keepalive;
 mod message;
+#[cfg(feature = "net")]
 mod osc;
+mod pattern;
+/// Thread-local pooling of receive buffers.
+#[cfg(feature = "net")]
+pub mod pool;
+mod router;
+#[cfg(feature = "net")]
+mod schedule;
+#[cfg(feature = "net")]
+mod sender_pool;
+#[cfg(feature = "net")]
+mod sequence;
+#[cfg(all(unix, feature = "serial"))]
+mod serial;
+#[cfg(feature = "net")]
+mod shared;
+#[cfg(feature = "net")]
+mod sockopt;
+#[cfg(feature = "net")]
+mod state;
+#[cfg(feature = "net")]
+mod stream;
+#[cfg(feature = "net")]
+mod subscribe;
+#[cfg(feature = "net")]
+mod target;
+#[cfg(feature = "net")]
+mod tee;
+/// Assertion helpers for tests, available with the `testing` feature.
+#[cfg(feature = "testing")]
+pub mod testing;
+mod time;
+#[cfg(feature = "net")]
 mod udp;
+mod value;
 
+#[cfg(feature = "net")]
+pub use addr::parse_scoped_addr;
+/// Derives `TryFrom<&OscMessage>` for an enum, available with the `derive` feature.
+///
+/// Each variant maps to the address given in its `#[osc(addr = "...")]` attribute. The fields of
+/// the variant are converted from the message's arguments in order with [`FromOscType`]. Messages
+/// with an unknown address or with a different number or type of arguments are rejected with
+/// [`Error::Osc`].
+///
+/// ```
+/// use async_osc::{prelude::*, FromOscMessage, OscMessage};
+/// use std::convert::TryFrom;
+///
+/// #[derive(Debug, PartialEq, FromOscMessage)]
+/// enum Command {
+///     #[osc(addr = "/play")]
+///     Play,
+///     #[osc(addr = "/volume")]
+///     Volume { channel: i32, value: f32 },
+/// }
+///
+/// let message = OscMessage::new("/volume", (2, 0.5f32));
+/// let command = Command::try_from(&message).unwrap();
+/// assert_eq!(command, Command::Volume { channel: 2, value: 0.5 });
+/// ```
+#[cfg(feature = "derive")]
+pub use async_osc_derive::FromOscMessage;
+#[cfg(feature = "net")]
+pub use bridge::OscBridge;
+#[cfg(feature = "net")]
+pub use builder::OscSocketBuilder;
+pub use bundle::OscBundleBuilder;
+#[cfg(feature = "net")]
+pub use cancel::{Cancellable, CancellationToken};
+#[cfg(feature = "net")]
+pub use capture::{CaptureHeader, CaptureReader, CaptureRecord, CaptureWriter};
+#[cfg(feature = "net")]
+pub use coalesce::CoalesceWindow;
 pub use error::{Error, Result};
-pub use osc::{OscSender, OscSocket};
+#[cfg(feature = "net")]
+pub use framing::{Framing, OscStream, DEFAULT_MAX_FRAME_LEN};
+#[cfg(feature = "net")]
+pub use keepalive::Keepalive;
+pub use message::{
+    format_message, format_message_with, AddressPolicy, FormatOptions, FromOscType, OscNumber,
+};
+#[cfg(feature = "net")]
+pub use osc::{OscReceiver, OscSender, OscSocket, Port, RawStream};
+pub use pattern::{is_pattern, OscAddressPattern};
+pub use router::{LogSampling, MatchContext, OscRouter};
+#[cfg(feature = "net")]
+pub use schedule::{Playback, ScheduledSend, Scheduler};
+#[cfg(feature = "net")]
+pub use sender_pool::OscSenderPool;
+#[cfg(feature = "net")]
+pub use sequence::{SequenceStatus, Sequenced, SequencedSender};
+#[cfg(all(unix, feature = "serial"))]
+pub use serial::OscSerial;
+#[cfg(feature = "net")]
+pub use shared::SharedReceiver;
+#[cfg(feature = "net")]
+pub use state::OscState;
+#[cfg(feature = "net")]
+pub use stream::{Dedup, Scheduled};
+#[cfg(feature = "net")]
+pub use subscribe::Subscription;
+#[cfg(feature = "net")]
+pub use target::TargetedSender;
+#[cfg(feature = "net")]
+pub use tee::TeeSender;
+pub use time::Time;
+pub use value::OscValue;
 // pub use udp::*;
 
 /// Prelude with extensions to [`rosc`] types.
@@ -67,6 +201,8 @@ pub use osc::{OscSender, OscSocket};
 /// See [`preulude::OscMessageExt`] for details.
 pub mod prelude {
     pub use crate::message::{
-        IntoOscArgs, IntoOscMessage, IntoOscPacket, OscMessageExt, OscPacketExt,
+        IntoOscArgs, IntoOscMessage, IntoOscPacket, OscBundleExt, OscMessageExt, OscPacketExt,
+        OscTypeExt,
     };
+    pub use crate::time::OscTimeExt;
 }
@@ -1,4 +1,11 @@
-use rosc::{OscBundle, OscMessage, OscPacket, OscType};
+use rosc::{OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscTime, OscType};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::codec;
+use crate::error::Error;
+use crate::pattern::normalize;
+use crate::value::OscValue;
 
 /// Extension methods for the [`rosc::OscMessage`] type.
 pub trait OscMessageExt {
@@ -9,11 +16,73 @@ pub trait OscMessageExt {
     where
         T: IntoOscArgs;
 
+    /// Create a new OscMessage like [`new`](OscMessageExt::new), normalizing the address with
+    /// `policy`.
+    ///
+    /// ```
+    /// # use async_osc::{prelude::*, AddressPolicy, OscMessage};
+    /// let policy = AddressPolicy {
+    ///     lowercase: true,
+    ///     strip_whitespace: true,
+    /// };
+    /// let message = OscMessage::new_normalized(" /Mixer/Volume ", (0.5f32,), policy);
+    /// assert_eq!(message.addr, "/mixer/volume");
+    /// ```
+    fn new_normalized<T>(addr: impl ToString, args: T, policy: AddressPolicy) -> Self
+    where
+        T: IntoOscArgs;
+
     /// Returns `true` if the address starts with the given prefix.
     ///
-    /// Returns `false` otherwise.
+    /// Returns `false` otherwise. A trailing slash in the address is ignored, like in
+    /// [`OscAddressPattern`](crate::OscAddressPattern). A prefix with a trailing slash only
+    /// matches whole parts, so `/a` and `/a/b` start with `/a/`, but `/ab` does not.
     fn starts_with(&self, prefix: &str) -> bool;
 
+    /// Returns `true` if the message has the address `addr` and the argument types `tags`.
+    ///
+    /// `tags` are OSC type tags like `"fs"` for a float and a string, optionally with the leading
+    /// comma. A trailing slash in the address is ignored. This is a quick guard before extracting
+    /// arguments.
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// let message = OscMessage::new("/label", (0.5f32, "main"));
+    /// assert!(message.matches("/label", "fs"));
+    /// assert!(!message.matches("/label", "fi"));
+    /// ```
+    fn matches(&self, addr: &str, tags: &str) -> bool;
+
+    /// Checks that the message arguments have the type tags `tags`.
+    ///
+    /// `tags` are OSC type tags like in [`matches`](OscMessageExt::matches). Returns
+    /// [`Error::SignatureMismatch`] with the expected and actual type tags otherwise, so that
+    /// handlers can reject unexpected messages with a clear diagnostic.
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// let message = OscMessage::new("/label", (0.5f32, "main"));
+    /// assert!(message.validate_signature("fs").is_ok());
+    /// assert!(message.validate_signature("fi").is_err());
+    /// ```
+    fn validate_signature(&self, tags: &str) -> Result<(), Error>;
+
+    /// Compares two messages, treating numbers of different widths as equal.
+    ///
+    /// `Int` and `Long` arguments are equal if they have the same value, and so are `Float` and
+    /// `Double` arguments. Integers are never equal to floats. All other arguments, and the
+    /// addresses, are compared exactly. This helps handlers written against one device to work
+    /// with another device that encodes numbers differently.
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// let a = OscMessage::new("/level", (1, 0.5f32));
+    /// let b = OscMessage::new("/level", (1i64, 0.5f64));
+    /// assert_ne!(a, b);
+    /// assert!(a.eq_numeric_normalized(&b));
+    /// ```
+    fn eq_numeric_normalized(&self, other: &OscMessage) -> bool;
+
     /// Get a reference to the message in tuple form.
     ///
     /// This is useful for pattern matching. Example:
@@ -32,6 +101,122 @@ pub trait OscMessageExt {
     /// }
     /// ```
     fn as_tuple(&self) -> (&str, &[OscType]);
+
+    /// Get the argument at `index` as a `bool`.
+    ///
+    /// Returns `None` if there is no argument at `index` or if it is not an [`OscType::Bool`].
+    fn get_bool(&self, index: usize) -> Option<bool>;
+
+    /// Get the argument at `index` as `(red, green, blue, alpha)`.
+    ///
+    /// Returns `None` if there is no argument at `index` or if it is not an [`OscType::Color`].
+    fn get_color(&self, index: usize) -> Option<(u8, u8, u8, u8)>;
+
+    /// Get the argument at `index` as `[port, status, data1, data2]`.
+    ///
+    /// Returns `None` if there is no argument at `index` or if it is not an [`OscType::Midi`].
+    fn get_midi(&self, index: usize) -> Option<[u8; 4]>;
+
+    /// Get the elements of the array argument at `index`.
+    ///
+    /// Returns `None` if there is no argument at `index` or if it is not an [`OscType::Array`].
+    /// Arrays are built with [`OscArray`](crate::OscArray), for example by collecting values that
+    /// convert into [`OscType`]:
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// let position: OscArray = vec![0.5f32, 0.25].into_iter().collect();
+    /// let message = OscMessage::new("/position", ("main", position));
+    /// assert_eq!(message.get_array(1).unwrap().len(), 2);
+    /// assert!(message.get_array(0).is_none());
+    /// ```
+    fn get_array(&self, index: usize) -> Option<&[OscType]>;
+
+    /// Returns a new message with the same address and the given args.
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// let message = OscMessage::new("/volume", (0.5f32,));
+    /// assert_eq!(message.with_args((1.0f32,)), OscMessage::new("/volume", (1.0f32,)));
+    /// ```
+    fn with_args<T>(&self, args: T) -> OscMessage
+    where
+        T: IntoOscArgs;
+
+    /// Transforms the message's args with `f`, keeping the address.
+    ///
+    /// This is useful, for example, for proxies that modify arguments before forwarding them.
+    fn map_args<F>(self, f: F) -> OscMessage
+    where
+        F: FnOnce(Vec<OscType>) -> Vec<OscType>;
+
+    /// Appends an argument to the message.
+    ///
+    /// The type tags are derived from the arguments when the message is encoded, so they always
+    /// match.
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// let mut message = OscMessage::new("/volume", (0.5f32,));
+    /// message.push_arg("mixer-1");
+    /// assert_eq!(message, OscMessage::new("/volume", (0.5f32, "mixer-1")));
+    /// ```
+    fn push_arg(&mut self, arg: impl Into<OscType>);
+
+    /// Inserts an argument before the first argument of the message.
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// let mut message = OscMessage::new("/volume", (0.5f32,));
+    /// message.prepend_arg(2);
+    /// assert_eq!(message, OscMessage::new("/volume", (2, 0.5f32)));
+    /// ```
+    fn prepend_arg(&mut self, arg: impl Into<OscType>);
+
+    /// Converts the message into its address and a list of [`OscValue`]s.
+    ///
+    /// This is useful to bridge OSC to configuration systems or REST APIs. Use
+    /// [`from_pairs`](Self::from_pairs) to convert back.
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// let message = OscMessage::new("/volume", (0.5f32,));
+    /// let (addr, values) = message.to_pairs();
+    /// assert_eq!(addr, "/volume");
+    /// assert_eq!(values, vec![OscValue::Float(0.5)]);
+    /// ```
+    fn to_pairs(&self) -> (String, Vec<OscValue>);
+
+    /// Creates a message from an address and a list of [`OscValue`]s.
+    fn from_pairs(pairs: (String, Vec<OscValue>)) -> Self;
+
+    /// Transforms the [`OscType::Float`] argument at `index` in place.
+    ///
+    /// Returns `false` and leaves the message unchanged if there is no argument at `index` or if
+    /// it has a different type.
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// let mut message = OscMessage::new("/volume", (0.5f32,));
+    /// assert!(message.map_f32(0, |volume| volume * 2.0));
+    /// assert_eq!(message, OscMessage::new("/volume", (1.0f32,)));
+    /// ```
+    fn map_f32(&mut self, index: usize, f: impl FnOnce(f32) -> f32) -> bool;
+
+    /// Transforms the [`OscType::Double`] argument at `index` in place.
+    ///
+    /// See [`map_f32`](Self::map_f32).
+    fn map_f64(&mut self, index: usize, f: impl FnOnce(f64) -> f64) -> bool;
+
+    /// Transforms the [`OscType::Int`] argument at `index` in place.
+    ///
+    /// See [`map_f32`](Self::map_f32).
+    fn map_i32(&mut self, index: usize, f: impl FnOnce(i32) -> i32) -> bool;
+
+    /// Transforms the [`OscType::Long`] argument at `index` in place.
+    ///
+    /// See [`map_f32`](Self::map_f32).
+    fn map_i64(&mut self, index: usize, f: impl FnOnce(i64) -> i64) -> bool;
 }
 
 impl OscMessageExt for OscMessage {
@@ -44,13 +229,153 @@ impl OscMessageExt for OscMessage {
         OscMessage { addr, args }
     }
 
+    fn new_normalized<T>(addr: impl ToString, args: T, policy: AddressPolicy) -> Self
+    where
+        T: IntoOscArgs,
+    {
+        let addr = policy.apply(&addr.to_string());
+        Self::new(addr, args)
+    }
+
     fn starts_with(&self, prefix: &str) -> bool {
-        self.addr.starts_with(prefix)
+        let addr = normalize(&self.addr);
+        match prefix.strip_suffix('/') {
+            Some(parent) => addr == parent || addr.starts_with(prefix),
+            None => addr.starts_with(prefix),
+        }
+    }
+
+    fn matches(&self, addr: &str, tags: &str) -> bool {
+        let tags = tags.strip_prefix(',').unwrap_or(tags);
+        normalize(&self.addr) == normalize(addr) && codec::type_tags(&self.args) == tags
+    }
+
+    fn validate_signature(&self, tags: &str) -> Result<(), Error> {
+        let expected = tags.strip_prefix(',').unwrap_or(tags);
+        let actual = codec::type_tags(&self.args);
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(Error::SignatureMismatch {
+                addr: self.addr.clone(),
+                expected: expected.to_string(),
+                actual,
+            })
+        }
+    }
+
+    fn eq_numeric_normalized(&self, other: &OscMessage) -> bool {
+        self.addr == other.addr && args_eq_numeric(&self.args, &other.args)
     }
 
     fn as_tuple(&self) -> (&str, &[OscType]) {
         (self.addr.as_str(), &self.args[..])
     }
+
+    fn get_bool(&self, index: usize) -> Option<bool> {
+        match self.args.get(index) {
+            Some(OscType::Bool(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn get_color(&self, index: usize) -> Option<(u8, u8, u8, u8)> {
+        match self.args.get(index) {
+            Some(OscType::Color(color)) => Some((color.red, color.green, color.blue, color.alpha)),
+            _ => None,
+        }
+    }
+
+    fn get_midi(&self, index: usize) -> Option<[u8; 4]> {
+        match self.args.get(index) {
+            Some(OscType::Midi(midi)) => Some([midi.port, midi.status, midi.data1, midi.data2]),
+            _ => None,
+        }
+    }
+
+    fn get_array(&self, index: usize) -> Option<&[OscType]> {
+        match self.args.get(index) {
+            Some(OscType::Array(array)) => Some(&array.content),
+            _ => None,
+        }
+    }
+
+    fn with_args<T>(&self, args: T) -> OscMessage
+    where
+        T: IntoOscArgs,
+    {
+        OscMessage {
+            addr: self.addr.clone(),
+            args: args.into_osc_args(),
+        }
+    }
+
+    fn push_arg(&mut self, arg: impl Into<OscType>) {
+        self.args.push(arg.into());
+    }
+
+    fn prepend_arg(&mut self, arg: impl Into<OscType>) {
+        self.args.insert(0, arg.into());
+    }
+
+    fn map_args<F>(self, f: F) -> OscMessage
+    where
+        F: FnOnce(Vec<OscType>) -> Vec<OscType>,
+    {
+        OscMessage {
+            addr: self.addr,
+            args: f(self.args),
+        }
+    }
+
+    fn to_pairs(&self) -> (String, Vec<OscValue>) {
+        let values = self.args.iter().cloned().map(OscValue::from).collect();
+        (self.addr.clone(), values)
+    }
+
+    fn from_pairs((addr, values): (String, Vec<OscValue>)) -> Self {
+        OscMessage::new(addr, values)
+    }
+
+    fn map_f32(&mut self, index: usize, f: impl FnOnce(f32) -> f32) -> bool {
+        match self.args.get_mut(index) {
+            Some(OscType::Float(value)) => {
+                *value = f(*value);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn map_f64(&mut self, index: usize, f: impl FnOnce(f64) -> f64) -> bool {
+        match self.args.get_mut(index) {
+            Some(OscType::Double(value)) => {
+                *value = f(*value);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn map_i32(&mut self, index: usize, f: impl FnOnce(i32) -> i32) -> bool {
+        match self.args.get_mut(index) {
+            Some(OscType::Int(value)) => {
+                *value = f(*value);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn map_i64(&mut self, index: usize, f: impl FnOnce(i64) -> i64) -> bool {
+        match self.args.get_mut(index) {
+            Some(OscType::Long(value)) => {
+                *value = f(*value);
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 /// Extension methods for the [`rosc::OscMessage`] type.
@@ -64,6 +389,18 @@ pub trait OscPacketExt {
     ///
     /// Return None otherwise.
     fn into_message(self) -> Option<OscMessage>;
+
+    /// Returns a hash of the packet's contents.
+    ///
+    /// The hash is computed over the encoded packet, so two packets have the same hash if they
+    /// encode to the same bytes.
+    fn content_hash(&self) -> u64;
+
+    /// Compares two packets, optionally ignoring the timetags of bundles.
+    ///
+    /// With `ignore_timetag` set, bundles are equal if their contents are equal, at any nesting
+    /// depth. Otherwise this is the same as `==`.
+    fn structurally_eq(&self, other: &OscPacket, ignore_timetag: bool) -> bool;
 }
 
 impl OscPacketExt for OscPacket {
@@ -79,6 +416,343 @@ impl OscPacketExt for OscPacket {
             _ => None,
         }
     }
+
+    fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        match codec::encode_small(self) {
+            Ok(buf) => buf[..].hash(&mut hasher),
+            // Packets too long to encode are only hashed by their length.
+            Err(_) => codec::estimate_encoded_len(self).hash(&mut hasher),
+        }
+        hasher.finish()
+    }
+
+    fn structurally_eq(&self, other: &OscPacket, ignore_timetag: bool) -> bool {
+        match (self, other) {
+            (OscPacket::Message(a), OscPacket::Message(b)) => a == b,
+            (OscPacket::Bundle(a), OscPacket::Bundle(b)) => {
+                (ignore_timetag || a.timetag == b.timetag)
+                    && a.content.len() == b.content.len()
+                    && a.content
+                        .iter()
+                        .zip(&b.content)
+                        .all(|(a, b)| a.structurally_eq(b, ignore_timetag))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Extension methods for the [`rosc::OscBundle`] type.
+pub trait OscBundleExt: Sized {
+    /// Build a bundle from an iterator of fallible messages.
+    ///
+    /// Returns the first error if any of the items is an error. The bundle's timetag is set to
+    /// "immediately".
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// let messages = vec![
+    ///     Ok(OscMessage::new("/a", (1,))),
+    ///     Err("invalid message"),
+    /// ];
+    /// assert_eq!(OscBundle::try_from_iter(messages), Err("invalid message"));
+    /// ```
+    fn try_from_iter<I, E>(iter: I) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = Result<OscMessage, E>>;
+}
+
+impl OscBundleExt for OscBundle {
+    fn try_from_iter<I, E>(iter: I) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = Result<OscMessage, E>>,
+    {
+        let content = iter
+            .into_iter()
+            .map(|message| message.map(OscPacket::Message))
+            .collect::<Result<Vec<_>, E>>()?;
+        Ok(OscBundle {
+            // The OSC timetag for "immediately".
+            timetag: (0, 1),
+            content,
+        })
+    }
+}
+
+fn args_eq_numeric(a: &[OscType], b: &[OscType]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| arg_eq_numeric(a, b))
+}
+
+fn arg_eq_numeric(a: &OscType, b: &OscType) -> bool {
+    match (a.as_number(), b.as_number()) {
+        (Some(a), Some(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => a.as_f64() == b.as_f64(),
+            _ => false,
+        },
+        _ => match (a, b) {
+            (OscType::Array(a), OscType::Array(b)) => args_eq_numeric(&a.content, &b.content),
+            _ => a == b,
+        },
+    }
+}
+
+/// Appends all messages in `packet`, including those in nested bundles, to `messages`.
+pub(crate) fn flatten(packet: OscPacket, messages: &mut Vec<OscMessage>) {
+    match packet {
+        OscPacket::Message(message) => messages.push(message),
+        OscPacket::Bundle(bundle) => {
+            for packet in bundle.content {
+                flatten(packet, messages);
+            }
+        }
+    }
+}
+
+/// Options for [`format_message_with`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Maximum number of bytes shown in hex for blobs. With `0`, only the length is shown.
+    pub blob_hex_len: usize,
+}
+
+/// Formats a message as a single human-readable line.
+///
+/// The address is followed by the arguments, separated by spaces. Floats always contain a
+/// decimal point and strings are quoted, so that integers, floats and strings can be told
+/// apart. Blobs are shown as `blob[N]` with their length, arrays in square brackets, time tags
+/// as UTC dates, colors as `rgba(r,g,b,a)` and MIDI messages as `midi(port,status,data1,data2)`.
+///
+/// ```
+/// # use async_osc::{*, prelude::*};
+/// let message = OscMessage::new("/mixer/volume", (1, 0.5f32, "main"));
+/// assert_eq!(format_message(&message), r#"/mixer/volume 1 0.5 "main""#);
+/// ```
+pub fn format_message(message: &OscMessage) -> String {
+    format_message_with(message, FormatOptions::default())
+}
+
+/// Formats a message like [`format_message`] with the given options.
+///
+/// ```
+/// # use async_osc::{*, prelude::*};
+/// let message = OscMessage::new("/data", (vec![0xde, 0xad, 0xbe, 0xef, 0x01],));
+/// let options = FormatOptions { blob_hex_len: 4 };
+/// assert_eq!(format_message_with(&message, options), "/data blob[5]:deadbeef..");
+/// ```
+pub fn format_message_with(message: &OscMessage, options: FormatOptions) -> String {
+    let mut line = message.addr.clone();
+    for arg in &message.args {
+        line.push(' ');
+        format_arg(arg, options, &mut line);
+    }
+    line
+}
+
+fn format_arg(arg: &OscType, options: FormatOptions, out: &mut String) {
+    let formatted = match arg {
+        OscType::Int(x) => x.to_string(),
+        OscType::Long(x) => x.to_string(),
+        OscType::Float(x) => format!("{:?}", x),
+        OscType::Double(x) => format!("{:?}", x),
+        OscType::String(x) => format!("{:?}", x),
+        OscType::Char(x) => format!("{:?}", x),
+        OscType::Blob(x) => {
+            out.push_str(&format!("blob[{}]", x.len()));
+            if options.blob_hex_len > 0 && !x.is_empty() {
+                out.push(':');
+                for byte in x.iter().take(options.blob_hex_len) {
+                    out.push_str(&format!("{:02x}", byte));
+                }
+                if x.len() > options.blob_hex_len {
+                    out.push_str("..");
+                }
+            }
+            return;
+        }
+        OscType::Time(time) => format!("time({})", format_time(*time)),
+        OscType::Color(x) => format!("rgba({},{},{},{})", x.red, x.green, x.blue, x.alpha),
+        OscType::Midi(x) => format!(
+            "midi({},0x{:02x},{},{})",
+            x.port, x.status, x.data1, x.data2
+        ),
+        OscType::Bool(x) => x.to_string(),
+        OscType::Nil => "nil".to_string(),
+        OscType::Inf => "inf".to_string(),
+        OscType::Array(array) => {
+            out.push('[');
+            for (i, arg) in array.content.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                format_arg(arg, options, out);
+            }
+            out.push(']');
+            return;
+        }
+    };
+    out.push_str(&formatted);
+}
+
+/// Formats a time tag as a UTC date with milliseconds, like `2024-05-01T12:00:00.250Z`.
+fn format_time((seconds, fractional): OscTime) -> String {
+    if (seconds, fractional) == (0, 1) {
+        return "immediately".to_string();
+    }
+    // Seconds between the NTP epoch (1900) and the Unix epoch (1970).
+    let unix_seconds = i64::from(seconds) - 2_208_988_800;
+    let millis = (u64::from(fractional) * 1000) >> 32;
+    let days = unix_seconds.div_euclid(86_400);
+    let day_seconds = unix_seconds.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        day_seconds / 3600,
+        day_seconds / 60 % 60,
+        day_seconds % 60,
+        millis
+    )
+}
+
+/// Converts days since the Unix epoch into a proleptic Gregorian `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// A numeric OSC argument.
+///
+/// See [`OscTypeExt::as_number`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OscNumber {
+    /// A 32-bit integer.
+    Int(i32),
+    /// A 64-bit integer.
+    Long(i64),
+    /// A 32-bit float.
+    Float(f32),
+    /// A 64-bit float.
+    Double(f64),
+}
+
+impl OscNumber {
+    /// Returns the number as `f64`.
+    ///
+    /// Large 64-bit integers may lose precision.
+    pub fn as_f64(self) -> f64 {
+        match self {
+            OscNumber::Int(value) => value as f64,
+            OscNumber::Long(value) => value as f64,
+            OscNumber::Float(value) => value as f64,
+            OscNumber::Double(value) => value,
+        }
+    }
+
+    /// Returns the number as `i64` if it is an integer.
+    pub fn as_i64(self) -> Option<i64> {
+        match self {
+            OscNumber::Int(value) => Some(value as i64),
+            OscNumber::Long(value) => Some(value),
+            OscNumber::Float(_) | OscNumber::Double(_) => None,
+        }
+    }
+}
+
+/// Conversion from a single OSC argument, used by the `FromOscMessage` derive macro.
+///
+/// Only exact types match, so an [`OscType::Int`] argument converts to `i32` but not to `i64`.
+pub trait FromOscType: Sized {
+    /// Returns the argument as `Self`, or `None` if it has a different type.
+    fn from_osc_type(arg: &OscType) -> Option<Self>;
+}
+
+macro_rules! from_osc_type {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl FromOscType for $ty {
+                fn from_osc_type(arg: &OscType) -> Option<Self> {
+                    match arg {
+                        OscType::$variant(value) => Some(value.clone()),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_osc_type! {
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    bool => Bool,
+    char => Char,
+    String => String,
+    Vec<u8> => Blob,
+    OscColor => Color,
+    OscMidiMessage => Midi
+}
+
+impl FromOscType for OscType {
+    fn from_osc_type(arg: &OscType) -> Option<Self> {
+        Some(arg.clone())
+    }
+}
+
+/// Extension methods for the [`rosc::OscType`] type.
+pub trait OscTypeExt {
+    /// Returns the argument as [`OscNumber`] if it is one of the four numeric OSC types.
+    ///
+    /// ```
+    /// # use async_osc::{*, prelude::*};
+    /// assert_eq!(OscType::Long(4).as_number(), Some(OscNumber::Long(4)));
+    /// assert_eq!(OscType::String("4".into()).as_number(), None);
+    /// ```
+    fn as_number(&self) -> Option<OscNumber>;
+
+    /// Returns any numeric argument as `f64`.
+    ///
+    /// See [`OscNumber::as_f64`].
+    fn as_f64(&self) -> Option<f64>;
+
+    /// Returns an integer argument (`Int` or `Long`) as `i64`.
+    ///
+    /// Returns `None` for floats and non-numeric arguments.
+    fn as_i64(&self) -> Option<i64>;
+}
+
+impl OscTypeExt for OscType {
+    fn as_number(&self) -> Option<OscNumber> {
+        match *self {
+            OscType::Int(value) => Some(OscNumber::Int(value)),
+            OscType::Long(value) => Some(OscNumber::Long(value)),
+            OscType::Float(value) => Some(OscNumber::Float(value)),
+            OscType::Double(value) => Some(OscNumber::Double(value)),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(OscNumber::as_f64)
+    }
+
+    fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(OscNumber::as_i64)
+    }
 }
 
 /// Helper trait to convert types into `Vec<[OscType]>`
@@ -109,6 +783,12 @@ where
 //     }
 // }
 
+impl IntoOscArgs for () {
+    fn into_osc_args(self) -> Vec<OscType> {
+        vec![]
+    }
+}
+
 impl<T1> IntoOscArgs for (T1,)
 where
     T1: Into<OscType>,
@@ -139,12 +819,78 @@ where
     }
 }
 
+impl IntoOscArgs for &[OscType] {
+    fn into_osc_args(self) -> Vec<OscType> {
+        self.to_vec()
+    }
+}
+
 impl IntoOscArgs for OscType {
     fn into_osc_args(self) -> Vec<OscType> {
         vec![self]
     }
 }
 
+/// Converts a JSON value into arguments, available with the `json` feature.
+///
+/// The elements of a JSON array become the arguments, any other value becomes a single argument.
+/// Integers become [`OscType::Int`], or [`OscType::Long`] if they do not fit into 32 bits. Other
+/// numbers become [`OscType::Double`], so integers above [`i64::MAX`] are rounded to the nearest
+/// `f64`. Strings, booleans and `null` become [`OscType::String`], [`OscType::Bool`] and
+/// [`OscType::Nil`], and nested arrays become [`OscType::Array`]. Objects have no OSC equivalent
+/// and are sent as their JSON text.
+///
+/// A `Vec<serde_json::Value>` cannot implement [`IntoOscArgs`] next to the impl for `Vec<T>`, so
+/// pass a slice of it instead.
+///
+/// ```
+/// # use async_osc::{*, prelude::*};
+/// let args: serde_json::Value = serde_json::from_str(r#"[1, 0.5, "main", true]"#).unwrap();
+/// let message = OscMessage::new("/mixer", args);
+/// assert_eq!(message.args, vec![1.into(), 0.5f64.into(), "main".into(), true.into()]);
+/// ```
+#[cfg(feature = "json")]
+impl IntoOscArgs for serde_json::Value {
+    fn into_osc_args(self) -> Vec<OscType> {
+        match self {
+            serde_json::Value::Array(values) => values.into_iter().map(json_to_osc).collect(),
+            value => vec![json_to_osc(value)],
+        }
+    }
+}
+
+/// Converts JSON values into arguments, available with the `json` feature.
+///
+/// See the impl for [`serde_json::Value`] for how values are converted.
+#[cfg(feature = "json")]
+impl IntoOscArgs for &[serde_json::Value] {
+    fn into_osc_args(self) -> Vec<OscType> {
+        self.iter().cloned().map(json_to_osc).collect()
+    }
+}
+
+#[cfg(feature = "json")]
+fn json_to_osc(value: serde_json::Value) -> OscType {
+    use serde_json::Value;
+    match value {
+        Value::Null => OscType::Nil,
+        Value::Bool(value) => OscType::Bool(value),
+        Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+            (Some(value), _) => match std::convert::TryFrom::try_from(value) {
+                Ok(value) => OscType::Int(value),
+                Err(_) => OscType::Long(value),
+            },
+            (None, Some(value)) => OscType::Double(value),
+            (None, None) => OscType::Nil,
+        },
+        Value::String(value) => OscType::String(value),
+        Value::Array(values) => OscType::Array(rosc::OscArray {
+            content: values.into_iter().map(json_to_osc).collect(),
+        }),
+        value @ Value::Object(_) => OscType::String(value.to_string()),
+    }
+}
+
 /// Helper trait to convert [`OscMessage`] and [`OscBundle`] into [`OscPacket`].
 pub trait IntoOscPacket {
     /// Convert into [`OscPacket`].
@@ -178,6 +924,32 @@ where
     }
 }
 
+/// Rules to normalize message addresses, used by [`OscMessageExt::new_normalized`].
+///
+/// The default policy keeps addresses unchanged.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AddressPolicy {
+    /// Convert the address to lowercase.
+    pub lowercase: bool,
+    /// Remove all whitespace from the address.
+    pub strip_whitespace: bool,
+}
+
+impl AddressPolicy {
+    /// Returns `addr` normalized according to this policy.
+    pub fn apply(&self, addr: &str) -> String {
+        let mut addr = if self.strip_whitespace {
+            addr.chars().filter(|c| !c.is_whitespace()).collect()
+        } else {
+            addr.to_string()
+        };
+        if self.lowercase {
+            addr = addr.to_lowercase();
+        }
+        addr
+    }
+}
+
 /// Helper trait to convert a `(impl ToString, impl IntoOscArgs)` tuple into [`OscMessage`].
 pub trait IntoOscMessage {
     /// Convert to [`OscMessage`].
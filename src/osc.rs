@@ -1,28 +1,164 @@
+use async_io::Async;
 use async_std::net::{ToSocketAddrs, UdpSocket};
 use async_std::stream::Stream;
-use futures_lite::ready;
-use rosc::OscPacket;
+use futures_lite::{future, ready, StreamExt};
+use futures_sink::Sink;
+use rosc::{OscBundle, OscMessage, OscPacket, OscTime, OscType};
+use socket2::SockRef;
+use std::collections::VecDeque;
+use std::fmt;
 use std::io;
-use std::net::SocketAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::ops::Deref;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime};
 
+use crate::addr::{self, Family};
+use crate::builder::OscSocketBuilder;
+use crate::cancel::{Cancellable, CancellationToken};
+use crate::codec::{self, CompiledMessage, DecodeMode};
 use crate::error::Error;
-use crate::prelude::IntoOscPacket;
+use crate::message::flatten;
+use crate::pattern::OscAddressPattern;
+use crate::prelude::{IntoOscPacket, OscMessageExt};
+use crate::schedule::ScheduledSend;
+use crate::sequence::Sequenced;
+use crate::sockopt;
+use crate::stream::{Dedup, Scheduled};
+use crate::subscribe::{FanOut, Subscription};
 use crate::udp::UdpSocketStream;
 
+/// A UDP port number.
+///
+/// Used by helpers like [`OscSocket::bind_local`] to avoid mixing up port numbers with other
+/// integer arguments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Port(pub u16);
+
+impl Port {
+    /// Returns the port number.
+    pub fn get(self) -> u16 {
+        self.0
+    }
+}
+
+impl From<u16> for Port {
+    fn from(port: u16) -> Self {
+        Self(port)
+    }
+}
+
+impl From<Port> for u16 {
+    fn from(port: Port) -> Self {
+        port.0
+    }
+}
+
+impl fmt::Display for Port {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+const PING_ADDR: &str = "/ping";
+const PONG_ADDR: &str = "/pong";
+
+/// Size of the buffer that acknowledgements are received into. Longer datagrams are skipped.
+const ACK_BUF_LEN: usize = 1024;
+
+/// Marks the argument after it as an acknowledgement id.
+const ACK_MARKER: char = '!';
+
 /// A UDP socket to send and receive OSC messages.
+///
+/// # Cancellation safety
+///
+/// Receiving from the socket's [`Stream`] is cancellation safe. The pending receive is stored in
+/// the socket, so if a future like `socket.next()` is dropped before it completes, for example in
+/// a `select` or a timeout, no datagram is lost and the next poll continues the receive. Dropping
+/// the socket while a receive is pending cancels the receive and closes the socket.
+///
+/// # Runtime
+///
+/// The socket does not need to be created inside an async-std runtime. The reactor that drives
+/// the socket is started on first use in a background thread, so the socket's futures can be
+/// awaited from any executor, for example [`futures_lite::future::block_on`]. There is thus no
+/// "missing runtime" error: an error returned from [`bind`] is always an error of the OS socket.
+///
+/// [`bind`]: OscSocket::bind
 #[derive(Debug)]
 pub struct OscSocket {
+    /// The receive stream. After a rebind, it is replaced on the next poll.
     socket: UdpSocketStream,
+    /// The socket and suppressed addresses of the last rebind, until the next poll picks them up.
+    rebound: Mutex<Option<Rebound>>,
+    /// Set while `rebound` holds a socket, so that sends only lock it after a rebind.
+    rebound_pending: AtomicBool,
+    /// The family of the socket, read when it was bound. `None` if it could not be read.
+    family: Option<Family>,
+    decode_mode: DecodeMode,
+    fanout: Mutex<FanOut>,
+    own_addrs: Option<Vec<SocketAddr>>,
+    flatten_bundles: bool,
+    flattened: VecDeque<(OscPacket, SocketAddr)>,
+    last_peer: Option<SocketAddr>,
+    /// A duplicate of the socket registered with the reactor, created on first use by
+    /// [`ready`](OscSocket::ready) or [`send_from`](OscSocket::send_from).
+    watcher: Mutex<Option<Arc<Async<std::net::UdpSocket>>>>,
+}
+
+#[derive(Debug)]
+struct Rebound {
+    socket: Arc<UdpSocket>,
+    own_addrs: Option<Vec<SocketAddr>>,
+    family: Option<Family>,
+}
+
+/// The socket that packets are sent on and its family, see [`OscSocket::current_socket`].
+enum Current<'a> {
+    Bound(&'a UdpSocket, Option<Family>),
+    Rebound(Arc<UdpSocket>, Option<Family>),
+}
+
+impl Current<'_> {
+    fn family(&self) -> Option<Family> {
+        match self {
+            Current::Bound(_, family) | Current::Rebound(_, family) => *family,
+        }
+    }
+}
+
+impl Deref for Current<'_> {
+    type Target = UdpSocket;
+    fn deref(&self) -> &UdpSocket {
+        match self {
+            Current::Bound(socket, _) => socket,
+            Current::Rebound(socket, _) => socket,
+        }
+    }
 }
 
 impl OscSocket {
     /// Creates a new OSC socket from a [`async_std::net::UdpSocket`].
     pub fn new(socket: UdpSocket) -> Self {
+        let family = Family::of(&socket).ok();
         let socket = UdpSocketStream::new(socket);
-        Self { socket }
+        Self {
+            socket,
+            rebound: Mutex::new(None),
+            rebound_pending: AtomicBool::new(false),
+            family,
+            decode_mode: DecodeMode::default(),
+            fanout: Mutex::new(FanOut::default()),
+            own_addrs: None,
+            flatten_bundles: false,
+            flattened: VecDeque::new(),
+            last_peer: None,
+            watcher: Mutex::new(None),
+        }
     }
 
     /// Creates an OSC socket from the given address.
@@ -36,6 +172,256 @@ impl OscSocket {
         Ok(Self::new(socket))
     }
 
+    /// Creates an OSC socket from the given address, retrying if the address cannot be resolved.
+    ///
+    /// Name resolution is attempted up to `attempts` times, waiting `delay` before the second
+    /// attempt and doubling the delay after each failed attempt. This helps at startup, when the
+    /// resolver may not be ready yet. A malformed address, like one without a port, fails right
+    /// away. Binding itself is not retried.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    /// use std::time::Duration;
+    ///
+    /// let socket = OscSocket::bind_retry("myhost.local:9000", 5, Duration::from_millis(100)).await?;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn bind_retry<A: ToSocketAddrs>(
+        addr: A,
+        attempts: u32,
+        delay: Duration,
+    ) -> Result<Self, Error> {
+        let addrs = addr::resolve_retry(&addr, attempts, delay).await?;
+        Self::bind(&addrs[..]).await
+    }
+
+    /// Replaces the underlying socket with a new socket bound to `addr`.
+    ///
+    /// This changes the port or address the socket listens on without replacing the
+    /// `OscSocket`, for example when the configuration is reloaded. The old socket is closed once
+    /// nothing else refers to it. Settings of this socket, like the decode mode, are kept.
+    ///
+    /// Rebinding only needs a shared reference, so it can happen while other tasks send on the
+    /// socket. Sends that start after the rebind use the new socket. A receive that is pending
+    /// on the old socket completes first, and the next one receives on the new socket. Until
+    /// then, [`socket`](Self::socket) returns the old socket and
+    /// [`current_socket`](Self::current_socket) the new one.
+    ///
+    /// The new socket is bound with the [`only_v6`](OscSocketBuilder::only_v6) and
+    /// [`reuse_address`](OscSocketBuilder::reuse_address) options of the old socket. Options set
+    /// after binding, like the DSCP value, don't fragment, buffer sizes and multicast
+    /// memberships, are not carried over and have to be set again.
+    ///
+    /// [`OscSender`]s created with [`sender`](Self::sender) before the rebind stay pinned to the
+    /// old socket. [Subscriptions](Self::subscribe) created before the rebind end with an error
+    /// of kind [`io::ErrorKind::NotConnected`]. Create new senders and subscriptions after
+    /// rebinding. If binding fails, the old socket is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    ///
+    /// let socket = OscSocket::bind("0.0.0.0:9000").await?;
+    /// // The configured port changed.
+    /// socket.rebind("0.0.0.0:9001").await?;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn rebind<A: ToSocketAddrs>(&self, addr: A) -> Result<(), Error> {
+        let builder = {
+            let current = self.current();
+            let current = SockRef::from(&*current);
+            let builder = OscSocketBuilder::new().reuse_address(current.reuse_address()?);
+            // Querying the option fails on IPv4 sockets.
+            match current.only_v6() {
+                Ok(only_v6) => builder.only_v6(only_v6),
+                Err(_) => builder,
+            }
+        };
+        let socket = Arc::new(builder.bind_socket(addr).await?);
+        let own_addrs = match self.own_addrs {
+            Some(_) => Some(own_addrs(&socket)?),
+            None => None,
+        };
+        let family = Family::of(&socket).ok();
+        *self.rebound.lock().unwrap() = Some(Rebound {
+            socket,
+            own_addrs,
+            family,
+        });
+        self.rebound_pending.store(true, Ordering::Release);
+        *self.watcher.lock().unwrap() = None;
+        let fanout = std::mem::take(&mut *self.fanout.lock().unwrap());
+        fanout.close("socket was rebound");
+        Ok(())
+    }
+
+    /// Switches the receive stream to the socket of the last rebind, if there was one since.
+    fn apply_rebind(&mut self) {
+        if let Some(rebound) = self.rebound.get_mut().unwrap().take() {
+            self.socket = UdpSocketStream::from_arc(rebound.socket);
+            self.own_addrs = rebound.own_addrs;
+            self.family = rebound.family;
+            *self.rebound_pending.get_mut() = false;
+        }
+    }
+
+    /// Returns the socket that packets are sent on.
+    fn current(&self) -> Current<'_> {
+        if self.rebound_pending.load(Ordering::Acquire) {
+            if let Some(rebound) = &*self.rebound.lock().unwrap() {
+                return Current::Rebound(rebound.socket.clone(), rebound.family);
+            }
+        }
+        Current::Bound(self.socket.get_ref(), self.family)
+    }
+
+    /// Returns a builder to configure a socket before binding it.
+    ///
+    /// See [`OscSocketBuilder`].
+    pub fn builder() -> OscSocketBuilder {
+        OscSocketBuilder::new()
+    }
+
+    /// Creates an OSC socket bound to the given port on `127.0.0.1`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::{OscSocket, Port};
+    ///
+    /// let socket = OscSocket::bind_local(Port(9000)).await?;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn bind_local(port: Port) -> Result<Self, Error> {
+        Self::bind((Ipv4Addr::LOCALHOST, port.get())).await
+    }
+
+    /// Creates an OSC socket bound to the given port on the IPv4 loopback address `127.0.0.1`.
+    ///
+    /// Unlike binding to `localhost`, which may resolve to either address family, this always
+    /// binds an IPv4 socket. Use port 0 to let the operating system choose a port. This is the
+    /// same as [`bind_local`](OscSocket::bind_local).
+    pub async fn bind_loopback_v4(port: Port) -> Result<Self, Error> {
+        Self::bind_local(port).await
+    }
+
+    /// Creates an OSC socket bound to the given port on the IPv6 loopback address `::1`.
+    ///
+    /// Unlike binding to `localhost`, which may resolve to either address family, this always
+    /// binds an IPv6 socket. Fails if IPv6 is not available. Use port 0 to let the operating
+    /// system choose a port.
+    pub async fn bind_loopback_v6(port: Port) -> Result<Self, Error> {
+        Self::bind((Ipv6Addr::LOCALHOST, port.get())).await
+    }
+
+    /// Creates an OSC socket that receives the IPv4 multicast group `group` on `port`.
+    ///
+    /// The socket is bound to the unspecified address with [address
+    /// reuse](OscSocketBuilder::reuse_address), so several listeners can share the port, and
+    /// then joins `group` on the interface with the address `iface`. Use
+    /// [`Ipv4Addr::UNSPECIFIED`] to let the operating system choose the interface. Packets sent
+    /// from the socket go out on the same interface and are looped back to listeners on this
+    /// host.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::{OscSocket, Port};
+    /// use std::net::Ipv4Addr;
+    ///
+    /// let group = Ipv4Addr::new(239, 255, 0, 1);
+    /// let socket = OscSocket::bind_multicast_v4(group, Port(9000), Ipv4Addr::UNSPECIFIED).await?;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn bind_multicast_v4(
+        group: Ipv4Addr,
+        port: Port,
+        iface: Ipv4Addr,
+    ) -> Result<Self, Error> {
+        if !group.is_multicast() {
+            let message = format!("{} is not a multicast address", group);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        }
+        let socket = Self::builder()
+            .reuse_address(true)
+            .bind((Ipv4Addr::UNSPECIFIED, port.get()))
+            .await?;
+        socket.socket().join_multicast_v4(group, iface)?;
+        socket.socket().set_multicast_loop_v4(true)?;
+        if !iface.is_unspecified() {
+            SockRef::from(socket.socket()).set_multicast_if_v4(&iface)?;
+        }
+        Ok(socket)
+    }
+
+    /// Creates an OSC socket bound to a port chosen by the operating system on `host`.
+    ///
+    /// Returns the socket together with the address it was bound to.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    ///
+    /// let (socket, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    /// eprintln!("Listening on port {}", addr.port());
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn bind_ephemeral(host: &str) -> Result<(Self, SocketAddr), Error> {
+        let socket = Self::bind((host, 0)).await?;
+        let addr = socket.local_addr()?;
+        Ok((socket, addr))
+    }
+
+    /// Sends a single packet from a new socket.
+    ///
+    /// This binds a socket to `local_addr`, connects it to `remote_addr`, sends the packet and
+    /// closes the socket again. Use a port number of 0 in `local_addr` to bind to a port
+    /// assigned by the OS.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    ///
+    /// OscSocket::send_once("127.0.0.1:0", "127.0.0.1:8080", ("/volume", (0.5f32,))).await?;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn send_once<A, B, P>(local_addr: A, remote_addr: B, packet: P) -> Result<(), Error>
+    where
+        A: ToSocketAddrs,
+        B: ToSocketAddrs,
+        P: IntoOscPacket,
+    {
+        // A plain socket, because the receive buffer of an `OscSocket` is not needed.
+        let socket = UdpSocket::bind(local_addr).await?;
+        socket.connect(remote_addr).await?;
+        let buf = codec::encode_small(&packet.into_osc_packet())?;
+        let n = socket.send(&buf[..]).await?;
+        check_len(&buf[..], n, SendKind::Connected)
+    }
+
     /// Connects the UDP socket to a remote address.
     ///
     /// When connected, only messages from this address will be received and the [`send`] method
@@ -56,12 +442,29 @@ impl OscSocket {
     /// # Ok(()) }) }
     /// ```
     pub async fn connect<A: ToSocketAddrs>(&self, addrs: A) -> Result<(), Error> {
-        self.socket().connect(addrs).await?;
+        self.current().connect(addrs).await?;
         Ok(())
     }
 
+    /// Connects the UDP socket to a remote address, retrying if the address cannot be resolved.
+    ///
+    /// Name resolution is retried like in [`bind_retry`](OscSocket::bind_retry).
+    pub async fn connect_retry<A: ToSocketAddrs>(
+        &self,
+        addrs: A,
+        attempts: u32,
+        delay: Duration,
+    ) -> Result<(), Error> {
+        let addrs = addr::resolve_retry(&addrs, attempts, delay).await?;
+        self.connect(&addrs[..]).await
+    }
+
     /// Sends an OSC packet on the socket to the given address.
     ///
+    /// If `addrs` resolves to several addresses, the packet is sent to the first one with an
+    /// address family the socket can send to. Returns [`Error::AddressFamilyMismatch`] if there is
+    /// none, for example when sending from an IPv6-only socket to an IPv4 address.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -81,9 +484,61 @@ impl OscSocket {
         packet: P,
         addrs: A,
     ) -> Result<(), Error> {
-        let buf = rosc::encoder::encode(&packet.into_osc_packet())?;
-        let n = self.socket().send_to(&buf[..], addrs).await?;
-        check_len(&buf[..], n)
+        let buf = codec::encode_small(&packet.into_osc_packet())?;
+        let current = self.current();
+        let n = addr::send_to_resolved(&current, current.family(), &buf[..], addrs).await?;
+        check_len(&buf[..], n, SendKind::Addressed)
+    }
+
+    /// Sends an OSC packet to `addr`, adapting the address to the family of the socket.
+    ///
+    /// The address family of `addr` is checked against the local address of the socket before
+    /// sending, instead of relying on the error the OS reports:
+    ///
+    /// * An IPv4 address is sent to as an IPv4-mapped IPv6 address from a dual-stack IPv6 socket.
+    /// * An IPv4-mapped IPv6 address is sent to as the IPv4 address from an IPv4 socket.
+    /// * Any other combination of families returns [`Error::AddressFamilyMismatch`], for example
+    ///   when sending from an IPv6-only socket to an IPv4 address.
+    pub async fn send_auto<P: IntoOscPacket>(
+        &self,
+        packet: P,
+        addr: SocketAddr,
+    ) -> Result<(), Error> {
+        let current = self.current();
+        send_packet(&current, current.family(), packet, Some(addr)).await
+    }
+
+    /// Sends a packet to `dst` with `src` as the source address of the datagram.
+    ///
+    /// On a socket bound to an unspecified address like `0.0.0.0`, this selects the local address
+    /// the datagram is sent from, for example to answer on the same interface a request was
+    /// received on. `src` must be an address of the host. This is supported on Linux, Android and
+    /// Apple platforms. On other platforms [`Error::Unsupported`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    ///
+    /// let socket = OscSocket::bind("0.0.0.0:0").await?;
+    /// let src = "192.168.1.2".parse().unwrap();
+    /// let dst = "192.168.1.10:9000".parse().unwrap();
+    /// socket.send_from(("/status", ("ok",)), src, dst).await?;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn send_from<P: IntoOscPacket>(
+        &self,
+        packet: P,
+        src: IpAddr,
+        dst: SocketAddr,
+    ) -> Result<(), Error> {
+        let buf = codec::encode_small(&packet.into_osc_packet())?;
+        let watcher = self.watcher()?;
+        let n = sockopt::send_from(&watcher, &buf[..], src, dst).await?;
+        check_len(&buf[..], n, SendKind::Addressed)
     }
 
     /// Sends a packet on the socket to the remote address to which it is connected.
@@ -107,95 +562,1164 @@ impl OscSocket {
     /// # Ok(()) }) }
     /// ```
     pub async fn send<P: IntoOscPacket>(&self, packet: P) -> Result<(), Error> {
-        let buf = rosc::encoder::encode(&packet.into_osc_packet())?;
-        let n = self.socket().send(&buf[..]).await?;
-        check_len(&buf[..], n)
+        let buf = codec::encode_small(&packet.into_osc_packet())?;
+        let n = self.current().send(&buf[..]).await?;
+        check_len(&buf[..], n, SendKind::Connected)
     }
 
-    /// Create a standalone sender for this socket.
+    /// Sends already encoded bytes on the connected socket.
     ///
-    /// The sender can be moved to other threads or tasks.
-    pub fn sender(&self) -> OscSender {
-        OscSender::new(self.socket.clone_inner())
-    }
-
-    /// Get a reference to the underling [`UdpSocket`].
-    pub fn socket(&self) -> &UdpSocket {
-        self.socket.get_ref()
+    /// The bytes are sent as is, without checking that they are a valid OSC packet. This is useful
+    /// to forward packets without decoding them.
+    pub async fn send_raw(&self, buf: &[u8]) -> Result<(), Error> {
+        let n = self.current().send(buf).await?;
+        check_len(buf, n, SendKind::Connected)
     }
 
-    /// Returns the local address that this socket is bound to.
+    /// Sends a message with a precomputed address and type tags on the connected socket.
     ///
-    /// This can be useful, for example, when binding to port 0 to figure out which port was
-    /// actually bound.
-    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
-        let addr = self.socket().local_addr()?;
-        Ok(addr)
-    }
-}
-
-impl Stream for OscSocket {
-    type Item = Result<(OscPacket, SocketAddr), Error>;
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let packet = ready!(Pin::new(&mut self.socket).poll_next(cx));
-        let message = match packet {
-            None => None,
-            Some(packet) => Some(match packet {
-                Err(err) => Err(err.into()),
-                Ok((buf, peer_addr)) => rosc::decoder::decode(&buf[..])
-                    .map_err(|e| e.into())
-                    .map(|p| (p, peer_addr)),
-            }),
-        };
-        Poll::Ready(message)
+    /// Only the arguments are encoded, which saves work on hot paths where the shape of the
+    /// message is fixed. Returns an error if `args` do not match the type tags of `compiled`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::{codec::CompiledMessage, OscSocket, OscType};
+    ///
+    /// let socket = OscSocket::bind("127.0.0.1:0").await?;
+    /// socket.connect("127.0.0.1:8080").await?;
+    /// let compiled = CompiledMessage::new("/volume", ",if")?;
+    /// socket.send_compiled(&compiled, &[OscType::Int(1), OscType::Float(0.5)]).await?;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn send_compiled(
+        &self,
+        compiled: &CompiledMessage,
+        args: &[OscType],
+    ) -> Result<(), Error> {
+        let buf = compiled.encode(args)?;
+        let n = self.current().send(&buf[..]).await?;
+        check_len(&buf[..], n, SendKind::Connected)
     }
-}
-
-/// A sender to send messages over an OSC socket.
-///
-/// See [`OscSocket::sender`].
-#[derive(Clone, Debug)]
-pub struct OscSender {
-    socket: Arc<UdpSocket>,
-}
 
-impl OscSender {
-    fn new(socket: Arc<UdpSocket>) -> Self {
-        Self { socket }
+    /// Sends a packet to `to`, or to the connected address if `to` is `None`.
+    ///
+    /// This is useful for code paths that only sometimes have an explicit target. If `to` is
+    /// `None` and the socket is not connected, an error of kind [`io::ErrorKind::NotConnected`] is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    ///
+    /// let socket = OscSocket::bind("127.0.0.1:0").await?;
+    /// socket.connect("127.0.0.1:8080").await?;
+    /// socket.send_packet(("/volume", (0.5f32,)), None).await?;
+    /// let other = "127.0.0.1:9090".parse().unwrap();
+    /// socket.send_packet(("/volume", (0.5f32,)), Some(other)).await?;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn send_packet<P: IntoOscPacket>(
+        &self,
+        packet: P,
+        to: Option<SocketAddr>,
+    ) -> Result<(), Error> {
+        let current = self.current();
+        send_packet(&current, current.family(), packet, to).await
     }
 
-    /// Sends an OSC packet on the socket to the given address.
+    /// Sends a packet on the connected socket, encoding it into the given buffer.
     ///
-    /// See [`OscSocket::send_to`].
-    pub async fn send_to<A: ToSocketAddrs, P: IntoOscPacket>(
+    /// The buffer is cleared before encoding. Reusing the same buffer for many sends avoids
+    /// allocating a new buffer for each packet.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::{prelude::*, OscSocket};
+    ///
+    /// let socket = OscSocket::bind("127.0.0.1:0").await?;
+    /// socket.connect("127.0.0.1:8080").await?;
+    /// let mut buf = Vec::with_capacity(1024);
+    /// for i in 0..10 {
+    ///     socket.send_into(("/count", (i,)), &mut buf).await?;
+    /// }
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn send_into<P: IntoOscPacket>(
         &self,
         packet: P,
-        addrs: A,
+        buf: &mut Vec<u8>,
     ) -> Result<(), Error> {
-        let buf = rosc::encoder::encode(&packet.into_osc_packet())?;
-        let n = self.socket().send_to(&buf[..], addrs).await?;
-        check_len(&buf[..], n)
+        send_into(&self.current(), packet, buf).await
     }
 
-    /// Sends an OSC packet on the connected socket.
+    /// Sets how received datagrams are decoded.
     ///
-    /// See [`OscSocket::send`].
-    pub async fn send<P: IntoOscPacket>(&self, packet: P) -> Result<(), Error> {
-        let buf = rosc::encoder::encode(&packet.into_osc_packet())?;
-        let n = self.socket().send(&buf[..]).await?;
-        check_len(&buf[..], n)
+    /// Defaults to [`DecodeMode::Strict`]. See [`DecodeMode`] for details.
+    pub fn set_decode_mode(&mut self, mode: DecodeMode) {
+        self.decode_mode = mode;
     }
 
-    /// Get a reference to the underling [`UdpSocket`].
-    pub fn socket(&self) -> &UdpSocket {
-        &*self.socket
-    }
-}
+    /// Receives the next packet from the socket's stream.
+    ///
+    /// This is a shorthand for `socket.next().await` for scripts and tests. If the stream has
+    /// ended, an error of kind [`io::ErrorKind::UnexpectedEof`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    ///
+    /// let mut socket = OscSocket::bind("127.0.0.1:9000").await?;
+    /// let (packet, peer_addr) = socket.recv_one().await?;
+    /// eprintln!("Received from {}: {:?}", peer_addr, packet);
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn recv_one(&mut self) -> Result<(OscPacket, SocketAddr), Error> {
+        match self.next().await {
+            Some(item) => item,
+            None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "socket closed").into()),
+        }
+    }
+
+    /// Measures the round-trip time to a peer.
+    ///
+    /// Sends a `/ping` message with a nonce to `addr` and waits up to `timeout` for a `/pong`
+    /// message with the same nonce from `addr`, as sent by [`respond_to_ping`]. Returns the time
+    /// between sending and receiving, or an error of kind [`io::ErrorKind::TimedOut`].
+    ///
+    /// The answer is read from the socket's stream. Other packets, packets that fail to decode and
+    /// transient receive errors while waiting are discarded.
+    ///
+    /// [`respond_to_ping`]: OscSocket::respond_to_ping
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    /// use std::time::Duration;
+    ///
+    /// let mut socket = OscSocket::bind("127.0.0.1:0").await?;
+    /// let addr = "127.0.0.1:9000".parse().unwrap();
+    /// let rtt = socket.ping(addr, Duration::from_secs(1)).await?;
+    /// eprintln!("Round-trip time: {:?}", rtt);
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn ping(&mut self, addr: SocketAddr, timeout: Duration) -> Result<Duration, Error> {
+        static NONCE: AtomicI64 = AtomicI64::new(0);
+        let nonce = OscType::Long(NONCE.fetch_add(1, Ordering::Relaxed));
+        let start = Instant::now();
+        self.send_to(OscMessage::new(PING_ADDR, vec![nonce.clone()]), addr)
+            .await?;
+        let recv = async {
+            loop {
+                let (packet, peer_addr) = match self.recv_one().await {
+                    Ok(item) => item,
+                    Err(err) if err.is_recoverable() => {
+                        log::debug!("Skipping packet while waiting for pong: {}", err);
+                        continue;
+                    }
+                    Err(err) => return Err(err),
+                };
+                if let OscPacket::Message(message) = packet {
+                    if peer_addr == addr
+                        && message.addr == PONG_ADDR
+                        && message.args.first() == Some(&nonce)
+                    {
+                        return Ok(start.elapsed());
+                    }
+                }
+            }
+        };
+        match async_std::future::timeout(timeout, recv).await {
+            Ok(res) => res,
+            Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "no pong received").into()),
+        }
+    }
+
+    /// Answers `packet` with a `/pong` if it is a `/ping` message sent by [`ping`].
+    ///
+    /// The pong is sent to `peer_addr` with the arguments of the ping. Returns `true` if the
+    /// packet was a ping. Call this for each packet received on a socket that should answer
+    /// pings.
+    ///
+    /// [`ping`]: OscSocket::ping
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    /// use async_std::stream::StreamExt;
+    ///
+    /// let mut socket = OscSocket::bind("127.0.0.1:9000").await?;
+    /// while let Some(packet) = socket.next().await {
+    ///     let (packet, peer_addr) = packet?;
+    ///     if socket.respond_to_ping(&packet, peer_addr).await? {
+    ///         continue;
+    ///     }
+    ///     // Handle other packets.
+    /// }
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn respond_to_ping(
+        &self,
+        packet: &OscPacket,
+        peer_addr: SocketAddr,
+    ) -> Result<bool, Error> {
+        match packet {
+            OscPacket::Message(message) if message.addr == PING_ADDR => {
+                let pong = OscMessage::new(PONG_ADDR, message.args.clone());
+                self.send_to(pong, peer_addr).await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Acknowledges `message` if it was sent with [`OscSender::send_reliable`].
+    ///
+    /// Removes the marked acknowledgement id that `send_reliable` appended to the arguments and
+    /// sends the id back to `peer_addr` in a message with the address `ack_addr`. Returns `true`
+    /// if the message had a marked id. Other messages are left unchanged and return `false`.
+    /// Resent copies of a message have the same id, so a peer can also use it to detect
+    /// duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::{prelude::*, OscSocket};
+    /// use async_std::stream::StreamExt;
+    ///
+    /// let mut socket = OscSocket::bind("127.0.0.1:9000").await?;
+    /// while let Some(packet) = socket.next().await {
+    ///     let (packet, peer_addr) = packet?;
+    ///     if let Some(mut message) = packet.into_message() {
+    ///         socket.acknowledge(&mut message, peer_addr, "/ack").await?;
+    ///         // Handle the message.
+    ///     }
+    /// }
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn acknowledge(
+        &self,
+        message: &mut OscMessage,
+        peer_addr: SocketAddr,
+        ack_addr: &str,
+    ) -> Result<bool, Error> {
+        match message.args[..] {
+            [.., OscType::Char(ACK_MARKER), OscType::Long(_)] => {
+                let id = message.args.pop().unwrap();
+                message.args.pop();
+                self.send_to(OscMessage::new(ack_addr, vec![id]), peer_addr)
+                    .await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Returns the address of the peer that sent the most recently received packet.
+    ///
+    /// Returns `None` if no packet was received on this socket yet.
+    pub fn last_peer(&self) -> Option<SocketAddr> {
+        self.last_peer
+    }
+
+    /// Sends a packet to the peer that sent the most recently received packet.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::NotConnected`] if no packet was received yet.
+    pub async fn reply<P: IntoOscPacket>(&self, packet: P) -> Result<(), Error> {
+        let peer_addr = self
+            .last_peer
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "no packet received yet"))?;
+        self.send_to(packet, peer_addr).await
+    }
+
+    /// Sends `messages` in a bundle with `timetag` to the peer that sent the most recently
+    /// received packet.
+    ///
+    /// A bundle is received as a whole, so this answers a query with several values atomically.
+    /// See [`reply`](Self::reply).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::{prelude::*, OscMessage, OscSocket};
+    /// use async_std::stream::StreamExt;
+    ///
+    /// let mut socket = OscSocket::bind("127.0.0.1:9000").await?;
+    /// while let Some(packet) = socket.next().await {
+    ///     let (packet, _peer_addr) = packet?;
+    ///     if packet.message().map_or(false, |m| m.addr == "/mixer/query") {
+    ///         let messages = vec![
+    ///             OscMessage::new("/mixer/volume", (0.8f32,)),
+    ///             OscMessage::new("/mixer/mute", (false,)),
+    ///         ];
+    ///         socket.reply_bundle(messages, (0, 1)).await?;
+    ///     }
+    /// }
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn reply_bundle<I>(&self, messages: I, timetag: OscTime) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = OscMessage>,
+    {
+        let bundle = OscBundle {
+            timetag,
+            content: messages.into_iter().map(OscPacket::Message).collect(),
+        };
+        self.reply(bundle).await
+    }
+
+    /// Drops received datagrams that were sent from this socket.
+    ///
+    /// A socket that sends to a multicast group it has joined receives its own packets if
+    /// multicast loopback is enabled. With this flag set, datagrams whose source address equals
+    /// the socket's local address are not yielded from the stream. If the socket is bound to an
+    /// unspecified address, the addresses of all local network interfaces with the socket's port
+    /// are considered own addresses (on platforms where they can be listed).
+    ///
+    /// The local addresses are looked up when the flag is set.
+    pub fn set_suppress_own(&mut self, suppress: bool) -> Result<(), Error> {
+        self.apply_rebind();
+        self.own_addrs = match suppress {
+            true => Some(own_addrs(&self.current())?),
+            false => None,
+        };
+        Ok(())
+    }
+
+    /// Yields the messages in received bundles individually from the stream.
+    ///
+    /// With the flag set, each message in a received bundle, including messages in nested
+    /// bundles, is yielded as a separate [`OscPacket::Message`] in the order of the bundle. All
+    /// messages from a bundle are yielded with the peer address the bundle was received from.
+    /// Timetags are discarded. Empty bundles are skipped.
+    pub fn set_auto_flatten_bundles(&mut self, flatten: bool) {
+        self.flatten_bundles = flatten;
+    }
+
+    /// Maps received packets into application events.
+    ///
+    /// Returns a stream that yields the values returned from `f` for each received packet.
+    /// Packets for which `f` returns `None` are dropped. Receive and decode errors are passed
+    /// through.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::{prelude::*, OscSocket};
+    /// use async_std::stream::StreamExt;
+    ///
+    /// enum Event {
+    ///     Fire,
+    /// }
+    ///
+    /// let socket = OscSocket::bind("127.0.0.1:0").await?;
+    /// let mut events = socket.map_into(|packet, _peer_addr| match packet.message() {
+    ///     Some(message) if message.addr == "/fire" => Some(Event::Fire),
+    ///     _ => None,
+    /// });
+    /// while let Some(event) = events.next().await {
+    ///     match event? {
+    ///         Event::Fire => eprintln!("Fire!"),
+    ///     }
+    /// }
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub fn map_into<T, F>(self, mut f: F) -> impl Stream<Item = Result<T, Error>>
+    where
+        F: FnMut(OscPacket, SocketAddr) -> Option<T>,
+    {
+        self.filter_map(move |item| match item {
+            Ok((packet, peer_addr)) => f(packet, peer_addr).map(Ok),
+            Err(err) => Some(Err(err)),
+        })
+    }
+
+    /// Feeds all received packets into `sink`.
+    ///
+    /// This allows to integrate the socket with channels or buffers that implement [`Sink`].
+    /// Runs until receiving fails or the sink returns an error. When the sink fails, for
+    /// example because the receiving end of a channel was dropped, its error is returned as an
+    /// IO error of kind [`io::ErrorKind::BrokenPipe`].
+    ///
+    /// [`Sink`]: futures_sink::Sink
+    pub async fn pipe_to<S>(mut self, mut sink: S) -> Result<(), Error>
+    where
+        S: Sink<(OscPacket, SocketAddr)> + Unpin,
+        S::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let sink_error = |err| Error::Io(io::Error::new(io::ErrorKind::BrokenPipe, err));
+        while let Some(item) = self.next().await {
+            let item = item?;
+            future::poll_fn(|cx| Pin::new(&mut sink).poll_ready(cx))
+                .await
+                .map_err(sink_error)?;
+            Pin::new(&mut sink).start_send(item).map_err(sink_error)?;
+            future::poll_fn(|cx| Pin::new(&mut sink).poll_flush(cx))
+                .await
+                .map_err(sink_error)?;
+        }
+        future::poll_fn(|cx| Pin::new(&mut sink).poll_close(cx))
+            .await
+            .map_err(sink_error)
+    }
+
+    /// Drops packets that are identical to a packet received within the last `window`.
+    ///
+    /// Packets are compared by their [`content_hash`], regardless of the peer they were
+    /// received from. This is useful over lossy links where senders retransmit messages.
+    ///
+    /// [`content_hash`]: crate::prelude::OscPacketExt::content_hash
+    pub fn dedup(self, window: Duration) -> Dedup<Self> {
+        Dedup::new(self, window)
+    }
+
+    /// Holds back received bundles until the time given by their timetag.
+    ///
+    /// See [`Scheduled`] for details and for latency compensation.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    /// use async_std::stream::StreamExt;
+    /// use std::time::Duration;
+    ///
+    /// let socket = OscSocket::bind("127.0.0.1:9000").await?;
+    /// let mut scheduled = socket
+    ///     .scheduled()
+    ///     .with_latency_compensation(Duration::from_millis(5));
+    /// while let Some(packet) = scheduled.next().await {
+    ///     eprintln!("Execute now: {:?}", packet?.0);
+    /// }
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub fn scheduled(self) -> Scheduled<Self> {
+        Scheduled::new(self)
+    }
+
+    /// Ends the stream of received packets when `token` is cancelled.
+    ///
+    /// This allows to stop a receive loop running on another task gracefully.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::{CancellationToken, OscSocket};
+    /// use async_std::stream::StreamExt;
+    ///
+    /// let token = CancellationToken::new();
+    /// let mut socket = OscSocket::bind("127.0.0.1:5050").await?.with_cancellation(token.clone());
+    /// let task = async_std::task::spawn(async move {
+    ///     while let Some(packet) = socket.next().await {
+    ///         eprintln!("Received: {:?}", packet);
+    ///     }
+    /// });
+    /// token.cancel();
+    /// task.await;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub fn with_cancellation(self, token: CancellationToken) -> Cancellable<Self> {
+        Cancellable::new(self, token)
+    }
+
+    /// Strips sequence numbers appended by a [`SequencedSender`] and reports gaps.
+    ///
+    /// Each received packet is yielded together with a [`SequenceStatus`]. Sequence numbers are
+    /// tracked separately for each peer.
+    ///
+    /// [`SequencedSender`]: crate::SequencedSender
+    /// [`SequenceStatus`]: crate::SequenceStatus
+    pub fn sequenced(self) -> Sequenced<Self> {
+        Sequenced::new(self)
+    }
+
+    /// Returns a stream of raw datagrams received on this socket.
+    ///
+    /// The stream yields the received bytes and the peer address before decoding. Use
+    /// [`codec::decode`] to decode them selectively, for example when capturing wire data.
+    ///
+    /// The raw stream reads from the same underlying socket. If both this socket and the raw
+    /// stream are polled, each datagram is received by only one of them.
+    pub fn raw_stream(&self) -> RawStream {
+        RawStream {
+            socket: UdpSocketStream::from_arc(self.current_socket()),
+        }
+    }
+
+    /// Subscribes to messages with addresses matching `pattern`.
+    ///
+    /// Returns a stream of the matching messages. Bundles are flattened into their messages,
+    /// which all share the bundle's peer address. Any number of subscriptions can be created,
+    /// each receiving every message that matches its pattern.
+    ///
+    /// All subscriptions share a single background task that receives from the socket. This task
+    /// is started with the first subscription and competes with this socket's own [`Stream`]
+    /// implementation for datagrams, so the socket should not be polled directly once there are
+    /// subscriptions. The subscriptions end when the socket is dropped, or after yielding a
+    /// persistent receive error. A later subscription then starts a new task.
+    ///
+    /// Each subscription queues up to 1024 messages. When the queue of a subscription is full
+    /// because it is not polled, newer messages are dropped for it until it catches up, so a slow
+    /// subscriber does not grow memory without limit or hold back the other subscriptions.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    /// use async_std::stream::StreamExt;
+    ///
+    /// let socket = OscSocket::bind("127.0.0.1:9000").await?;
+    /// let mut volume = socket.subscribe("/mixer/*/volume");
+    /// let mut transport = socket.subscribe("/transport/{play,stop}");
+    /// while let Some(message) = volume.next().await {
+    ///     let (message, _peer_addr) = message?;
+    ///     eprintln!("{:?}", message);
+    /// }
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub fn subscribe(&self, pattern: impl Into<OscAddressPattern>) -> Subscription {
+        let socket = self.current_socket();
+        self.fanout
+            .lock()
+            .unwrap()
+            .subscribe(&socket, pattern.into())
+    }
+
+    /// Create a standalone sender for this socket.
+    ///
+    /// The sender can be moved to other threads or tasks.
+    pub fn sender(&self) -> OscSender {
+        let family = self.current().family();
+        OscSender::new(self.current_socket(), family)
+    }
+
+    /// Get a reference to the underling [`UdpSocket`].
+    ///
+    /// After a [`rebind`](OscSocket::rebind), this returns the old socket until the next receive
+    /// switches to the new socket. Use [`current_socket`](Self::current_socket) to get the new
+    /// socket right away.
+    pub fn socket(&self) -> &UdpSocket {
+        self.socket.get_ref()
+    }
+
+    /// Returns the socket that packets are sent on.
+    ///
+    /// This is the socket returned by [`socket`](Self::socket), except after a
+    /// [`rebind`](OscSocket::rebind) and before the next receive, when it already is the new
+    /// socket.
+    pub fn current_socket(&self) -> Arc<UdpSocket> {
+        match self.current() {
+            Current::Bound(..) => self.socket.clone_inner(),
+            Current::Rebound(socket, _) => socket,
+        }
+    }
+
+    /// Sets the "don't fragment" flag for packets sent on this socket.
+    ///
+    /// With the flag set, sending a packet that is larger than the path MTU fails instead of
+    /// being fragmented by the IP layer. This is supported for IPv6 sockets on Linux, Android and
+    /// Apple platforms, and for IPv4 sockets on Apple platforms. On other platforms
+    /// [`Error::Unsupported`] is returned.
+    pub fn set_dont_fragment(&self, dont_fragment: bool) -> Result<(), Error> {
+        sockopt::set_dont_fragment(&self.current(), dont_fragment)
+    }
+
+    /// Marks packets sent on this socket with a DSCP value for QoS.
+    ///
+    /// Networks that prioritize traffic by its Differentiated Services Code Point can then
+    /// forward OSC control traffic ahead of bulk traffic, for example with `46` (Expedited
+    /// Forwarding). `dscp` must be at most 63. The value is written to the IPv4 type of service
+    /// byte, or to the IPv6 traffic class, which is supported on Linux. On other platforms
+    /// [`Error::Unsupported`] is returned for IPv6 sockets.
+    pub fn set_dscp(&self, dscp: u8) -> Result<(), Error> {
+        sockopt::set_dscp(&self.current(), dscp)
+    }
+
+    /// Returns the number of datagrams the kernel dropped for this socket.
+    ///
+    /// Datagrams are dropped when they arrive while the receive buffer is full, for example
+    /// because the application does not read fast enough. The counter helps to diagnose packet
+    /// loss under load. This is supported on Linux, where it is the same counter that
+    /// `SO_RXQ_OVFL` reports. On other platforms [`Error::Unsupported`] is returned.
+    pub fn dropped_packets(&self) -> Result<u64, Error> {
+        sockopt::dropped_packets(&self.current())
+    }
+
+    /// Waits until the socket is writable.
+    ///
+    /// Sending on a UDP socket rarely blocks, but this gives a readiness checkpoint, for example
+    /// before the first send on a connected socket or after sends failed because the send buffer
+    /// was full.
+    pub async fn ready(&self) -> Result<(), Error> {
+        self.watcher()?.writable().await?;
+        Ok(())
+    }
+
+    /// Returns the watcher for the readiness of the socket, registering it on first use.
+    fn watcher(&self) -> Result<Arc<Async<std::net::UdpSocket>>, Error> {
+        let mut watcher = self.watcher.lock().unwrap();
+        if let Some(watcher) = &*watcher {
+            return Ok(watcher.clone());
+        }
+        // Register a duplicate of the socket with the reactor, which shares the readiness of the
+        // original socket.
+        let socket: std::net::UdpSocket = SockRef::from(&*self.current()).try_clone()?.into();
+        let registered = Arc::new(Async::new(socket)?);
+        *watcher = Some(registered.clone());
+        Ok(registered)
+    }
+
+    /// Returns the local address that this socket is bound to.
+    ///
+    /// This can be useful, for example, when binding to port 0 to figure out which port was
+    /// actually bound.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        let addr = self.current().local_addr()?;
+        Ok(addr)
+    }
+}
+
+impl OscSocket {
+    fn poll_next_item(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<(OscPacket, SocketAddr), Error>>> {
+        if let Some(item) = self.flattened.pop_front() {
+            return Poll::Ready(Some(Ok(item)));
+        }
+        self.apply_rebind();
+        let mode = self.decode_mode;
+        loop {
+            let item = ready!(poll_next_packet(&mut self.socket, mode, cx));
+            if let (Some(Ok((_, peer_addr))), Some(own_addrs)) = (&item, &self.own_addrs) {
+                if own_addrs.contains(peer_addr) {
+                    continue;
+                }
+            }
+            if !self.flatten_bundles {
+                return Poll::Ready(item);
+            }
+            if let Some(Ok((packet @ OscPacket::Bundle(_), peer_addr))) = item {
+                let mut messages = Vec::new();
+                flatten(packet, &mut messages);
+                let packets = messages
+                    .into_iter()
+                    .map(|message| (OscPacket::Message(message), peer_addr));
+                self.flattened.extend(packets);
+                match self.flattened.pop_front() {
+                    Some(item) => return Poll::Ready(Some(Ok(item))),
+                    None => continue,
+                }
+            }
+            return Poll::Ready(item);
+        }
+    }
+}
+
+/// Returns the addresses that datagrams sent from `socket` arrive from.
+///
+/// For a socket bound to an unspecified address, these are the addresses of all interfaces.
+fn own_addrs(socket: &UdpSocket) -> Result<Vec<SocketAddr>, Error> {
+    let local_addr = socket.local_addr()?;
+    let mut own_addrs = vec![local_addr];
+    if local_addr.ip().is_unspecified() {
+        let ips = addr::interface_ips()?;
+        own_addrs.extend(
+            ips.into_iter()
+                .filter(|ip| ip.is_ipv4() == local_addr.is_ipv4())
+                .map(|ip| SocketAddr::new(ip, local_addr.port())),
+        );
+    }
+    Ok(own_addrs)
+}
+
+impl Stream for OscSocket {
+    type Item = Result<(OscPacket, SocketAddr), Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = ready!(self.poll_next_item(cx));
+        if let Some(Ok((_, peer_addr))) = &item {
+            self.last_peer = Some(*peer_addr);
+        }
+        Poll::Ready(item)
+    }
+}
+
+/// A UDP socket to only receive OSC messages.
+///
+/// Unlike [`OscSocket`], this type does not have any methods to send messages. Use it for
+/// applications that only listen.
+#[derive(Debug)]
+pub struct OscReceiver {
+    socket: UdpSocketStream,
+    decode_mode: DecodeMode,
+}
+
+impl OscReceiver {
+    /// Creates a new OSC receiver from a [`async_std::net::UdpSocket`].
+    pub fn new(socket: UdpSocket) -> Self {
+        let socket = UdpSocketStream::new(socket);
+        Self {
+            socket,
+            decode_mode: DecodeMode::default(),
+        }
+    }
+
+    /// Creates an OSC receiver from the given address.
+    ///
+    /// See [`OscSocket::bind`].
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> Result<Self, Error> {
+        let socket = UdpSocket::bind(addr).await?;
+        Ok(Self::new(socket))
+    }
+
+    /// Get a reference to the underling [`UdpSocket`].
+    pub fn socket(&self) -> &UdpSocket {
+        self.socket.get_ref()
+    }
+
+    /// Sets how received datagrams are decoded.
+    ///
+    /// See [`OscSocket::set_decode_mode`].
+    pub fn set_decode_mode(&mut self, mode: DecodeMode) {
+        self.decode_mode = mode;
+    }
+
+    /// Returns the local address that this receiver is bound to.
+    ///
+    /// See [`OscSocket::local_addr`].
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        let addr = self.socket().local_addr()?;
+        Ok(addr)
+    }
+}
+
+impl Stream for OscReceiver {
+    type Item = Result<(OscPacket, SocketAddr), Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mode = self.decode_mode;
+        poll_next_packet(&mut self.socket, mode, cx)
+    }
+}
+
+/// A stream of raw datagrams received on an OSC socket.
+///
+/// See [`OscSocket::raw_stream`].
+#[derive(Debug)]
+pub struct RawStream {
+    socket: UdpSocketStream,
+}
+
+impl Stream for RawStream {
+    type Item = Result<(Vec<u8>, SocketAddr), Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = ready!(Pin::new(&mut self.socket).poll_next(cx));
+        Poll::Ready(item.map(|res| res.map_err(Error::from)))
+    }
+}
+
+fn poll_next_packet(
+    socket: &mut UdpSocketStream,
+    mode: DecodeMode,
+    cx: &mut Context<'_>,
+) -> Poll<Option<Result<(OscPacket, SocketAddr), Error>>> {
+    // Decode directly from the receive buffer to avoid copying the datagram. Keep this in sync
+    // with `testing::decode_from_bytes`.
+    let res = ready!(socket.poll_recv_with(cx, |buf, peer_addr| {
+        codec::decode(buf, mode).map(|packet| (packet, peer_addr))
+    }));
+    Poll::Ready(res.map(|res| res.map_err(Error::from).and_then(|res| res)))
+}
+
+/// A sender to send messages over an OSC socket.
+///
+/// See [`OscSocket::sender`].
+#[derive(Clone, Debug)]
+pub struct OscSender {
+    socket: Arc<UdpSocket>,
+    family: Option<Family>,
+}
+
+impl OscSender {
+    fn new(socket: Arc<UdpSocket>, family: Option<Family>) -> Self {
+        Self { socket, family }
+    }
+
+    /// Sends a packet on the connected socket at the given time.
+    ///
+    /// The packet is sent by a background task. Returns a handle that can cancel the send while
+    /// it is pending, for example when a cue was recalled. A time in the past sends the packet
+    /// right away.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let socket = OscSocket::bind("127.0.0.1:0").await?;
+    /// socket.connect("127.0.0.1:8080").await?;
+    /// let at = SystemTime::now() + Duration::from_secs(5);
+    /// let cue = socket.sender().send_at(("/cue/go", (1,)), at);
+    /// // The cue was recalled.
+    /// cue.cancel();
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub fn send_at<P: IntoOscPacket>(&self, packet: P, at: SystemTime) -> ScheduledSend {
+        ScheduledSend::spawn(self.clone(), packet.into_osc_packet(), at)
+    }
+
+    /// Sends an OSC packet on the socket to the given address.
+    ///
+    /// See [`OscSocket::send_to`].
+    pub async fn send_to<A: ToSocketAddrs, P: IntoOscPacket>(
+        &self,
+        packet: P,
+        addrs: A,
+    ) -> Result<(), Error> {
+        let buf = codec::encode_small(&packet.into_osc_packet())?;
+        let n = addr::send_to_resolved(self.socket(), self.family, &buf[..], addrs).await?;
+        check_len(&buf[..], n, SendKind::Addressed)
+    }
+
+    /// Sends an OSC packet on the connected socket.
+    ///
+    /// See [`OscSocket::send`].
+    pub async fn send<P: IntoOscPacket>(&self, packet: P) -> Result<(), Error> {
+        let buf = codec::encode_small(&packet.into_osc_packet())?;
+        let n = self.socket().send(&buf[..]).await?;
+        check_len(&buf[..], n, SendKind::Connected)
+    }
+
+    /// Sends an OSC packet to `addr`, adapting the address to the family of the socket.
+    ///
+    /// See [`OscSocket::send_auto`].
+    pub async fn send_auto<P: IntoOscPacket>(
+        &self,
+        packet: P,
+        addr: SocketAddr,
+    ) -> Result<(), Error> {
+        send_packet(self.socket(), self.family, packet, Some(addr)).await
+    }
+
+    /// Sends already encoded bytes on the connected socket.
+    ///
+    /// See [`OscSocket::send_raw`].
+    pub async fn send_raw(&self, buf: &[u8]) -> Result<(), Error> {
+        let n = self.socket().send(buf).await?;
+        check_len(buf, n, SendKind::Connected)
+    }
+
+    /// Sends a message with a precomputed address and type tags on the connected socket.
+    ///
+    /// Only the arguments are encoded, which saves work on hot paths where the shape of the
+    /// message is fixed. Returns an error if `args` do not match the type tags of `compiled`.
+    pub async fn send_compiled(
+        &self,
+        compiled: &CompiledMessage,
+        args: &[OscType],
+    ) -> Result<(), Error> {
+        let buf = compiled.encode(args)?;
+        let n = self.socket().send(&buf[..]).await?;
+        check_len(&buf[..], n, SendKind::Connected)
+    }
+
+    /// Sends a packet to `to`, or to the connected address if `to` is `None`.
+    ///
+    /// See [`OscSocket::send_packet`].
+    pub async fn send_packet<P: IntoOscPacket>(
+        &self,
+        packet: P,
+        to: Option<SocketAddr>,
+    ) -> Result<(), Error> {
+        send_packet(self.socket(), self.family, packet, to).await
+    }
+
+    /// Tries to send an OSC packet on the connected socket without waiting.
+    ///
+    /// Returns `Ok(None)` if the socket's send buffer is full and the packet was not sent. This
+    /// never awaits, so it can be called from threads that must not block, like audio callbacks.
+    /// Packets of up to 256 bytes are also encoded without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::OscSocket;
+    ///
+    /// let socket = OscSocket::bind("127.0.0.1:0").await?;
+    /// socket.connect("127.0.0.1:8080").await?;
+    /// let sender = socket.sender();
+    /// if sender.try_send(("/meter", (0.7f32,)))?.is_none() {
+    ///     // Dropped, try again with the next value.
+    /// }
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub fn try_send<P: IntoOscPacket>(&self, packet: P) -> Result<Option<()>, Error> {
+        let buf = codec::encode_small(&packet.into_osc_packet())?;
+        // The socket is in non-blocking mode, so sending directly does not block.
+        match SockRef::from(self.socket()).send(&buf[..]) {
+            Ok(n) => check_len(&buf[..], n, SendKind::Connected).map(Some),
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Sends all packets from a stream on the connected socket.
+    ///
+    /// The stream is driven to completion. Sending stops at the first error, which is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::{prelude::*, OscSocket};
+    /// use async_std::stream;
+    ///
+    /// let socket = OscSocket::bind("127.0.0.1:0").await?;
+    /// socket.connect("127.0.0.1:8080").await?;
+    /// let packets = stream::from_iter((0..3).map(|i| ("/count", (i,))));
+    /// socket.sender().send_all(packets).await?;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn send_all<S>(&self, stream: S) -> Result<(), Error>
+    where
+        S: Stream,
+        S::Item: IntoOscPacket,
+    {
+        futures_lite::pin!(stream);
+        while let Some(packet) = stream.next().await {
+            self.send(packet).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends an OSC packet on the connected socket and waits for an acknowledgement.
+    ///
+    /// An id is appended to the message as two arguments, an [`OscType::Char`] `'!'` that marks
+    /// it and the id as an [`OscType::Long`], so that the type tags end with `ch`. After sending,
+    /// this waits up to `timeout` for a message with the address `ack_addr` from the connected
+    /// peer that has the same id as its first argument, as sent by [`OscSocket::acknowledge`]. If
+    /// none arrives, the message is sent again with the same id, up to `retries` times. This gives
+    /// at-least-once delivery for important messages, provided the peer answers with an
+    /// acknowledgement. Returns the acknowledgement message, or an error of kind
+    /// [`io::ErrorKind::TimedOut`] if all attempts time out. Bundles cannot be acknowledged and
+    /// return [`Error::Unsupported`].
+    ///
+    /// The acknowledgement is read directly from the socket. Other packets received while
+    /// waiting, including acknowledgements of other messages, are discarded, so the socket should
+    /// not be read from elsewhere at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+    /// #
+    /// use async_osc::{prelude::*, OscSocket};
+    /// use std::time::Duration;
+    ///
+    /// let socket = OscSocket::bind("127.0.0.1:0").await?;
+    /// socket.connect("127.0.0.1:8080").await?;
+    /// let sender = socket.sender();
+    /// let timeout = Duration::from_millis(100);
+    /// let ack = sender.send_reliable(("/cue/go", (1,)), "/ack", 3, timeout).await?;
+    /// #
+    /// # Ok(()) }) }
+    /// ```
+    pub async fn send_reliable<P: IntoOscPacket>(
+        &self,
+        packet: P,
+        ack_addr: &str,
+        retries: usize,
+        timeout: Duration,
+    ) -> Result<OscMessage, Error> {
+        static ACK_ID: AtomicI64 = AtomicI64::new(0);
+        let mut message = match packet.into_osc_packet() {
+            OscPacket::Message(message) => message,
+            OscPacket::Bundle(_) => return Err(Error::Unsupported("sending a bundle reliably")),
+        };
+        let id = OscType::Long(ACK_ID.fetch_add(1, Ordering::Relaxed));
+        message.args.push(OscType::Char(ACK_MARKER));
+        message.args.push(id.clone());
+        let encoded = codec::encode_small(&OscPacket::Message(message))?;
+        let mut buf = [0u8; ACK_BUF_LEN];
+        for _ in 0..=retries {
+            self.send_raw(&encoded[..]).await?;
+            let recv = recv_ack(self.socket(), &mut buf, ack_addr, &id);
+            if let Ok(res) = async_std::future::timeout(timeout, recv).await {
+                return res;
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::TimedOut, "no acknowledgement received").into())
+    }
+
+    /// Sends an OSC packet on the connected socket, encoding it into the given buffer.
+    ///
+    /// See [`OscSocket::send_into`].
+    pub async fn send_into<P: IntoOscPacket>(
+        &self,
+        packet: P,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        send_into(self.socket(), packet, buf).await
+    }
+
+    /// Get a reference to the underling [`UdpSocket`].
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+}
+
+async fn recv_ack(
+    socket: &UdpSocket,
+    buf: &mut [u8],
+    addr: &str,
+    id: &OscType,
+) -> Result<OscMessage, Error> {
+    loop {
+        let n = socket.recv(buf).await?;
+        if let Ok(OscPacket::Message(message)) = codec::decode_safe(&buf[..n]) {
+            if message.addr == addr && message.args.first() == Some(id) {
+                return Ok(message);
+            }
+        }
+    }
+}
+
+async fn send_into<P: IntoOscPacket>(
+    socket: &UdpSocket,
+    packet: P,
+    buf: &mut Vec<u8>,
+) -> Result<(), Error> {
+    buf.clear();
+    codec::encode_into(&packet.into_osc_packet(), buf)?;
+    let n = socket.send(&buf[..]).await?;
+    check_len(&buf[..], n, SendKind::Connected)
+}
+
+async fn send_packet<P: IntoOscPacket>(
+    socket: &UdpSocket,
+    family: Option<Family>,
+    packet: P,
+    to: Option<SocketAddr>,
+) -> Result<(), Error> {
+    let buf = codec::encode_small(&packet.into_osc_packet())?;
+    let (n, kind) = match to {
+        Some(addr) => {
+            let n = addr::send_to_any(socket, family, &buf[..], &[addr]).await?;
+            (n, SendKind::Addressed)
+        }
+        None => {
+            if let Err(err) = socket.peer_addr() {
+                let message = "no target address given and the socket is not connected";
+                return Err(io::Error::new(err.kind(), message).into());
+            }
+            (socket.send(&buf[..]).await?, SendKind::Connected)
+        }
+    };
+    check_len(&buf[..], n, kind)
+}
+
+/// The path a packet was sent on, used to report short sends.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SendKind {
+    /// Sent on a connected socket.
+    Connected,
+    /// Sent to an explicitly given address.
+    Addressed,
+}
+
+fn check_len(buf: &[u8], sent: usize, kind: SendKind) -> Result<(), Error> {
+    let len = buf.len();
+    if sent == len {
+        return Ok(());
+    }
+    match kind {
+        SendKind::Connected => Err(Error::ShortSend { sent, len }),
+        SendKind::Addressed => Err(Error::DatagramTooLarge { sent, len }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_len_short_send() {
+        let buf = [0u8; 16];
+        assert!(check_len(&buf, 16, SendKind::Connected).is_ok());
+        assert!(check_len(&buf, 16, SendKind::Addressed).is_ok());
+        assert!(matches!(
+            check_len(&buf, 8, SendKind::Connected),
+            Err(Error::ShortSend { sent: 8, len: 16 })
+        ));
+        assert!(matches!(
+            check_len(&buf, 8, SendKind::Addressed),
+            Err(Error::DatagramTooLarge { sent: 8, len: 16 })
+        ));
+    }
 
-fn check_len(buf: &[u8], len: usize) -> Result<(), Error> {
-    if len != buf.len() {
-        Err(io::Error::new(io::ErrorKind::Interrupted, "UDP packet not fully sent").into())
-    } else {
+    #[async_std::test]
+    async fn ready_registers_once() -> Result<(), Error> {
+        let socket = OscSocket::bind("127.0.0.1:0").await?;
+        socket.ready().await?;
+        let watcher = socket.watcher()?;
+        socket.ready().await?;
+        assert!(Arc::ptr_eq(&watcher, &socket.watcher()?));
+        #[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+        {
+            // Sending with a source address reuses the watcher.
+            let addr = socket.local_addr()?;
+            socket.send_from(("/a", ()), addr.ip(), addr).await?;
+            assert!(Arc::ptr_eq(&watcher, &socket.watcher()?));
+        }
         Ok(())
     }
 }
@@ -0,0 +1,184 @@
+use std::fmt;
+
+/// An OSC address pattern.
+///
+/// Address patterns are matched against the addresses of OSC messages as described in the
+/// [OSC 1.0 specification]. Each part of the address between two slashes is matched separately.
+/// The following wildcards are supported:
+///
+/// - `?` matches any single character
+/// - `*` matches any sequence of zero or more characters
+/// - `[abc]` matches any of the listed characters. `[a-z]` matches a range of characters and
+///   `[!abc]` matches any character that is not listed.
+/// - `{foo,bar}` matches any of the comma-separated strings
+///
+/// A single trailing slash is ignored, both in the pattern and in the matched address, so `/a/`
+/// and `/a` are equivalent. The root address `/` is kept as is.
+///
+/// ```
+/// # use async_osc::OscAddressPattern;
+/// let pattern = OscAddressPattern::new("/mixer/*/volume");
+/// assert!(pattern.matches("/mixer/channel1/volume"));
+/// assert!(!pattern.matches("/mixer/channel1/pan"));
+/// ```
+///
+/// [OSC 1.0 specification]: https://web.archive.org/web/20201211193930/http://opensoundcontrol.org/spec-1_0
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OscAddressPattern {
+    pattern: String,
+}
+
+impl OscAddressPattern {
+    /// Creates a new address pattern.
+    ///
+    /// A trailing slash is removed from the pattern.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        let mut pattern = pattern.into();
+        let len = normalize(&pattern).len();
+        pattern.truncate(len);
+        Self { pattern }
+    }
+
+    /// Returns the pattern as a string.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns `true` if the pattern matches the given address.
+    pub fn matches(&self, addr: &str) -> bool {
+        let mut pattern_parts = self.pattern.split('/');
+        let mut addr_parts = normalize(addr).split('/');
+        loop {
+            match (pattern_parts.next(), addr_parts.next()) {
+                (None, None) => return true,
+                (Some(pattern), Some(part)) => {
+                    let pattern: Vec<char> = pattern.chars().collect();
+                    let part: Vec<char> = part.chars().collect();
+                    if !match_part(&pattern, &part) {
+                        return false;
+                    }
+                }
+                _ => return false,
+            }
+        }
+    }
+
+    /// Returns `true` if the pattern can match `addr` or any address below it.
+    ///
+    /// Each part of `addr` must match the corresponding part of the pattern, and the pattern may
+    /// have further parts. This is useful to skip whole branches of an address tree that no
+    /// pattern can match.
+    ///
+    /// ```
+    /// # use async_osc::OscAddressPattern;
+    /// let pattern = OscAddressPattern::new("/a/*/c");
+    /// assert!(pattern.matches_prefix("/a"));
+    /// assert!(pattern.matches_prefix("/a/b"));
+    /// assert!(!pattern.matches_prefix("/b"));
+    /// assert!(!pattern.matches("/a"));
+    /// ```
+    pub fn matches_prefix(&self, addr: &str) -> bool {
+        let addr = match normalize(addr) {
+            "/" => "",
+            addr => addr,
+        };
+        let mut pattern_parts = self.pattern.split('/');
+        for part in addr.split('/') {
+            let pattern = match pattern_parts.next() {
+                Some(pattern) => pattern,
+                None => return false,
+            };
+            let pattern: Vec<char> = pattern.chars().collect();
+            let part: Vec<char> = part.chars().collect();
+            if !match_part(&pattern, &part) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl fmt::Display for OscAddressPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.pattern.fmt(f)
+    }
+}
+
+impl From<&str> for OscAddressPattern {
+    fn from(pattern: &str) -> Self {
+        Self::new(pattern)
+    }
+}
+
+impl From<String> for OscAddressPattern {
+    fn from(pattern: String) -> Self {
+        Self::new(pattern)
+    }
+}
+
+/// Returns `true` if `addr` contains any OSC pattern metacharacters.
+///
+/// Strings without metacharacters are literal addresses, which only match themselves. Routers can
+/// use this to look up literal routes directly instead of scanning all patterns.
+///
+/// ```
+/// # use async_osc::is_pattern;
+/// assert!(is_pattern("/mixer/*/volume"));
+/// assert!(!is_pattern("/mixer/channel1/volume"));
+/// ```
+pub fn is_pattern(addr: &str) -> bool {
+    addr.contains(&['?', '*', '[', ']', '{', '}'][..])
+}
+
+/// Removes a single trailing slash from an address or pattern, except from the root address.
+pub(crate) fn normalize(addr: &str) -> &str {
+    match addr.strip_suffix('/') {
+        Some(stripped) if !stripped.is_empty() => stripped,
+        _ => addr,
+    }
+}
+
+/// Matches a single part of an address (between two slashes) against a part of a pattern.
+fn match_part(pattern: &[char], part: &[char]) -> bool {
+    match pattern.first() {
+        None => part.is_empty(),
+        Some('*') => (0..=part.len()).any(|i| match_part(&pattern[1..], &part[i..])),
+        Some('?') => !part.is_empty() && match_part(&pattern[1..], &part[1..]),
+        Some('[') => match (pattern.iter().position(|c| *c == ']'), part.first()) {
+            (Some(end), Some(c)) => {
+                match_class(&pattern[1..end], *c) && match_part(&pattern[end + 1..], &part[1..])
+            }
+            _ => false,
+        },
+        Some('{') => match pattern.iter().position(|c| *c == '}') {
+            Some(end) => {
+                let rest = &pattern[end + 1..];
+                pattern[1..end].split(|c| *c == ',').any(|alternative| {
+                    part.starts_with(alternative) && match_part(rest, &part[alternative.len()..])
+                })
+            }
+            None => false,
+        },
+        Some(c) => part.first() == Some(c) && match_part(&pattern[1..], &part[1..]),
+    }
+}
+
+/// Matches a character against the contents of a `[]` character class.
+fn match_class(class: &[char], c: char) -> bool {
+    let (negate, class) = match class.first() {
+        Some('!') => (true, &class[1..]),
+        _ => (false, class),
+    };
+    let mut matched = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            matched |= class[i] <= c && c <= class[i + 2];
+            i += 3;
+        } else {
+            matched |= class[i] == c;
+            i += 1;
+        }
+    }
+    matched != negate
+}
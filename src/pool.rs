@@ -0,0 +1,79 @@
+use std::cell::{Cell, RefCell};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Size of the receive buffers.
+pub(crate) const BUF_SIZE: usize = 1024 * 64;
+
+/// Maximum number of idle buffers kept per thread.
+const MAX_IDLE: usize = 8;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static POOL: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+    static STATS: Cell<PoolStats> = const {
+        Cell::new(PoolStats {
+            allocated: 0,
+            reused: 0,
+        })
+    };
+}
+
+/// Counters for the receive buffers handed out on the current thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Number of newly allocated buffers.
+    pub allocated: u64,
+    /// Number of buffers that were taken from the pool.
+    pub reused: u64,
+}
+
+/// Enables or disables pooling of receive buffers.
+///
+/// Every socket allocates a 64 KiB receive buffer. With pooling enabled, the buffer of a dropped
+/// socket is kept in a thread-local pool and reused for the next socket created on the same
+/// thread. This avoids repeated allocations for applications that create many short-lived
+/// sockets. Pooling is disabled by default.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if pooling of receive buffers is enabled.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Returns the buffer counters for the current thread.
+pub fn stats() -> PoolStats {
+    STATS.with(|stats| stats.get())
+}
+
+pub(crate) fn take() -> Vec<u8> {
+    let buf = if is_enabled() {
+        POOL.with(|pool| pool.borrow_mut().pop())
+    } else {
+        None
+    };
+    STATS.with(|stats| {
+        let mut next = stats.get();
+        match buf {
+            Some(_) => next.reused += 1,
+            None => next.allocated += 1,
+        }
+        stats.set(next);
+    });
+    buf.unwrap_or_else(|| vec![0u8; BUF_SIZE])
+}
+
+pub(crate) fn give_back(buf: Vec<u8>) {
+    if !is_enabled() || buf.len() != BUF_SIZE {
+        return;
+    }
+    // Ignore the error when the thread-local is already destroyed.
+    let _ = POOL.try_with(|pool| {
+        let mut pool = pool.borrow_mut();
+        if pool.len() < MAX_IDLE {
+            pool.push(buf);
+        }
+    });
+}
@@ -0,0 +1,311 @@
+#[cfg(feature = "net")]
+use async_std::stream::{Stream, StreamExt};
+use rosc::{OscMessage, OscPacket};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "net")]
+use crate::error::Error;
+use crate::message::{flatten, format_message};
+use crate::pattern::{is_pattern, normalize, OscAddressPattern};
+
+type Handler = Box<dyn FnMut(&OscMessage, &MatchContext<'_>) + Send>;
+type Fallback = Box<dyn FnMut(&OscMessage, SocketAddr) + Send>;
+
+/// Information about how a message was dispatched to a handler.
+#[derive(Clone, Copy, Debug)]
+pub struct MatchContext<'a> {
+    pattern: &'a OscAddressPattern,
+    peer_addr: SocketAddr,
+}
+
+impl<'a> MatchContext<'a> {
+    /// Returns the registered pattern that matched the message's address.
+    pub fn pattern(&self) -> &'a OscAddressPattern {
+        self.pattern
+    }
+
+    /// Returns the address of the peer the message was received from.
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer_addr
+    }
+}
+
+/// How often [`OscRouter`] logs dispatched messages.
+///
+/// See [`OscRouter::log_messages`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogSampling {
+    /// Logs every message.
+    All,
+    /// Logs the first of every `n` messages. `0` and `1` log every message.
+    OneIn(u64),
+    /// Logs at most one message per address in each interval.
+    ///
+    /// Up to 4096 addresses that were logged within the interval are remembered. While that many
+    /// are remembered, messages with other addresses are not logged.
+    PerAddress(Duration),
+}
+
+/// Maximum number of addresses remembered for [`LogSampling::PerAddress`].
+const MAX_ADDRESSES: usize = 4096;
+
+#[derive(Debug)]
+struct LogSampler {
+    sampling: LogSampling,
+    skip: u64,
+    last_logged: HashMap<String, Instant>,
+    /// When addresses that were logged longer than the interval ago were last removed.
+    last_pruned: Option<Instant>,
+}
+
+impl LogSampler {
+    fn new(sampling: LogSampling) -> Self {
+        Self {
+            sampling,
+            skip: 0,
+            last_logged: HashMap::new(),
+            last_pruned: None,
+        }
+    }
+
+    /// Returns `true` if the message with address `addr` should be logged.
+    fn sample(&mut self, addr: &str) -> bool {
+        match self.sampling {
+            LogSampling::All => true,
+            LogSampling::OneIn(n) => {
+                // Counts down the messages to skip before the next sample.
+                if self.skip == 0 {
+                    self.skip = n.max(1) - 1;
+                    true
+                } else {
+                    self.skip -= 1;
+                    false
+                }
+            }
+            LogSampling::PerAddress(interval) => {
+                let now = Instant::now();
+                match self.last_logged.get_mut(addr) {
+                    Some(last) if now.duration_since(*last) < interval => false,
+                    Some(last) => {
+                        *last = now;
+                        true
+                    }
+                    None => {
+                        if self.last_logged.len() >= MAX_ADDRESSES {
+                            self.prune(now, interval);
+                        }
+                        if self.last_logged.len() >= MAX_ADDRESSES {
+                            return false;
+                        }
+                        self.last_logged.insert(addr.to_string(), now);
+                        true
+                    }
+                }
+            }
+        }
+    }
+
+    /// Forgets the addresses that were logged longer than `interval` ago.
+    ///
+    /// This runs at most once per interval, because only then can more addresses have expired.
+    fn prune(&mut self, now: Instant, interval: Duration) {
+        if let Some(last) = self.last_pruned {
+            if now.duration_since(last) < interval {
+                return;
+            }
+        }
+        self.last_pruned = Some(now);
+        self.last_logged
+            .retain(|_, last| now.duration_since(*last) < interval);
+    }
+}
+
+/// Dispatches received messages to handlers by address pattern.
+///
+/// Routes without wildcards (see [`is_pattern`]) are kept in a map and looked up directly. Each
+/// message is passed to all literal routes for its address, in the order they were added. Only if
+/// there is none, the message is passed to all routes whose [`OscAddressPattern`] matches its
+/// address, again in the order they were added. Messages in bundles are dispatched individually.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[cfg(feature = "net")]
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{OscRouter, OscSocket};
+///
+/// let socket = OscSocket::bind("127.0.0.1:5050").await?;
+/// let mut router = OscRouter::new();
+/// router.route("/mixer/*/volume", |message, cx| {
+///     eprintln!("{} matched {}: {:?}", message.addr, cx.pattern(), message.args);
+/// });
+/// router.run(socket).await?;
+/// #
+/// # Ok(()) }) }
+/// # #[cfg(not(feature = "net"))]
+/// # fn main() {}
+/// ```
+///
+/// [`is_pattern`]: crate::is_pattern
+#[derive(Default)]
+pub struct OscRouter {
+    literals: HashMap<String, Vec<(OscAddressPattern, Handler)>>,
+    patterns: Vec<(OscAddressPattern, Handler)>,
+    fallback: Option<Fallback>,
+    log_sampler: Option<LogSampler>,
+}
+
+impl fmt::Debug for OscRouter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let literals: Vec<_> = self.literals.keys().collect();
+        let patterns: Vec<_> = self.patterns.iter().map(|(pattern, _)| pattern).collect();
+        f.debug_struct("OscRouter")
+            .field("literals", &literals)
+            .field("patterns", &patterns)
+            .field("fallback", &self.fallback.is_some())
+            .field(
+                "log_sampling",
+                &self.log_sampler.as_ref().map(|s| s.sampling),
+            )
+            .finish()
+    }
+}
+
+impl OscRouter {
+    /// Creates a router without routes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a route that calls `handler` for messages matching `pattern`.
+    pub fn route<F>(&mut self, pattern: impl Into<OscAddressPattern>, handler: F) -> &mut Self
+    where
+        F: FnMut(&OscMessage, &MatchContext<'_>) + Send + 'static,
+    {
+        let pattern = pattern.into();
+        let route = (pattern.clone(), Box::new(handler) as Handler);
+        if is_pattern(pattern.as_str()) {
+            self.patterns.push(route);
+        } else {
+            let addr = pattern.as_str().to_string();
+            self.literals.entry(addr).or_default().push(route);
+        }
+        self
+    }
+
+    /// Sets a handler that is called for messages that no route matches.
+    ///
+    /// This is useful to log or reject unknown messages instead of dropping them silently. A
+    /// second call replaces the previous fallback.
+    pub fn fallback<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: FnMut(&OscMessage, SocketAddr) + Send + 'static,
+    {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    /// Logs dispatched messages at the debug level, sampled with `sampling`.
+    ///
+    /// Logging every message of a high-throughput server produces more output than is useful.
+    /// With [`LogSampling::OneIn`] or [`LogSampling::PerAddress`], only a sample of the messages
+    /// is logged. Messages are logged with the `async_osc::router` target, whether or not a
+    /// route matches them. A second call replaces the previous sampling and resets its state.
+    ///
+    /// ```
+    /// # use async_osc::{LogSampling, OscRouter};
+    /// let mut router = OscRouter::new();
+    /// router.log_messages(LogSampling::OneIn(100));
+    /// ```
+    pub fn log_messages(&mut self, sampling: LogSampling) -> &mut Self {
+        self.log_sampler = Some(LogSampler::new(sampling));
+        self
+    }
+
+    /// Dispatches the messages in `packet` to the matching handlers.
+    ///
+    /// Returns the number of handler calls. Calls of the [`fallback`](OscRouter::fallback) handler
+    /// are not counted.
+    pub fn dispatch(&mut self, packet: OscPacket, peer_addr: SocketAddr) -> usize {
+        let mut messages = Vec::new();
+        flatten(packet, &mut messages);
+        let mut calls = 0;
+        for message in &messages {
+            if let Some(sampler) = self.log_sampler.as_mut() {
+                if sampler.sample(&message.addr) {
+                    log::debug!("Routing {} from {}", format_message(message), peer_addr);
+                }
+            }
+            if let Some(routes) = self.literals.get_mut(normalize(&message.addr)) {
+                for (pattern, handler) in routes.iter_mut() {
+                    handler(message, &MatchContext { pattern, peer_addr });
+                    calls += 1;
+                }
+                continue;
+            }
+            let mut matched = false;
+            for (pattern, handler) in self.patterns.iter_mut() {
+                if pattern.matches(&message.addr) {
+                    handler(message, &MatchContext { pattern, peer_addr });
+                    matched = true;
+                    calls += 1;
+                }
+            }
+            if let (false, Some(fallback)) = (matched, self.fallback.as_mut()) {
+                fallback(message, peer_addr);
+            }
+        }
+        calls
+    }
+
+    /// Dispatches all packets received from `stream`.
+    ///
+    /// Packets that fail to decode and transient receive errors are logged and skipped, so a
+    /// malformed packet from a peer does not stop the router. Returns when the stream ends, or
+    /// with the first persistent error.
+    #[cfg(feature = "net")]
+    pub async fn run<S>(mut self, mut stream: S) -> Result<(), Error>
+    where
+        S: Stream<Item = Result<(OscPacket, SocketAddr), Error>> + Unpin,
+    {
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok((packet, peer_addr)) => {
+                    self.dispatch(packet, peer_addr);
+                }
+                Err(err) if err.is_recoverable() => log::debug!("Skipping packet: {}", err),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_sampler_bounds_addresses() {
+        let mut sampler = LogSampler::new(LogSampling::PerAddress(Duration::from_secs(3600)));
+        for i in 0..MAX_ADDRESSES + 10 {
+            sampler.sample(&format!("/meter/{}", i));
+        }
+        assert_eq!(sampler.last_logged.len(), MAX_ADDRESSES);
+        assert!(!sampler.sample("/other"));
+
+        let interval = Duration::from_millis(10);
+        let mut sampler = LogSampler::new(LogSampling::PerAddress(interval));
+        for i in 0..MAX_ADDRESSES {
+            assert!(sampler.sample(&format!("/meter/{}", i)));
+        }
+        std::thread::sleep(interval);
+        // Expired addresses are forgotten to make room for new ones.
+        assert!(sampler.sample("/other"));
+        assert_eq!(sampler.last_logged.len(), 1);
+    }
+}
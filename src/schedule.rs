@@ -0,0 +1,217 @@
+use async_std::task::{self, JoinHandle};
+use futures_lite::future;
+use rosc::{OscMessage, OscPacket};
+use std::time::{Duration, SystemTime};
+
+use crate::cancel::CancellationToken;
+use crate::error::Error;
+use crate::osc::OscSender;
+
+/// A handle to a packet scheduled with [`OscSender::send_at`].
+///
+/// The packet is sent by a background task when its time has come, unless the send was cancelled
+/// before. Dropping the handle does not cancel the send.
+#[derive(Debug)]
+pub struct ScheduledSend {
+    token: CancellationToken,
+    task: JoinHandle<Result<bool, Error>>,
+}
+
+impl ScheduledSend {
+    pub(crate) fn spawn(sender: OscSender, packet: OscPacket, at: SystemTime) -> Self {
+        let token = CancellationToken::new();
+        let cancelled = token.clone();
+        let task = task::spawn(async move {
+            let delay = at.duration_since(SystemTime::now()).unwrap_or_default();
+            let elapsed = async {
+                task::sleep(delay).await;
+                true
+            };
+            let cancel = async {
+                cancelled.cancelled().await;
+                false
+            };
+            // Check the token again, the send may have been cancelled just as the delay elapsed.
+            if !future::or(cancel, elapsed).await || cancelled.is_cancelled() {
+                return Ok(false);
+            }
+            sender.send(packet).await?;
+            Ok(true)
+        });
+        Self { token, task }
+    }
+
+    /// Cancels the send if the packet was not sent yet.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Returns `true` if the send was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Waits until the packet was sent or the send was cancelled.
+    ///
+    /// Returns `true` if the packet was sent and `false` if the send was cancelled.
+    pub async fn wait(self) -> Result<bool, Error> {
+        self.task.await
+    }
+}
+
+/// Plays a list of cues relative to a start time, optionally in a loop.
+///
+/// Each cue is a message with an offset from the start of the playback. The cues are sent with
+/// [`OscSender::send_at`] at their absolute times, so delays in sending one cue do not shift the
+/// following cues. With [`looping`](Self::looping), the cues are repeated every period.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{prelude::*, OscMessage, OscSocket, Scheduler};
+/// use std::time::Duration;
+///
+/// let socket = OscSocket::bind("127.0.0.1:0").await?;
+/// socket.connect("127.0.0.1:8080").await?;
+/// let cues = vec![
+///     (Duration::from_millis(0), OscMessage::new("/light/on", ())),
+///     (Duration::from_millis(500), OscMessage::new("/light/off", ())),
+/// ];
+/// let playback = Scheduler::new(socket.sender(), cues)
+///     .looping(Duration::from_secs(1))
+///     .start();
+/// async_std::task::sleep(Duration::from_secs(10)).await;
+/// playback.stop();
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Scheduler {
+    sender: OscSender,
+    cues: Vec<(Duration, OscMessage)>,
+    period: Option<Duration>,
+}
+
+impl Scheduler {
+    /// Creates a scheduler that sends `cues` with `sender`.
+    ///
+    /// The cues are sorted by their offsets. Cues with the same offset are sent in the given
+    /// order.
+    pub fn new(sender: OscSender, cues: impl IntoIterator<Item = (Duration, OscMessage)>) -> Self {
+        let mut cues: Vec<_> = cues.into_iter().collect();
+        cues.sort_by_key(|(offset, _)| *offset);
+        Self {
+            sender,
+            cues,
+            period: None,
+        }
+    }
+
+    /// Repeats the cues every `period`.
+    ///
+    /// Cues with an offset of `period` or more are not sent. Panics if `period` is zero.
+    pub fn looping(mut self, period: Duration) -> Self {
+        assert!(period > Duration::ZERO, "loop period must not be zero");
+        self.period = Some(period);
+        self
+    }
+
+    /// Returns the cues, sorted by their offsets.
+    pub fn cues(&self) -> &[(Duration, OscMessage)] {
+        &self.cues
+    }
+
+    /// Starts the playback now.
+    pub fn start(&self) -> Playback {
+        self.start_at(SystemTime::now())
+    }
+
+    /// Starts the playback with the cue offsets relative to `start`.
+    ///
+    /// Cues whose time is already past when the playback reaches them are sent right away.
+    pub fn start_at(&self, start: SystemTime) -> Playback {
+        let token = CancellationToken::new();
+        let stopped = token.clone();
+        let scheduler = self.clone();
+        let task = task::spawn(async move { scheduler.play(start, stopped).await });
+        Playback { token, task }
+    }
+
+    async fn play(self, start: SystemTime, stopped: CancellationToken) -> Result<(), Error> {
+        let mut cycle_start = start;
+        loop {
+            for (offset, message) in &self.cues {
+                if matches!(self.period, Some(period) if *offset >= period) {
+                    break;
+                }
+                let mut send = self.sender.send_at(message.clone(), cycle_start + *offset);
+                let stop = async {
+                    stopped.cancelled().await;
+                    None
+                };
+                let sent = async { Some((&mut send.task).await) };
+                match future::or(stop, sent).await {
+                    Some(res) => {
+                        res?;
+                    }
+                    None => {
+                        send.cancel();
+                        return Ok(());
+                    }
+                }
+            }
+            let period = match self.period {
+                Some(period) => period,
+                None => return Ok(()),
+            };
+            // Wait for the end of the cycle, otherwise a loop without cues in the period would
+            // never yield.
+            cycle_start += period;
+            let delay = cycle_start
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+            let stop = async {
+                stopped.cancelled().await;
+                false
+            };
+            let elapsed = async {
+                task::sleep(delay).await;
+                true
+            };
+            if !future::or(stop, elapsed).await {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// A handle to a playback started with [`Scheduler::start`].
+///
+/// Dropping the handle does not stop the playback.
+#[derive(Debug)]
+pub struct Playback {
+    token: CancellationToken,
+    task: JoinHandle<Result<(), Error>>,
+}
+
+impl Playback {
+    /// Stops the playback. Cues that were not sent yet are not sent.
+    pub fn stop(&self) {
+        self.token.cancel();
+    }
+
+    /// Returns `true` if the playback was stopped.
+    pub fn is_stopped(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Waits until all cues were sent or the playback was stopped.
+    ///
+    /// A looping playback only ends when it is stopped. Returns an error if sending a cue
+    /// failed, which ends the playback.
+    pub async fn wait(self) -> Result<(), Error> {
+        self.task.await
+    }
+}
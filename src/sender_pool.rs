@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use crate::error::Error;
+use crate::osc::{OscSender, OscSocket};
+
+/// A pool of connected senders, keyed by target address.
+///
+/// Applications that control many devices can use the pool to get a connected [`OscSender`] for
+/// each device. A socket is bound and connected on the first use of an address and reused
+/// afterwards. Clones share the same pool.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::OscSenderPool;
+///
+/// let pool = OscSenderPool::new();
+/// for device in &["192.168.1.10:9000", "192.168.1.11:9000"] {
+///     let sender = pool.get(device.parse().unwrap()).await?;
+///     sender.send(("/power", (true,))).await?;
+/// }
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OscSenderPool {
+    senders: Arc<Mutex<HashMap<SocketAddr, OscSender>>>,
+}
+
+impl OscSenderPool {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a sender connected to `addr`, creating it if needed.
+    ///
+    /// New sockets are bound to an ephemeral port on the unspecified address of the target's
+    /// address family.
+    pub async fn get(&self, addr: SocketAddr) -> Result<OscSender, Error> {
+        if let Some(sender) = self.senders.lock().unwrap().get(&addr) {
+            return Ok(sender.clone());
+        }
+        let local_addr = if addr.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = OscSocket::bind(local_addr).await?;
+        socket.connect(addr).await?;
+        // Another task may have created a sender for the same address in the meantime.
+        let mut senders = self.senders.lock().unwrap();
+        Ok(senders
+            .entry(addr)
+            .or_insert_with(|| socket.sender())
+            .clone())
+    }
+
+    /// Removes the sender for `addr` from the pool.
+    ///
+    /// The socket is closed once all clones of the sender are dropped.
+    pub fn remove(&self, addr: SocketAddr) -> Option<OscSender> {
+        self.senders.lock().unwrap().remove(&addr)
+    }
+
+    /// Returns the number of senders in the pool.
+    pub fn len(&self) -> usize {
+        self.senders.lock().unwrap().len()
+    }
+
+    /// Returns `true` if the pool has no senders.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,238 @@
+use async_std::stream::Stream;
+use futures_lite::ready;
+use rosc::{OscPacket, OscType};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use crate::error::Error;
+use crate::osc::OscSender;
+use crate::prelude::IntoOscPacket;
+
+/// Marks the argument after it as a sequence number.
+const MARKER: char = '#';
+
+/// Maximum number of peers whose sequence numbers are tracked.
+const MAX_PEERS: usize = 1024;
+
+/// A sender that appends a sequence number to every message.
+///
+/// The sequence number is appended as two arguments, an [`OscType::Char`] `'#'` that marks it and
+/// the number as an [`OscType::Int`], so that the type tags of a sequenced message end with `ci`.
+/// It increases by one for every message sent, wrapping around on overflow. Bundles are sent
+/// unchanged. On the receiving side,
+/// use [`OscSocket::sequenced`](crate::OscSocket::sequenced) to strip the sequence numbers and
+/// detect lost or reordered messages.
+///
+/// Clones share the same counter.
+#[derive(Clone, Debug)]
+pub struct SequencedSender {
+    sender: OscSender,
+    next: Arc<AtomicU32>,
+}
+
+impl SequencedSender {
+    /// Creates a new sequenced sender, starting at sequence number 0.
+    pub fn new(sender: OscSender) -> Self {
+        Self {
+            sender,
+            next: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Sends a packet on the connected socket.
+    ///
+    /// See [`OscSender::send`].
+    pub async fn send<P: IntoOscPacket>(&self, packet: P) -> Result<(), Error> {
+        self.sender.send(self.stamp(packet.into_osc_packet())).await
+    }
+
+    /// Sends a packet to the given address.
+    ///
+    /// See [`OscSender::send_to`].
+    pub async fn send_to<P: IntoOscPacket>(
+        &self,
+        packet: P,
+        addr: SocketAddr,
+    ) -> Result<(), Error> {
+        self.sender
+            .send_to(self.stamp(packet.into_osc_packet()), addr)
+            .await
+    }
+
+    fn stamp(&self, packet: OscPacket) -> OscPacket {
+        match packet {
+            OscPacket::Message(mut message) => {
+                let seq = self.next.fetch_add(1, Ordering::Relaxed);
+                message.args.push(OscType::Char(MARKER));
+                message.args.push(OscType::Int(seq as i32));
+                OscPacket::Message(message)
+            }
+            bundle => bundle,
+        }
+    }
+}
+
+/// Where a received message falls in the sequence of its peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceStatus {
+    /// The message has the next expected sequence number, or is the first from its peer.
+    InOrder,
+    /// One or more messages before this one were lost (or are late).
+    Gap {
+        /// Number of skipped sequence numbers.
+        missing: u32,
+    },
+    /// The message arrived after a message with a higher sequence number.
+    Reordered,
+    /// The packet does not carry a sequence number, e.g. because it is a bundle or was not sent by
+    /// a [`SequencedSender`].
+    Unsequenced,
+}
+
+/// A stream that strips sequence numbers from received messages and detects gaps.
+///
+/// Sequence numbers are tracked for up to 1024 peers. When a message from another peer arrives,
+/// the peer that sent a sequenced message least recently is forgotten, and its next message is
+/// treated like the first one.
+///
+/// See [`OscSocket::sequenced`](crate::OscSocket::sequenced).
+#[derive(Debug)]
+pub struct Sequenced<S> {
+    stream: S,
+    peers: HashMap<SocketAddr, Peer>,
+    /// Counts tracked messages, to find the least recently seen peer.
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Peer {
+    expected: u32,
+    last_seen: u64,
+}
+
+impl<S> Sequenced<S> {
+    pub(crate) fn new(stream: S) -> Self {
+        Self {
+            stream,
+            peers: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Get a reference to the wrapped stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Get a mutable reference to the wrapped stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes the adapter, returning the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    fn track(&mut self, peer_addr: SocketAddr, seq: u32) -> SequenceStatus {
+        self.clock += 1;
+        let clock = self.clock;
+        if !self.peers.contains_key(&peer_addr) && self.peers.len() >= MAX_PEERS {
+            let oldest = self
+                .peers
+                .iter()
+                .min_by_key(|(_, peer)| peer.last_seen)
+                .map(|(addr, _)| *addr);
+            if let Some(oldest) = oldest {
+                self.peers.remove(&oldest);
+            }
+        }
+        let peer = match self.peers.get_mut(&peer_addr) {
+            Some(peer) => peer,
+            None => {
+                let peer = Peer {
+                    expected: seq.wrapping_add(1),
+                    last_seen: clock,
+                };
+                self.peers.insert(peer_addr, peer);
+                return SequenceStatus::InOrder;
+            }
+        };
+        peer.last_seen = clock;
+        // Compare with wrapping arithmetic so that the counter can overflow.
+        match seq.wrapping_sub(peer.expected) as i32 {
+            0 => {
+                peer.expected = seq.wrapping_add(1);
+                SequenceStatus::InOrder
+            }
+            missing if missing > 0 => {
+                peer.expected = seq.wrapping_add(1);
+                SequenceStatus::Gap {
+                    missing: missing as u32,
+                }
+            }
+            _ => SequenceStatus::Reordered,
+        }
+    }
+}
+
+impl<S> Stream for Sequenced<S>
+where
+    S: Stream<Item = Result<(OscPacket, SocketAddr), Error>> + Unpin,
+{
+    type Item = Result<(OscPacket, SocketAddr, SequenceStatus), Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let (packet, peer_addr) = match ready!(Pin::new(&mut self.stream).poll_next(cx)) {
+            Some(Ok(item)) => item,
+            Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+            None => return Poll::Ready(None),
+        };
+        let (packet, status) = match packet {
+            OscPacket::Message(mut message) => match sequence_number(&message.args) {
+                Some(seq) => {
+                    let status = self.track(peer_addr, seq);
+                    message.args.truncate(message.args.len() - 2);
+                    (OscPacket::Message(message), status)
+                }
+                None => (OscPacket::Message(message), SequenceStatus::Unsequenced),
+            },
+            bundle => (bundle, SequenceStatus::Unsequenced),
+        };
+        Poll::Ready(Some(Ok((packet, peer_addr, status))))
+    }
+}
+
+/// Returns the sequence number at the end of `args`, if it is preceded by the marker.
+fn sequence_number(args: &[OscType]) -> Option<u32> {
+    match args {
+        [.., OscType::Char(MARKER), OscType::Int(seq)] => Some(*seq as u32),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgets_least_recently_seen_peer() {
+        let mut sequenced = Sequenced::new(futures_lite::stream::empty::<()>());
+        let addr = |i: usize| SocketAddr::from(([127, 0, 0, 1], i as u16));
+        for i in 0..MAX_PEERS {
+            sequenced.track(addr(i), 0);
+        }
+        // Peer 0 is seen again, so peer 1 is the least recently seen one.
+        assert_eq!(sequenced.track(addr(0), 1), SequenceStatus::InOrder);
+        assert_eq!(sequenced.track(addr(MAX_PEERS), 0), SequenceStatus::InOrder);
+        assert_eq!(sequenced.peers.len(), MAX_PEERS);
+        assert!(!sequenced.peers.contains_key(&addr(1)));
+        assert_eq!(
+            sequenced.track(addr(0), 3),
+            SequenceStatus::Gap { missing: 1 }
+        );
+    }
+}
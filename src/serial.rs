@@ -0,0 +1,111 @@
+use async_io::Async;
+use async_std::stream::Stream;
+use nix::sys::termios::{self, BaudRate, ControlFlags, SetArg};
+use rosc::OscPacket;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::error::Error;
+use crate::framing::{Framing, OscStream};
+use crate::prelude::IntoOscPacket;
+
+/// An OSC endpoint over a serial port, available with the `serial` feature on Unix.
+///
+/// The port is put into raw mode with the given baud rate, 8 data bits and no parity. Packets are
+/// delimited with the given [`Framing`], usually [`Framing::Slip`] for embedded OSC controllers.
+/// Received packets are yielded from the [`Stream`] implementation.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{Framing, OscSerial};
+/// use async_std::stream::StreamExt;
+///
+/// let mut serial = OscSerial::open("/dev/ttyUSB0", 115_200, Framing::Slip)?;
+/// serial.send(("/led", (1,))).await?;
+/// while let Some(packet) = serial.next().await {
+///     eprintln!("Received: {:?}", packet?);
+/// }
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Debug)]
+pub struct OscSerial {
+    stream: OscStream<Async<File>>,
+}
+
+impl OscSerial {
+    /// Opens the serial port at `path` with the given baud rate and framing.
+    ///
+    /// Supported baud rates are 1200, 2400, 4800, 9600, 19200, 38400, 57600 and 115200. Other
+    /// rates fail with an error of kind [`io::ErrorKind::InvalidInput`].
+    pub fn open(path: impl AsRef<Path>, baud: u32, framing: Framing) -> Result<Self, Error> {
+        let baud = baud_rate(baud)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(nix::libc::O_NOCTTY)
+            .open(path)?;
+        let mut attrs = termios::tcgetattr(&file).map_err(io::Error::from)?;
+        termios::cfmakeraw(&mut attrs);
+        attrs.control_flags |= ControlFlags::CLOCAL | ControlFlags::CREAD;
+        termios::cfsetspeed(&mut attrs, baud).map_err(io::Error::from)?;
+        termios::tcsetattr(&file, SetArg::TCSANOW, &attrs).map_err(io::Error::from)?;
+        let io = Async::new(file)?;
+        Ok(Self {
+            stream: OscStream::new(io, framing),
+        })
+    }
+
+    /// Sends a packet on the serial port.
+    pub async fn send<P: IntoOscPacket>(&mut self, packet: P) -> Result<(), Error> {
+        self.stream.send(packet).await
+    }
+
+    /// Sets the maximum length of a received frame in bytes.
+    ///
+    /// See [`OscStream::set_max_frame_len`].
+    pub fn set_max_frame_len(&mut self, max_frame_len: usize) {
+        self.stream.set_max_frame_len(max_frame_len);
+    }
+
+    /// Returns the framing used on the serial port.
+    pub fn framing(&self) -> Framing {
+        self.stream.framing()
+    }
+
+    /// Get a reference to the underlying serial port.
+    pub fn get_ref(&self) -> &File {
+        self.stream.get_ref().get_ref()
+    }
+}
+
+impl Stream for OscSerial {
+    type Item = Result<OscPacket, Error>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.stream).poll_next(cx)
+    }
+}
+
+fn baud_rate(baud: u32) -> io::Result<BaudRate> {
+    Ok(match baud {
+        1200 => BaudRate::B1200,
+        2400 => BaudRate::B2400,
+        4800 => BaudRate::B4800,
+        9600 => BaudRate::B9600,
+        19200 => BaudRate::B19200,
+        38400 => BaudRate::B38400,
+        57600 => BaudRate::B57600,
+        115_200 => BaudRate::B115200,
+        _ => {
+            let message = "unsupported baud rate";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+    })
+}
@@ -0,0 +1,164 @@
+use async_std::channel::{bounded, Receiver, Sender, TrySendError};
+use async_std::stream::{Stream, StreamExt};
+use async_std::task;
+use futures_lite::future;
+use rosc::OscPacket;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use crate::error::Error;
+use crate::osc::OscSocket;
+
+type Item = Result<(OscPacket, SocketAddr), Error>;
+
+/// Number of packets queued for each handle by [`SharedReceiver::new`].
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// A receiver that can be cloned to receive the same packets in several places.
+///
+/// A single background task receives on the socket and sends each packet to all handles. Each
+/// handle receives the packets that arrive after it was created. The task stops when all handles
+/// are dropped.
+///
+/// Receive errors are passed to all handles. Packets that fail to decode are skipped.
+///
+/// Each handle queues up to 1024 packets, or the capacity passed to
+/// [`with_capacity`](SharedReceiver::with_capacity). When the queue of a handle is full because it
+/// is not polled, newer packets are dropped for that handle until it catches up, so a forgotten
+/// clone does not grow memory without limit or hold back the other handles.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{OscSocket, SharedReceiver};
+/// use async_std::stream::StreamExt;
+///
+/// let socket = OscSocket::bind("127.0.0.1:5050").await?;
+/// let mut receiver = SharedReceiver::new(socket);
+/// let mut logger = receiver.clone();
+/// async_std::task::spawn(async move {
+///     while let Some(Ok((packet, peer_addr))) = logger.next().await {
+///         eprintln!("{}: {:?}", peer_addr, packet);
+///     }
+/// });
+/// while let Some(packet) = receiver.next().await {
+///     let (packet, _) = packet?;
+///     // Handle the packet.
+/// }
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Debug)]
+pub struct SharedReceiver {
+    shared: Arc<Shared>,
+    receiver: Receiver<Item>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    local_addr: io::Result<SocketAddr>,
+    subscribers: Arc<Mutex<Vec<Sender<Item>>>>,
+    capacity: usize,
+    // Dropping the sender stops the receive task.
+    _stop: Sender<()>,
+}
+
+impl SharedReceiver {
+    /// Starts receiving on `socket` in a background task.
+    pub fn new(socket: OscSocket) -> Self {
+        Self::with_capacity(socket, DEFAULT_CAPACITY)
+    }
+
+    /// Starts receiving on `socket` in a background task, queueing up to `capacity` packets for
+    /// each handle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(socket: OscSocket, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        let local_addr = socket.socket().local_addr();
+        let (sender, receiver) = bounded(capacity);
+        let subscribers = Arc::new(Mutex::new(vec![sender]));
+        let (stop, stopped) = bounded(1);
+        task::spawn(run(socket, subscribers.clone(), stopped));
+        let shared = Shared {
+            local_addr,
+            subscribers,
+            capacity,
+            _stop: stop,
+        };
+        Self {
+            shared: Arc::new(shared),
+            receiver,
+        }
+    }
+
+    /// Returns the local address of the socket.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        match &self.shared.local_addr {
+            Ok(addr) => Ok(*addr),
+            Err(err) => Err(io::Error::new(err.kind(), err.to_string()).into()),
+        }
+    }
+}
+
+impl Clone for SharedReceiver {
+    fn clone(&self) -> Self {
+        let (sender, receiver) = bounded(self.shared.capacity);
+        self.shared.subscribers.lock().unwrap().push(sender);
+        Self {
+            shared: self.shared.clone(),
+            receiver,
+        }
+    }
+}
+
+impl Stream for SharedReceiver {
+    type Item = Item;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+async fn run(
+    mut socket: OscSocket,
+    subscribers: Arc<Mutex<Vec<Sender<Item>>>>,
+    stopped: Receiver<()>,
+) {
+    loop {
+        let stop = async {
+            let _ = stopped.recv().await;
+            None
+        };
+        let recv = async { socket.next().await.map(Some) };
+        let item = match future::or(stop, recv).await {
+            Some(Some(item)) => item,
+            // Stopped, or the socket stream ended.
+            _ => break,
+        };
+        let mut subscribers = subscribers.lock().unwrap();
+        match item {
+            Ok(item) => subscribers.retain(|subscriber| send(subscriber, Ok(item.clone()))),
+            Err(Error::Io(err)) => subscribers.retain(|subscriber| {
+                let err = io::Error::new(err.kind(), err.to_string());
+                send(subscriber, Err(err.into()))
+            }),
+            Err(err) => log::debug!("Failed to decode packet: {}", err),
+        }
+    }
+    // End the streams of all handles.
+    subscribers.lock().unwrap().clear();
+}
+
+/// Queues `item` for a handle, dropping it if the queue is full.
+///
+/// Returns `false` if the handle was dropped.
+fn send(subscriber: &Sender<Item>, item: Item) -> bool {
+    !matches!(subscriber.try_send(item), Err(TrySendError::Closed(_)))
+}
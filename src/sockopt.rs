@@ -0,0 +1,192 @@
+use async_io::Async;
+use async_std::net::UdpSocket;
+use std::net::{IpAddr, SocketAddr};
+
+use crate::error::Error;
+
+/// Sets the "don't fragment" flag for packets sent on `socket`.
+///
+/// Supported for IPv6 sockets on Linux, Android and Apple platforms, and for IPv4 sockets on
+/// Apple platforms.
+pub(crate) fn set_dont_fragment(socket: &UdpSocket, dont_fragment: bool) -> Result<(), Error> {
+    if socket.local_addr()?.is_ipv6() {
+        set_dont_fragment_v6(socket, dont_fragment)
+    } else {
+        set_dont_fragment_v4(socket, dont_fragment)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+fn set_dont_fragment_v6(socket: &UdpSocket, dont_fragment: bool) -> Result<(), Error> {
+    use nix::sys::socket::{setsockopt, sockopt::Ipv6DontFrag};
+    setsockopt(socket, Ipv6DontFrag, &dont_fragment).map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple")))]
+fn set_dont_fragment_v6(_socket: &UdpSocket, _dont_fragment: bool) -> Result<(), Error> {
+    Err(Error::Unsupported(
+        "setting the don't fragment flag on IPv6 sockets on this platform",
+    ))
+}
+
+#[cfg(target_vendor = "apple")]
+fn set_dont_fragment_v4(socket: &UdpSocket, dont_fragment: bool) -> Result<(), Error> {
+    use nix::sys::socket::{setsockopt, sockopt::IpDontFrag};
+    setsockopt(socket, IpDontFrag, &dont_fragment).map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+#[cfg(not(target_vendor = "apple"))]
+fn set_dont_fragment_v4(_socket: &UdpSocket, _dont_fragment: bool) -> Result<(), Error> {
+    Err(Error::Unsupported(
+        "setting the don't fragment flag on IPv4 sockets on this platform",
+    ))
+}
+
+/// Sets the DSCP value of packets sent on `socket`.
+///
+/// The DSCP is stored in the upper six bits of the IPv4 type of service byte or the IPv6 traffic
+/// class. Setting the traffic class is supported for IPv6 sockets on Linux.
+pub(crate) fn set_dscp(socket: &UdpSocket, dscp: u8) -> Result<(), Error> {
+    if dscp > 63 {
+        let message = format!("DSCP value {} is out of range, must be at most 63", dscp);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, message).into());
+    }
+    let tos = u32::from(dscp) << 2;
+    if socket.local_addr()?.is_ipv6() {
+        set_tclass_v6(socket, tos)
+    } else {
+        socket2::SockRef::from(socket).set_tos_v4(tos)?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_tclass_v6(socket: &UdpSocket, tclass: u32) -> Result<(), Error> {
+    use nix::sys::socket::{setsockopt, sockopt::Ipv6TClass};
+    setsockopt(socket, Ipv6TClass, &(tclass as i32)).map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_tclass_v6(_socket: &UdpSocket, _tclass: u32) -> Result<(), Error> {
+    Err(Error::Unsupported(
+        "setting the traffic class of IPv6 sockets on this platform",
+    ))
+}
+
+/// Sends `buf` to `dst` with `src` as the source address of the datagram.
+///
+/// `socket` is the readiness watcher of the socket, which is used to wait for writability.
+/// Supported on Linux, Android and Apple platforms, using `IP_PKTINFO` and `IPV6_PKTINFO`.
+#[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+pub(crate) async fn send_from(
+    socket: &Async<std::net::UdpSocket>,
+    buf: &[u8],
+    src: IpAddr,
+    dst: SocketAddr,
+) -> Result<usize, Error> {
+    use nix::libc::{in6_addr, in6_pktinfo, in_addr, in_pktinfo};
+    use nix::sys::socket::{sendmsg, ControlMessage, MsgFlags, SockaddrStorage};
+    use std::io::{self, IoSlice};
+    use std::os::unix::io::AsRawFd;
+
+    let v4_info;
+    let v6_info;
+    let cmsg = match (src, dst) {
+        (IpAddr::V4(src), SocketAddr::V4(_)) => {
+            v4_info = in_pktinfo {
+                ipi_ifindex: 0,
+                ipi_spec_dst: in_addr {
+                    s_addr: u32::from(src).to_be(),
+                },
+                ipi_addr: in_addr { s_addr: 0 },
+            };
+            ControlMessage::Ipv4PacketInfo(&v4_info)
+        }
+        (IpAddr::V6(src), SocketAddr::V6(_)) => {
+            v6_info = in6_pktinfo {
+                ipi6_addr: in6_addr {
+                    s6_addr: src.octets(),
+                },
+                ipi6_ifindex: 0,
+            };
+            ControlMessage::Ipv6PacketInfo(&v6_info)
+        }
+        _ => {
+            let message = "source and destination addresses are of different families";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message).into());
+        }
+    };
+    let dst = SockaddrStorage::from(dst);
+    let n = socket
+        .write_with(|socket| {
+            let iov = [IoSlice::new(buf)];
+            sendmsg(
+                socket.as_raw_fd(),
+                &iov,
+                &[cmsg],
+                MsgFlags::empty(),
+                Some(&dst),
+            )
+            .map_err(io::Error::from)
+        })
+        .await?;
+    Ok(n)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple")))]
+pub(crate) async fn send_from(
+    _socket: &Async<std::net::UdpSocket>,
+    _buf: &[u8],
+    _src: IpAddr,
+    _dst: SocketAddr,
+) -> Result<usize, Error> {
+    Err(Error::Unsupported(
+        "setting the source address of datagrams on this platform",
+    ))
+}
+
+/// Returns the number of datagrams the kernel dropped for `socket`.
+///
+/// This reads the `drops` column of `/proc/net/udp` and `/proc/net/udp6`, which is the counter
+/// that `SO_RXQ_OVFL` attaches to received datagrams. Reading it directly also works while no
+/// datagram is queued.
+#[cfg(target_os = "linux")]
+pub(crate) fn dropped_packets(socket: &UdpSocket) -> Result<u64, Error> {
+    use std::io;
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::io::AsRawFd;
+
+    let fd = socket.as_raw_fd();
+    let inode = std::fs::metadata(format!("/proc/self/fd/{}", fd))?.ino();
+    let table = if socket.local_addr()?.is_ipv6() {
+        "/proc/net/udp6"
+    } else {
+        "/proc/net/udp"
+    };
+    let contents = std::fs::read_to_string(table)?;
+    for line in contents.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.get(9).and_then(|field| field.parse::<u64>().ok()) != Some(inode) {
+            continue;
+        }
+        return fields
+            .last()
+            .and_then(|field| field.parse().ok())
+            .ok_or_else(|| {
+                let message = format!("malformed line in {}", table);
+                io::Error::new(io::ErrorKind::InvalidData, message).into()
+            });
+    }
+    let message = format!("socket not found in {}", table);
+    Err(io::Error::new(io::ErrorKind::NotFound, message).into())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn dropped_packets(_socket: &UdpSocket) -> Result<u64, Error> {
+    Err(Error::Unsupported(
+        "reading the dropped packet count on this platform",
+    ))
+}
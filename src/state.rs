@@ -0,0 +1,85 @@
+use async_std::stream::{Stream, StreamExt};
+use rosc::{OscMessage, OscPacket};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use crate::error::Error;
+use crate::message::flatten;
+
+/// A cache of the latest message received for each address.
+///
+/// Many applications, like user interfaces, only need the current value of each address. Feed
+/// received packets into the cache with [`OscState::track`] or [`OscState::update`] and look up
+/// the latest message for an address with [`OscState::get`]. Messages in bundles are cached
+/// individually.
+///
+/// Clones share the same cache, so a clone can be moved into a receive task while the original
+/// is queried.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{OscSocket, OscState};
+///
+/// let socket = OscSocket::bind("127.0.0.1:5050").await?;
+/// let state = OscState::new();
+/// async_std::task::spawn(state.clone().track(socket));
+/// // ...
+/// if let Some(message) = state.get("/volume") {
+///     eprintln!("Current volume: {:?}", message.args);
+/// }
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OscState {
+    messages: Arc<Mutex<HashMap<String, OscMessage>>>,
+}
+
+impl OscState {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the latest message received for `addr`.
+    pub fn get(&self, addr: &str) -> Option<OscMessage> {
+        self.messages.lock().unwrap().get(addr).cloned()
+    }
+
+    /// Returns a copy of all cached messages, keyed by address.
+    pub fn snapshot(&self) -> HashMap<String, OscMessage> {
+        self.messages.lock().unwrap().clone()
+    }
+
+    /// Stores the messages in `packet`, replacing earlier messages with the same address.
+    pub fn update(&self, packet: OscPacket) {
+        let mut messages = Vec::new();
+        flatten(packet, &mut messages);
+        let mut cache = self.messages.lock().unwrap();
+        for message in messages {
+            cache.insert(message.addr.clone(), message);
+        }
+    }
+
+    /// Updates the cache with all packets received from `stream`.
+    ///
+    /// Packets that fail to decode and transient receive errors are logged and skipped. Returns
+    /// when the stream ends, or with the first persistent error.
+    pub async fn track<S>(self, mut stream: S) -> Result<(), Error>
+    where
+        S: Stream<Item = Result<(OscPacket, SocketAddr), Error>> + Unpin,
+    {
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok((packet, _peer_addr)) => self.update(packet),
+                Err(err) if err.is_recoverable() => log::debug!("Skipping packet: {}", err),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+use async_io::Timer;
+use async_std::stream::Stream;
+use futures_lite::{ready, FutureExt};
+use rosc::{OscPacket, OscTime};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::error::Error;
+use crate::prelude::OscPacketExt;
+use crate::time::from_osc_time;
+
+/// Number of bundles held back by [`Scheduled`] unless set with [`Scheduled::with_capacity`].
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// A stream that drops duplicate packets.
+///
+/// See [`OscSocket::dedup`](crate::OscSocket::dedup).
+#[derive(Debug)]
+pub struct Dedup<S> {
+    stream: S,
+    window: Duration,
+    /// Hashes of the packets seen within the window, oldest first, to expire them.
+    seen: VecDeque<(Instant, u64)>,
+    /// The same hashes, to look them up.
+    seen_hashes: HashSet<u64>,
+}
+
+impl<S> Dedup<S> {
+    pub(crate) fn new(stream: S, window: Duration) -> Self {
+        Self {
+            stream,
+            window,
+            seen: VecDeque::new(),
+            seen_hashes: HashSet::new(),
+        }
+    }
+
+    /// Get a reference to the wrapped stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Get a mutable reference to the wrapped stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes the adapter, returning the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> Stream for Dedup<S>
+where
+    S: Stream<Item = Result<(OscPacket, SocketAddr), Error>> + Unpin,
+{
+    type Item = S::Item;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let (packet, peer_addr) = match ready!(Pin::new(&mut self.stream).poll_next(cx)) {
+                Some(Ok(item)) => item,
+                item => return Poll::Ready(item),
+            };
+            let this = &mut *self;
+            let now = Instant::now();
+            while let Some((seen_at, hash)) = this.seen.front() {
+                if now.duration_since(*seen_at) > this.window {
+                    this.seen_hashes.remove(hash);
+                    this.seen.pop_front();
+                } else {
+                    break;
+                }
+            }
+            let hash = packet.content_hash();
+            if !this.seen_hashes.insert(hash) {
+                continue;
+            }
+            this.seen.push_back((now, hash));
+            return Poll::Ready(Some(Ok((packet, peer_addr))));
+        }
+    }
+}
+
+/// A stream that holds back bundles until the time given by their timetag.
+///
+/// Messages and bundles with the timetag "immediately" or a timetag in the past are yielded right
+/// away. Bundles scheduled for the future are yielded when their time has come, ordered by
+/// timetag. With a latency compensation set, bundles are yielded that much earlier, to account
+/// for the time a scheduled action takes to reach its destination.
+///
+/// Up to 1024 bundles, or the capacity set with [`with_capacity`](Scheduled::with_capacity), are
+/// held back. When that many are waiting, newer bundles scheduled for the future are dropped, so
+/// that a peer cannot make the receiver hold arbitrarily many bundles in memory. Use
+/// [`dropped_count`](Scheduled::dropped_count) to detect this.
+///
+/// See [`OscSocket::scheduled`](crate::OscSocket::scheduled).
+#[derive(Debug)]
+pub struct Scheduled<S> {
+    stream: S,
+    compensation: Duration,
+    pending: BTreeMap<(Instant, u64), (OscPacket, SocketAddr)>,
+    capacity: usize,
+    dropped: u64,
+    next_id: u64,
+    timer: Timer,
+    ended: bool,
+}
+
+impl<S> Scheduled<S> {
+    pub(crate) fn new(stream: S) -> Self {
+        Self {
+            stream,
+            compensation: Duration::ZERO,
+            pending: BTreeMap::new(),
+            capacity: DEFAULT_CAPACITY,
+            dropped: 0,
+            next_id: 0,
+            timer: Timer::never(),
+            ended: false,
+        }
+    }
+
+    /// Yields bundles `compensation` earlier than their timetag.
+    ///
+    /// Use this to subtract a known or estimated one-way latency, so that the scheduled action
+    /// happens at the intended time.
+    pub fn with_latency_compensation(mut self, compensation: Duration) -> Self {
+        self.compensation = compensation;
+        self
+    }
+
+    /// Holds back at most `capacity` bundles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must not be zero");
+        self.capacity = capacity;
+        self
+    }
+
+    /// Returns the configured latency compensation.
+    pub fn latency_compensation(&self) -> Duration {
+        self.compensation
+    }
+
+    /// Returns the time at which a bundle with `timetag` is yielded.
+    ///
+    /// Returns `None` for the timetag "immediately".
+    pub fn fire_time(&self, timetag: OscTime) -> Option<SystemTime> {
+        match timetag {
+            (0, 1) => None,
+            timetag => Some(from_osc_time(timetag) - self.compensation),
+        }
+    }
+
+    /// Returns the number of bundles waiting for their time.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the number of bundles dropped because the capacity was reached.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Get a reference to the wrapped stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Get a mutable reference to the wrapped stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes the adapter, returning the wrapped stream.
+    ///
+    /// Bundles that are still waiting for their time are dropped.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Returns the instant to yield `packet` at, or `None` if it is due.
+    fn deadline(&self, packet: &OscPacket) -> Option<Instant> {
+        let fire_time = match packet {
+            OscPacket::Bundle(bundle) => self.fire_time(bundle.timetag)?,
+            OscPacket::Message(_) => return None,
+        };
+        let delay = fire_time.duration_since(SystemTime::now()).ok()?;
+        Some(Instant::now() + delay)
+    }
+}
+
+impl<S> Stream for Scheduled<S>
+where
+    S: Stream<Item = Result<(OscPacket, SocketAddr), Error>> + Unpin,
+{
+    type Item = S::Item;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if !this.ended {
+                match Pin::new(&mut this.stream).poll_next(cx) {
+                    Poll::Ready(Some(Ok((packet, peer_addr)))) => match this.deadline(&packet) {
+                        None => return Poll::Ready(Some(Ok((packet, peer_addr)))),
+                        Some(_) if this.pending.len() >= this.capacity => {
+                            log::debug!(
+                                "Dropping scheduled bundle from {}: too many pending",
+                                peer_addr
+                            );
+                            this.dropped += 1;
+                            continue;
+                        }
+                        Some(deadline) => {
+                            this.pending
+                                .insert((deadline, this.next_id), (packet, peer_addr));
+                            this.next_id += 1;
+                            continue;
+                        }
+                    },
+                    Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                    Poll::Ready(None) => this.ended = true,
+                    Poll::Pending => {}
+                }
+            }
+            let deadline = match this.pending.first_key_value() {
+                Some(((deadline, _), _)) => *deadline,
+                None if this.ended => return Poll::Ready(None),
+                None => return Poll::Pending,
+            };
+            if deadline <= Instant::now() {
+                let item = this.pending.pop_first().map(|(_, item)| Ok(item));
+                return Poll::Ready(item);
+            }
+            this.timer.set_at(deadline);
+            if this.timer.poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+    }
+}
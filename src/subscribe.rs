@@ -0,0 +1,160 @@
+use async_std::channel::{bounded, Receiver, Sender, TrySendError};
+use async_std::net::UdpSocket;
+use async_std::stream::Stream;
+use async_std::task;
+use futures_lite::future;
+use rosc::OscMessage;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+
+use crate::codec;
+use crate::error::Error;
+use crate::message::flatten;
+use crate::pattern::OscAddressPattern;
+use crate::udp;
+
+type Item = Result<(OscMessage, SocketAddr), Error>;
+
+/// Number of messages queued for each subscription.
+const CAPACITY: usize = 1024;
+
+/// A stream of messages matching an address pattern.
+///
+/// See [`OscSocket::subscribe`](crate::OscSocket::subscribe).
+#[derive(Debug)]
+pub struct Subscription {
+    receiver: Receiver<Item>,
+}
+
+impl Stream for Subscription {
+    type Item = Item;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+#[derive(Debug)]
+struct Subscriber {
+    pattern: OscAddressPattern,
+    sender: Sender<Item>,
+}
+
+/// Receives on a socket in a background task and distributes messages to subscribers.
+///
+/// The task is started with the first subscription and stopped when this is dropped. If the task
+/// ends after a persistent error, it passes the error to all subscribers and the next
+/// subscription starts a new task.
+#[derive(Debug, Default)]
+pub(crate) struct FanOut {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    /// Stops the running task when dropped. `None` if no task is running.
+    stop: Arc<Mutex<Option<Sender<()>>>>,
+}
+
+impl FanOut {
+    pub(crate) fn subscribe(
+        &self,
+        socket: &Arc<UdpSocket>,
+        pattern: OscAddressPattern,
+    ) -> Subscription {
+        let (sender, receiver) = bounded(CAPACITY);
+        // Hold the lock while adding the subscriber, so that a task that is ending either passes
+        // its error to the new subscriber or is replaced by a new task.
+        let mut stop = self.stop.lock().unwrap();
+        self.subscribers
+            .lock()
+            .unwrap()
+            .push(Subscriber { pattern, sender });
+        if stop.is_none() {
+            let (stop_sender, stopped) = bounded(1);
+            let fanout = Arc::downgrade(&self.stop);
+            let subscribers = self.subscribers.clone();
+            task::spawn(run(socket.clone(), subscribers, fanout, stopped));
+            *stop = Some(stop_sender);
+        }
+        Subscription { receiver }
+    }
+
+    /// Stops the task and ends all subscriptions with an error of kind
+    /// [`io::ErrorKind::NotConnected`].
+    pub(crate) fn close(&self, reason: &str) {
+        let mut stop = self.stop.lock().unwrap();
+        let mut subscribers = self.subscribers.lock().unwrap();
+        for subscriber in subscribers.drain(..) {
+            let err = io::Error::new(io::ErrorKind::NotConnected, reason);
+            let _ = subscriber.sender.try_send(Err(err.into()));
+        }
+        *stop = None;
+    }
+}
+
+async fn run(
+    socket: Arc<UdpSocket>,
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    fanout: Weak<Mutex<Option<Sender<()>>>>,
+    stopped: Receiver<()>,
+) {
+    let mut buf = vec![0u8; 1024 * 64];
+    let err = loop {
+        let stop = async {
+            let _ = stopped.recv().await;
+            None
+        };
+        let recv = async { Some(socket.recv_from(&mut buf).await) };
+        let res = match future::or(stop, recv).await {
+            Some(res) => res,
+            None => return,
+        };
+        let mut subscribers = subscribers.lock().unwrap();
+        match res {
+            Ok((n, peer_addr)) => match codec::decode_safe(&buf[..n]) {
+                Ok(packet) => {
+                    let mut messages = vec![];
+                    flatten(packet, &mut messages);
+                    for message in messages {
+                        subscribers.retain(|subscriber| {
+                            if subscriber.pattern.matches(&message.addr) {
+                                send(subscriber, Ok((message.clone(), peer_addr)))
+                            } else {
+                                !subscriber.sender.is_closed()
+                            }
+                        });
+                    }
+                }
+                Err(err) => log::debug!("Failed to decode packet from {}: {}", peer_addr, err),
+            },
+            Err(err) if udp::is_transient(&err) => {
+                subscribers.retain(|subscriber| {
+                    let err = io::Error::new(err.kind(), err.to_string());
+                    send(subscriber, Err(err.into()))
+                });
+            }
+            Err(err) => break err,
+        }
+    };
+    // The socket is unusable, so pass the error to the subscribers and end their streams.
+    let fanout = match fanout.upgrade() {
+        Some(fanout) => fanout,
+        None => return,
+    };
+    let mut stop = fanout.lock().unwrap();
+    let mut subscribers = subscribers.lock().unwrap();
+    for subscriber in subscribers.drain(..) {
+        let err = io::Error::new(err.kind(), err.to_string());
+        let _ = subscriber.sender.try_send(Err(err.into()));
+    }
+    *stop = None;
+}
+
+/// Queues `item` for a subscriber, dropping it if the queue is full.
+///
+/// Returns `false` if the subscription was dropped.
+fn send(subscriber: &Subscriber, item: Item) -> bool {
+    !matches!(
+        subscriber.sender.try_send(item),
+        Err(TrySendError::Closed(_))
+    )
+}
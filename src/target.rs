@@ -0,0 +1,91 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::error::Error;
+use crate::osc::OscSender;
+use crate::prelude::IntoOscPacket;
+
+#[cfg(feature = "arc-swap")]
+type Target = arc_swap::ArcSwap<SocketAddr>;
+#[cfg(not(feature = "arc-swap"))]
+type Target = std::sync::Mutex<SocketAddr>;
+
+#[cfg(feature = "arc-swap")]
+fn new_target(target: SocketAddr) -> Target {
+    arc_swap::ArcSwap::from_pointee(target)
+}
+
+#[cfg(not(feature = "arc-swap"))]
+fn new_target(target: SocketAddr) -> Target {
+    std::sync::Mutex::new(target)
+}
+
+/// A sender that sends to a target address that can be changed while sending.
+///
+/// This is useful when the peer's address can change at runtime, for example after it was
+/// discovered again. Clones share the same target, so [`set_target`](Self::set_target) affects
+/// all of them.
+///
+/// By default, the target is protected with a mutex. With the `arc-swap` feature, it is stored
+/// in an `ArcSwap` instead, which makes reading it on every send lock-free. This helps when many
+/// tasks send at a high rate and the target changes rarely.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{OscSocket, TargetedSender};
+///
+/// let socket = OscSocket::bind("0.0.0.0:0").await?;
+/// let sender = TargetedSender::new(socket.sender(), "192.168.1.10:9000".parse().unwrap());
+/// sender.send(("/volume", (0.5f32,))).await?;
+/// sender.set_target("192.168.1.11:9000".parse().unwrap());
+/// sender.send(("/volume", (0.5f32,))).await?;
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TargetedSender {
+    sender: OscSender,
+    target: Arc<Target>,
+}
+
+impl TargetedSender {
+    /// Creates a sender that sends to `target`.
+    pub fn new(sender: OscSender, target: SocketAddr) -> Self {
+        Self {
+            sender,
+            target: Arc::new(new_target(target)),
+        }
+    }
+
+    /// Returns the current target address.
+    #[cfg(feature = "arc-swap")]
+    pub fn target(&self) -> SocketAddr {
+        **self.target.load()
+    }
+
+    /// Returns the current target address.
+    #[cfg(not(feature = "arc-swap"))]
+    pub fn target(&self) -> SocketAddr {
+        *self.target.lock().unwrap()
+    }
+
+    /// Changes the target address for all following sends.
+    #[cfg(feature = "arc-swap")]
+    pub fn set_target(&self, target: SocketAddr) {
+        self.target.store(Arc::new(target));
+    }
+
+    /// Changes the target address for all following sends.
+    #[cfg(not(feature = "arc-swap"))]
+    pub fn set_target(&self, target: SocketAddr) {
+        *self.target.lock().unwrap() = target;
+    }
+
+    /// Sends a packet to the current target address.
+    pub async fn send<P: IntoOscPacket>(&self, packet: P) -> Result<(), Error> {
+        self.sender.send_to(packet, self.target()).await
+    }
+}
@@ -0,0 +1,100 @@
+use std::iter::FromIterator;
+
+use crate::codec;
+use crate::error::Error;
+use crate::osc::OscSender;
+use crate::prelude::IntoOscPacket;
+
+/// A sender that sends each packet over several connected senders.
+///
+/// This can be used for redundant links, for example to send show control messages over two
+/// networks. Each packet is encoded once and sent over all senders, in order. A failure on one
+/// sender does not stop the sends on the others.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::{OscSocket, TeeSender};
+///
+/// let primary = OscSocket::bind("0.0.0.0:0").await?;
+/// primary.connect("10.0.0.10:9000").await?;
+/// let backup = OscSocket::bind("0.0.0.0:0").await?;
+/// backup.connect("10.1.0.10:9000").await?;
+/// let tee = TeeSender::new(vec![primary.sender(), backup.sender()]);
+/// for res in tee.send(("/cue/go", (1,))).await {
+///     if let Err(err) = res {
+///         eprintln!("send failed on one link: {}", err);
+///     }
+/// }
+/// #
+/// # Ok(()) }) }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TeeSender {
+    senders: Vec<OscSender>,
+}
+
+impl TeeSender {
+    /// Creates a tee over `senders`.
+    pub fn new(senders: impl IntoIterator<Item = OscSender>) -> Self {
+        Self {
+            senders: senders.into_iter().collect(),
+        }
+    }
+
+    /// Adds a sender to the tee.
+    pub fn push(&mut self, sender: OscSender) {
+        self.senders.push(sender);
+    }
+
+    /// Returns the senders of the tee.
+    pub fn senders(&self) -> &[OscSender] {
+        &self.senders
+    }
+
+    /// Returns the number of senders.
+    pub fn len(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// Returns `true` if the tee has no senders.
+    pub fn is_empty(&self) -> bool {
+        self.senders.is_empty()
+    }
+
+    /// Sends an OSC packet over all senders.
+    ///
+    /// Returns the result of each send, in the order of the senders. If the packet is too long to
+    /// be encoded, each result is [`Error::DatagramTooLarge`].
+    pub async fn send<P: IntoOscPacket>(&self, packet: P) -> Vec<Result<(), Error>> {
+        let packet = packet.into_osc_packet();
+        let buf = match codec::encode_small(&packet) {
+            Ok(buf) => buf,
+            Err(_) => {
+                let len = codec::estimate_encoded_len(&packet);
+                let err = || Err(Error::DatagramTooLarge { sent: 0, len });
+                return self.senders.iter().map(|_| err()).collect();
+            }
+        };
+        self.send_raw(&buf[..]).await
+    }
+
+    /// Sends already encoded bytes over all senders.
+    ///
+    /// Returns the result of each send, in the order of the senders.
+    pub async fn send_raw(&self, buf: &[u8]) -> Vec<Result<(), Error>> {
+        let mut results = Vec::with_capacity(self.senders.len());
+        for sender in &self.senders {
+            results.push(sender.send_raw(buf).await);
+        }
+        results
+    }
+}
+
+impl FromIterator<OscSender> for TeeSender {
+    fn from_iter<I: IntoIterator<Item = OscSender>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
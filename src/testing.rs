@@ -0,0 +1,95 @@
+use async_std::stream::{Stream, StreamExt};
+use rosc::{OscMessage, OscPacket};
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::codec::{self, DecodeMode};
+use crate::error::Error;
+use crate::message::{format_message, IntoOscMessage};
+
+/// Decodes a datagram exactly like a socket with the given [`DecodeMode`] does.
+///
+/// This is the decode step of the receive path, without a socket, so property tests and fuzzing
+/// harnesses can feed crafted bytes through it.
+///
+/// ```
+/// use async_osc::codec::DecodeMode;
+/// use async_osc::testing::decode_from_bytes;
+///
+/// let bytes = b"/ping\0\0\0,\0\0\0";
+/// assert!(decode_from_bytes(bytes, DecodeMode::Strict).is_ok());
+/// ```
+pub fn decode_from_bytes(buf: &[u8], mode: DecodeMode) -> Result<OscPacket, Error> {
+    codec::decode(buf, mode)
+}
+
+/// Awaits the next packet from `socket` and asserts that it is the `expected` message.
+///
+/// Returns the address of the peer the message was received from.
+///
+/// # Panics
+///
+/// Panics if no packet is received within `timeout`, if receiving fails, or if the received packet
+/// is not equal to `expected`. The panic message lists the arguments that differ.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() -> async_osc::Result<()> { async_std::task::block_on(async {
+/// #
+/// use async_osc::testing::assert_recv;
+/// use async_osc::OscSocket;
+/// use std::time::Duration;
+///
+/// let (mut socket, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+/// socket.send_to(("/ping", (1,)), addr).await?;
+/// assert_recv(&mut socket, ("/ping", (1,)), Duration::from_secs(1)).await;
+/// #
+/// # Ok(()) }) }
+/// ```
+pub async fn assert_recv<S>(
+    socket: &mut S,
+    expected: impl IntoOscMessage,
+    timeout: Duration,
+) -> SocketAddr
+where
+    S: Stream<Item = Result<(OscPacket, SocketAddr), Error>> + Unpin,
+{
+    let expected = expected.into_osc_message();
+    let (packet, peer_addr) = match async_std::future::timeout(timeout, socket.next()).await {
+        Ok(Some(Ok(item))) => item,
+        Ok(Some(Err(err))) => panic!("receiving {} failed: {}", expected.addr, err),
+        Ok(None) => panic!("stream ended while waiting for {}", expected.addr),
+        Err(_) => panic!("no packet received within {:?}", timeout),
+    };
+    match packet {
+        OscPacket::Message(message) if message == expected => peer_addr,
+        OscPacket::Message(message) => panic!("{}", diff(&expected, &message)),
+        OscPacket::Bundle(bundle) => panic!(
+            "expected message {}, received a bundle with {} packets",
+            format_message(&expected),
+            bundle.content.len()
+        ),
+    }
+}
+
+fn diff(expected: &OscMessage, received: &OscMessage) -> String {
+    let mut out = String::from("received message does not match\n");
+    writeln!(out, "  expected: {}", format_message(expected)).unwrap();
+    writeln!(out, "  received: {}", format_message(received)).unwrap();
+    if expected.addr != received.addr {
+        writeln!(out, "  address: {:?} != {:?}", expected.addr, received.addr).unwrap();
+    }
+    let len = expected.args.len().max(received.args.len());
+    for i in 0..len {
+        match (expected.args.get(i), received.args.get(i)) {
+            (Some(a), Some(b)) if a == b => {}
+            (Some(a), Some(b)) => writeln!(out, "  arg {}: {:?} != {:?}", i, a, b).unwrap(),
+            (Some(a), None) => writeln!(out, "  arg {}: {:?} is missing", i, a).unwrap(),
+            (None, Some(b)) => writeln!(out, "  arg {}: unexpected {:?}", i, b).unwrap(),
+            (None, None) => unreachable!(),
+        }
+    }
+    out
+}
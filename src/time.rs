@@ -0,0 +1,140 @@
+use rosc::{OscTime, OscType};
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970).
+const NTP_UNIX_OFFSET: i128 = 2_208_988_800;
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+/// A [`SystemTime`] to be sent as an OSC time tag argument.
+///
+/// Converts into [`OscType::Time`], so it can be used with [`IntoOscArgs`] to carry timestamps
+/// inside messages:
+///
+/// ```
+/// # use async_osc::{*, prelude::*};
+/// use std::time::SystemTime;
+///
+/// let message = OscMessage::new("/cue/at", (Time(SystemTime::now()),));
+/// ```
+///
+/// [`IntoOscArgs`]: crate::prelude::IntoOscArgs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time(pub SystemTime);
+
+impl Time {
+    /// Converts an OSC time tag into a `Time`.
+    ///
+    /// Time tags are interpreted in the NTP era starting in 1900.
+    pub fn from_osc_time(time: OscTime) -> Self {
+        Self(from_osc_time(time))
+    }
+
+    /// Converts the time into an OSC time tag.
+    pub fn to_osc_time(self) -> OscTime {
+        to_osc_time(self.0)
+    }
+}
+
+impl From<SystemTime> for Time {
+    fn from(time: SystemTime) -> Self {
+        Self(time)
+    }
+}
+
+impl From<Time> for OscType {
+    fn from(time: Time) -> Self {
+        OscType::Time(time.to_osc_time())
+    }
+}
+
+impl TryFrom<OscType> for Time {
+    type Error = OscType;
+
+    /// Converts an [`OscType::Time`] argument into a `Time`, returning other arguments unchanged
+    /// as the error.
+    fn try_from(arg: OscType) -> Result<Self, Self::Error> {
+        match arg {
+            OscType::Time(time) => Ok(Self::from_osc_time(time)),
+            arg => Err(arg),
+        }
+    }
+}
+
+/// Extension methods for the [`rosc::OscTime`] type.
+///
+/// These build time tags relative to the current system time, for example to schedule a bundle:
+///
+/// ```
+/// # use async_osc::{*, prelude::*};
+/// use std::time::Duration;
+///
+/// let timetag = OscTime::after(Duration::from_millis(50));
+/// let bundle = OscBundleBuilder::at(timetag)
+///     .push(OscMessage::new("/cue/go", ()))
+///     .build()?;
+/// # Ok::<(), async_osc::Error>(())
+/// ```
+pub trait OscTimeExt {
+    /// Returns the time tag for `duration` from now.
+    fn after(duration: Duration) -> Self;
+
+    /// Returns the time tag for `seconds` from now.
+    ///
+    /// Negative values give a time tag in the past. Values beyond one NTP era (about 136 years)
+    /// in either direction are clamped to it, and NaN is treated as zero.
+    fn in_seconds(seconds: f64) -> Self;
+}
+
+impl OscTimeExt for OscTime {
+    fn after(duration: Duration) -> Self {
+        to_osc_time(SystemTime::now() + duration)
+    }
+
+    fn in_seconds(seconds: f64) -> Self {
+        let now = SystemTime::now();
+        // Time tags wrap around after one era, so larger offsets cannot be represented anyway.
+        let era = u32::MAX as f64;
+        let seconds = if seconds.is_nan() {
+            0.0
+        } else {
+            seconds.clamp(-era, era)
+        };
+        let offset = Duration::from_secs_f64(seconds.abs());
+        if seconds >= 0.0 {
+            to_osc_time(now + offset)
+        } else {
+            to_osc_time(now - offset)
+        }
+    }
+}
+
+/// Converts a system time into an OSC time tag, rounding to the nearest fraction.
+pub(crate) fn to_osc_time(time: SystemTime) -> OscTime {
+    let unix_nanos = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_nanos() as i128,
+        Err(err) => -(err.duration().as_nanos() as i128),
+    };
+    let ntp_nanos = unix_nanos + NTP_UNIX_OFFSET * NANOS_PER_SEC;
+    let mut seconds = ntp_nanos.div_euclid(NANOS_PER_SEC);
+    let nanos = ntp_nanos.rem_euclid(NANOS_PER_SEC);
+    let mut fractional = ((nanos << 32) + NANOS_PER_SEC / 2) / NANOS_PER_SEC;
+    if fractional > u32::MAX as i128 {
+        seconds += 1;
+        fractional = 0;
+    }
+    // Times outside of the first NTP era wrap around, as specified by NTP.
+    (seconds as u32, fractional as u32)
+}
+
+/// Converts an OSC time tag into a system time, rounding to the nearest nanosecond.
+pub(crate) fn from_osc_time((seconds, fractional): OscTime) -> SystemTime {
+    let nanos = ((fractional as i128 * NANOS_PER_SEC) + (1 << 31)) >> 32;
+    let unix_nanos = (seconds as i128 - NTP_UNIX_OFFSET) * NANOS_PER_SEC + nanos;
+    let since = Duration::from_nanos(unix_nanos.unsigned_abs() as u64);
+    if unix_nanos >= 0 {
+        UNIX_EPOCH + since
+    } else {
+        UNIX_EPOCH - since
+    }
+}
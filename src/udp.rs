@@ -6,18 +6,37 @@ use futures_lite::future::Future;
 use futures_lite::ready;
 use std::fmt;
 use std::io;
+use std::mem;
 use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use crate::pool;
+
 pub(crate) type RecvFut =
-    Pin<Box<dyn Future<Output = io::Result<(Vec<u8>, usize, SocketAddr)>> + Send + Sync>>;
+    Pin<Box<dyn Future<Output = (Vec<u8>, io::Result<(usize, SocketAddr)>)> + Send + Sync>>;
 
 pub(crate) struct UdpSocketStream {
     pub(crate) socket: Arc<UdpSocket>,
-    fut: Option<RecvFut>,
-    buf: Option<Vec<u8>>,
+    state: RecvState,
+    /// Number of receive futures created, to check that each yielded datagram takes one.
+    #[cfg(test)]
+    receives: usize,
+}
+
+/// The state of the receive loop.
+///
+/// Each poll makes at most one transition from `Idle` to `Polling` and, if the receive completes,
+/// back to `Idle` (or to `Done` after a persistent error). A poll never starts a second receive.
+enum RecvState {
+    /// Holds the receive buffer and needs to start a receive.
+    Idle(Vec<u8>),
+    /// A receive is pending. The future owns the receive buffer until it completes. Dropping it
+    /// mid receive returns the buffer to the pool.
+    Polling(RecvFut),
+    /// Receiving failed with a persistent error and the stream has ended.
+    Done,
 }
 
 // TODO: Decide if Clone shold be enabled.
@@ -43,11 +62,11 @@ impl UdpSocketStream {
     }
 
     pub fn from_arc(socket: Arc<UdpSocket>) -> Self {
-        let buf = vec![0u8; 1024 * 64];
         Self {
             socket,
-            fut: None,
-            buf: Some(buf),
+            state: RecvState::Idle(pool::take()),
+            #[cfg(test)]
+            receives: 0,
         }
     }
 
@@ -60,40 +79,184 @@ impl UdpSocketStream {
     }
 }
 
+impl Drop for UdpSocketStream {
+    fn drop(&mut self) {
+        if let RecvState::Idle(buf) = mem::replace(&mut self.state, RecvState::Done) {
+            pool::give_back(buf);
+        }
+    }
+}
+
+impl UdpSocketStream {
+    /// Polls for the next datagram and passes it to `f` without copying it.
+    ///
+    /// Returns `None` after an error that is not [transient](is_transient).
+    pub(crate) fn poll_recv_with<T, F>(
+        &mut self,
+        cx: &mut Context<'_>,
+        f: F,
+    ) -> Poll<Option<io::Result<T>>>
+    where
+        F: FnOnce(&[u8], SocketAddr) -> T,
+    {
+        if let RecvState::Idle(_) = self.state {
+            let buf = match mem::replace(&mut self.state, RecvState::Done) {
+                RecvState::Idle(buf) => buf,
+                _ => unreachable!(),
+            };
+            self.state = RecvState::Polling(Box::pin(recv_next(self.socket.clone(), buf)));
+            #[cfg(test)]
+            {
+                self.receives += 1;
+            }
+        }
+        let fut = match &mut self.state {
+            RecvState::Polling(fut) => fut,
+            RecvState::Done => return Poll::Ready(None),
+            RecvState::Idle(_) => unreachable!(),
+        };
+        let (buf, res) = ready!(fut.as_mut().poll(cx));
+        let res = res.map(|(n, addr)| f(&buf[..n], addr));
+        self.state = match &res {
+            // Polling again after a persistent error would fail again immediately.
+            Err(err) if !is_transient(err) => {
+                pool::give_back(buf);
+                RecvState::Done
+            }
+            _ => RecvState::Idle(buf),
+        };
+        Poll::Ready(Some(res))
+    }
+}
+
 impl Stream for UdpSocketStream {
     type Item = io::Result<(Vec<u8>, SocketAddr)>;
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_recv_with(cx, |buf, addr| (buf.to_vec(), addr))
+    }
+}
+
+async fn recv_next(
+    socket: Arc<UdpSocket>,
+    buf: Vec<u8>,
+) -> (Vec<u8>, io::Result<(usize, SocketAddr)>) {
+    let mut buf = PooledBuf(buf);
+    let res = socket.recv_from(&mut buf.0).await;
+    (mem::take(&mut buf.0), res)
+}
+
+/// A receive buffer that is returned to the pool when dropped, so that a pending receive that is
+/// dropped does not lose it.
+struct PooledBuf(Vec<u8>);
+
+impl Drop for PooledBuf {
+    fn drop(&mut self) {
+        pool::give_back(mem::take(&mut self.0));
+    }
+}
+
+/// Returns `true` if receiving can succeed again after `err`.
+///
+/// ICMP errors for earlier sends, like unreachable hosts or networks, are reported by the next
+/// receive on a UDP socket and do not affect later receives. Only errors that mean the socket
+/// itself is unusable are persistent: an invalid descriptor, a descriptor that is not a socket,
+/// invalid arguments and an unconnected stream socket.
+pub(crate) fn is_transient(err: &io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        use nix::libc::{EBADF, ENOTSOCK};
+        if matches!(err.raw_os_error(), Some(EBADF) | Some(ENOTSOCK)) {
+            return false;
+        }
+    }
+    !matches!(
+        err.kind(),
+        io::ErrorKind::InvalidInput | io::ErrorKind::NotConnected | io::ErrorKind::Unsupported
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::future::{poll_fn, poll_once};
+    use std::time::Duration;
+
+    async fn poll(stream: &mut UdpSocketStream) -> Option<Option<io::Result<usize>>> {
+        poll_once(poll_fn(|cx| stream.poll_recv_with(cx, |buf, _| buf.len()))).await
+    }
+
+    async fn wait_for_datagram(stream: &mut UdpSocketStream) -> io::Result<usize> {
         loop {
-            if self.fut.is_none() {
-                let buf = self.buf.take().unwrap();
-                let fut = recv_next(self.socket.clone(), buf);
-                self.fut = Some(Box::pin(fut));
+            if let Some(item) = poll(stream).await {
+                return item.unwrap();
             }
+            async_std::task::sleep(Duration::from_millis(1)).await;
+        }
+    }
 
-            if let Some(f) = &mut self.fut {
-                let res = ready!(f.as_mut().poll(cx));
-                self.fut = None;
-                return match res {
-                    Err(e) => Poll::Ready(Some(Err(e))),
-                    Ok((buf, n, addr)) => {
-                        let res_buf = buf[..n].to_vec();
-                        self.buf = Some(buf);
-                        Poll::Ready(Some(Ok((res_buf, addr))))
-                    }
-                };
-            }
+    #[async_std::test]
+    async fn idle_to_polling_to_idle() -> io::Result<()> {
+        let socket = UdpSocket::bind("127.0.0.1:0").await?;
+        let addr = socket.local_addr()?;
+        let mut stream = UdpSocketStream::new(socket);
+        assert!(matches!(stream.state, RecvState::Idle(_)));
+
+        // Without data the first poll starts a receive and returns pending right away.
+        assert!(poll(&mut stream).await.is_none());
+        assert!(matches!(stream.state, RecvState::Polling(_)));
+        assert_eq!(stream.receives, 1);
+
+        // Polling again keeps the pending receive instead of starting another one.
+        assert!(poll(&mut stream).await.is_none());
+        assert!(matches!(stream.state, RecvState::Polling(_)));
+        assert_eq!(stream.receives, 1);
+
+        let sender = UdpSocket::bind("127.0.0.1:0").await?;
+        for (i, len) in [3, 5, 7].iter().enumerate() {
+            sender.send_to(&vec![0u8; *len], addr).await?;
+            assert_eq!(wait_for_datagram(&mut stream).await?, *len);
+            assert!(matches!(stream.state, RecvState::Idle(_)));
+            // One receive future per yielded datagram.
+            assert_eq!(stream.receives, i + 1);
         }
+        Ok(())
     }
-}
 
-async fn recv_next(
-    socket: Arc<UdpSocket>,
-    mut buf: Vec<u8>,
-) -> io::Result<(Vec<u8>, usize, SocketAddr)> {
-    // let mut buf = vec![0u8; 1024];
-    let res = socket.recv_from(&mut buf).await;
-    match res {
-        Err(e) => Err(e),
-        Ok((n, addr)) => Ok((buf, n, addr)),
+    #[async_std::test]
+    async fn polling_to_done() -> io::Result<()> {
+        // Receiving on an unconnected stream socket fails with the same error every time.
+        let socket = socket2::Socket::new(socket2::Domain::IPV4, socket2::Type::STREAM, None)?;
+        socket.set_nonblocking(true)?;
+        let socket = UdpSocket::from(std::net::UdpSocket::from(socket));
+        let mut stream = UdpSocketStream::new(socket);
+
+        let err = wait_for_datagram(&mut stream).await.unwrap_err();
+        assert!(!is_transient(&err));
+        assert!(matches!(stream.state, RecvState::Done));
+        assert!(matches!(poll(&mut stream).await, Some(None)));
+        assert_eq!(stream.receives, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn transient_errors() {
+        for kind in &[
+            io::ErrorKind::ConnectionRefused,
+            io::ErrorKind::ConnectionReset,
+            io::ErrorKind::HostUnreachable,
+            io::ErrorKind::NetworkUnreachable,
+            io::ErrorKind::TimedOut,
+        ] {
+            assert!(is_transient(&io::Error::from(*kind)), "{:?}", kind);
+        }
+        assert!(!is_transient(&io::Error::from(io::ErrorKind::InvalidInput)));
+        #[cfg(unix)]
+        {
+            use nix::libc::{EBADF, EHOSTDOWN, ENETDOWN, ENOTSOCK};
+            assert!(is_transient(&io::Error::from_raw_os_error(EHOSTDOWN)));
+            assert!(is_transient(&io::Error::from_raw_os_error(ENETDOWN)));
+            assert!(!is_transient(&io::Error::from_raw_os_error(EBADF)));
+            assert!(!is_transient(&io::Error::from_raw_os_error(ENOTSOCK)));
+        }
     }
 }
@@ -0,0 +1,144 @@
+use rosc::{OscArray, OscColor, OscMidiMessage, OscType};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An owned OSC argument value.
+///
+/// This mirrors [`OscType`] with plain Rust types, so that it can be (de)serialized with serde
+/// when the `serde` feature is enabled. Use it to bridge OSC messages to formats like JSON or
+/// YAML, see [`OscMessageExt::to_pairs`](crate::prelude::OscMessageExt::to_pairs).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OscValue {
+    /// 32 bit integer
+    Int(i32),
+    /// 32 bit float
+    Float(f32),
+    /// String
+    String(String),
+    /// Binary blob
+    Blob(Vec<u8>),
+    /// NTP time tag
+    Time {
+        /// Seconds since January 1, 1900.
+        seconds: u32,
+        /// Fractional part of a second.
+        fractional: u32,
+    },
+    /// 64 bit integer
+    Long(i64),
+    /// 64 bit float
+    Double(f64),
+    /// ASCII character
+    Char(char),
+    /// RGBA color
+    Color {
+        /// Red component
+        red: u8,
+        /// Green component
+        green: u8,
+        /// Blue component
+        blue: u8,
+        /// Alpha component
+        alpha: u8,
+    },
+    /// MIDI message
+    Midi {
+        /// MIDI port
+        port: u8,
+        /// Status byte
+        status: u8,
+        /// First data byte
+        data1: u8,
+        /// Second data byte
+        data2: u8,
+    },
+    /// Boolean
+    Bool(bool),
+    /// Array of values
+    Array(Vec<OscValue>),
+    /// Nil
+    Nil,
+    /// Infinitum
+    Inf,
+}
+
+impl From<OscType> for OscValue {
+    fn from(arg: OscType) -> Self {
+        match arg {
+            OscType::Int(x) => OscValue::Int(x),
+            OscType::Float(x) => OscValue::Float(x),
+            OscType::String(x) => OscValue::String(x),
+            OscType::Blob(x) => OscValue::Blob(x),
+            OscType::Time((seconds, fractional)) => OscValue::Time {
+                seconds,
+                fractional,
+            },
+            OscType::Long(x) => OscValue::Long(x),
+            OscType::Double(x) => OscValue::Double(x),
+            OscType::Char(x) => OscValue::Char(x),
+            OscType::Color(x) => OscValue::Color {
+                red: x.red,
+                green: x.green,
+                blue: x.blue,
+                alpha: x.alpha,
+            },
+            OscType::Midi(x) => OscValue::Midi {
+                port: x.port,
+                status: x.status,
+                data1: x.data1,
+                data2: x.data2,
+            },
+            OscType::Bool(x) => OscValue::Bool(x),
+            OscType::Array(x) => OscValue::Array(x.content.into_iter().map(Into::into).collect()),
+            OscType::Nil => OscValue::Nil,
+            OscType::Inf => OscValue::Inf,
+        }
+    }
+}
+
+impl From<OscValue> for OscType {
+    fn from(value: OscValue) -> Self {
+        match value {
+            OscValue::Int(x) => OscType::Int(x),
+            OscValue::Float(x) => OscType::Float(x),
+            OscValue::String(x) => OscType::String(x),
+            OscValue::Blob(x) => OscType::Blob(x),
+            OscValue::Time {
+                seconds,
+                fractional,
+            } => OscType::Time((seconds, fractional)),
+            OscValue::Long(x) => OscType::Long(x),
+            OscValue::Double(x) => OscType::Double(x),
+            OscValue::Char(x) => OscType::Char(x),
+            OscValue::Color {
+                red,
+                green,
+                blue,
+                alpha,
+            } => OscType::Color(OscColor {
+                red,
+                green,
+                blue,
+                alpha,
+            }),
+            OscValue::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            } => OscType::Midi(OscMidiMessage {
+                port,
+                status,
+                data1,
+                data2,
+            }),
+            OscValue::Bool(x) => OscType::Bool(x),
+            OscValue::Array(x) => OscType::Array(OscArray {
+                content: x.into_iter().map(Into::into).collect(),
+            }),
+            OscValue::Nil => OscType::Nil,
+            OscValue::Inf => OscType::Inf,
+        }
+    }
+}
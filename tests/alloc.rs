@@ -0,0 +1,38 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_osc::{codec, prelude::*, OscMessage, OscType};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[test]
+fn small_packets_are_encoded_without_allocating() {
+    let small = OscMessage::new("/mixer/channel/1/volume", (0.8f32, 3, "label")).into_osc_packet();
+    let large = OscMessage::new("/blob", (OscType::Blob(vec![0; 512]),)).into_osc_packet();
+
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    let encoded = codec::encode_small(&small).unwrap();
+    assert_eq!(ALLOCATIONS.load(Ordering::SeqCst), before);
+    assert!(encoded.is_inline());
+    assert_eq!(&encoded[..], &codec::encode(&small).unwrap()[..]);
+
+    let encoded = codec::encode_small(&large).unwrap();
+    assert!(!encoded.is_inline());
+    assert_eq!(&encoded[..], &codec::encode(&large).unwrap()[..]);
+}
@@ -0,0 +1,86 @@
+use async_osc::prelude::*;
+use async_osc::{CaptureHeader, CaptureReader, CaptureWriter, Error, OscMessage, Result};
+use futures_lite::io::Cursor;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[async_std::test]
+async fn session_header_round_trip() -> Result<()> {
+    let start_time = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_000);
+    let header = CaptureHeader {
+        start_time,
+        ..CaptureHeader::new(Some("127.0.0.1:9000".parse().unwrap()))
+    };
+    let peer_addr = "[::1]:5000".parse().unwrap();
+    let packet = OscMessage::new("/volume", (0.5f32,)).into_osc_packet();
+
+    let mut writer = CaptureWriter::new(Cursor::new(Vec::new()), header.clone()).await?;
+    let time = start_time + Duration::from_millis(1500);
+    writer.write(&packet, peer_addr, time).await?;
+    let capture = writer.into_inner().into_inner();
+
+    let mut reader = CaptureReader::new(Cursor::new(capture)).await?;
+    assert_eq!(reader.header(), &header);
+    assert_eq!(reader.header().version, CaptureHeader::VERSION);
+    let record = reader.next_record().await?.unwrap();
+    assert_eq!(record.time, time);
+    assert_eq!(record.peer_addr, peer_addr);
+    assert_eq!(record.packet, packet);
+    assert!(reader.next_record().await?.is_none());
+    Ok(())
+}
+
+#[async_std::test]
+async fn newer_versions_are_rejected() -> Result<()> {
+    let header = CaptureHeader {
+        version: CaptureHeader::VERSION + 1,
+        ..CaptureHeader::new(None)
+    };
+    let writer = CaptureWriter::new(Cursor::new(Vec::new()), header).await?;
+    let capture = writer.into_inner().into_inner();
+    let res = CaptureReader::new(Cursor::new(capture)).await;
+    assert!(matches!(res, Err(Error::Unsupported(_))));
+    let res = CaptureReader::new(Cursor::new(b"not a capture".to_vec())).await;
+    assert!(matches!(res, Err(Error::Io(_))));
+    Ok(())
+}
+
+#[async_std::test]
+async fn corrupt_captures_are_rejected() -> Result<()> {
+    fn header(secs: u64, nanos: u32) -> Vec<u8> {
+        let mut capture = b"OSCCAP".to_vec();
+        capture.extend_from_slice(&CaptureHeader::VERSION.to_be_bytes());
+        capture.extend_from_slice(&secs.to_be_bytes());
+        capture.extend_from_slice(&nanos.to_be_bytes());
+        capture.extend_from_slice(&0u16.to_be_bytes());
+        capture
+    }
+    fn record(offset: u64, len: u32) -> Vec<u8> {
+        let peer_addr = b"127.0.0.1:5000";
+        let mut record = offset.to_be_bytes().to_vec();
+        record.extend_from_slice(&(peer_addr.len() as u16).to_be_bytes());
+        record.extend_from_slice(peer_addr);
+        record.extend_from_slice(&len.to_be_bytes());
+        record
+    }
+    let invalid_data = Some(std::io::ErrorKind::InvalidData);
+
+    for (secs, nanos) in [(0, 1_000_000_000), (u64::MAX, 0)] {
+        let res = CaptureReader::new(Cursor::new(header(secs, nanos))).await;
+        assert_eq!(res.unwrap_err().io_kind(), invalid_data);
+    }
+    let mut records = vec![(0, record(0, u32::MAX))];
+    // Close to the largest time on Unix, so that the record time overflows.
+    if cfg!(unix) {
+        records.push((i64::MAX as u64 - 1_000, record(u64::MAX, 0)));
+    }
+    for (start, record) in records {
+        let mut capture = header(start, 0);
+        capture.extend_from_slice(&record);
+        let mut reader = CaptureReader::new(Cursor::new(capture)).await?;
+        assert_eq!(
+            reader.next_record().await.unwrap_err().io_kind(),
+            invalid_data
+        );
+    }
+    Ok(())
+}
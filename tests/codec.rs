@@ -0,0 +1,308 @@
+use async_osc::codec::{
+    decode, decode_all, decode_base64, decode_header, decode_hex, decode_packet, decode_safe,
+    decode_with_spans, encode, encode_base64, encode_hex, encode_into, estimate_encoded_len,
+    hexdump, CompiledMessage, DecodeMode,
+};
+use async_osc::prelude::*;
+use async_osc::{
+    Error, OscArray, OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscType,
+};
+
+fn all_types_message() -> OscMessage {
+    OscMessage::new(
+        "/all/types",
+        vec![
+            OscType::Int(-4),
+            OscType::Float(1.5),
+            OscType::String("hello".into()),
+            OscType::Blob(vec![1, 2, 3, 4, 5]),
+            OscType::Time((12, 34)),
+            OscType::Long(1 << 40),
+            OscType::Double(0.25),
+            OscType::Char('x'),
+            OscType::Color(OscColor {
+                red: 1,
+                green: 2,
+                blue: 3,
+                alpha: 4,
+            }),
+            OscType::Midi(OscMidiMessage {
+                port: 0,
+                status: 0x90,
+                data1: 60,
+                data2: 127,
+            }),
+            OscType::Bool(true),
+            OscType::Bool(false),
+            OscType::Nil,
+            OscType::Inf,
+            OscType::Array(OscArray {
+                content: vec![OscType::Int(1), OscType::String("abc".into())],
+            }),
+        ],
+    )
+}
+
+fn test_packets() -> Vec<OscPacket> {
+    let message = all_types_message();
+    let empty = OscMessage::new("/empty", Vec::<OscType>::new());
+    let nested = OscBundle {
+        timetag: (1, 2),
+        content: vec![empty.clone().into_osc_packet()],
+    };
+    let bundle = OscBundle {
+        timetag: (3, 4),
+        content: vec![message.clone().into_osc_packet(), OscPacket::Bundle(nested)],
+    };
+    vec![
+        message.into_osc_packet(),
+        empty.into_osc_packet(),
+        OscPacket::Bundle(OscBundle {
+            timetag: (0, 1),
+            content: vec![],
+        }),
+        OscPacket::Bundle(bundle),
+    ]
+}
+
+#[test]
+fn encode_matches_rosc() {
+    for packet in test_packets() {
+        let expected = rosc::encoder::encode(&packet).unwrap();
+        assert_eq!(encode(&packet).unwrap(), expected);
+        assert_eq!(estimate_encoded_len(&packet), expected.len());
+    }
+}
+
+#[test]
+fn encode_into_presized_buffer_does_not_reallocate() {
+    for packet in test_packets() {
+        let mut buf = Vec::with_capacity(estimate_encoded_len(&packet));
+        let ptr = buf.as_ptr();
+        let capacity = buf.capacity();
+        encode_into(&packet, &mut buf).unwrap();
+        assert_eq!(buf.as_ptr(), ptr);
+        assert_eq!(buf.capacity(), capacity);
+        assert_eq!(buf, rosc::encoder::encode(&packet).unwrap());
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn encode_rejects_oversized_blobs() {
+    // The zeroed blob is not written to, so its pages are never allocated.
+    let blob = vec![0u8; i32::MAX as usize + 1];
+    let packet = OscMessage::new("/blob", vec![OscType::Blob(blob)]).into_osc_packet();
+    let len = estimate_encoded_len(&packet);
+    assert!(matches!(
+        encode(&packet),
+        Err(Error::DatagramTooLarge { sent: 0, len: n }) if n == len
+    ));
+    let mut buf = Vec::new();
+    assert!(encode_into(&packet, &mut buf).is_err());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn lenient_decode_ignores_trailing_bytes() {
+    let bundle = OscPacket::Bundle(OscBundle {
+        timetag: (1, 2),
+        content: vec![OscMessage::new("/foo", (1,)).into_osc_packet()],
+    });
+    let mut buf = encode(&bundle).unwrap();
+    let len = buf.len();
+    buf.extend_from_slice(&[0, 0, 0, 4, b'j', b'u', b'n', b'k']);
+
+    assert!(decode(&buf, DecodeMode::Strict).is_err());
+    assert_eq!(decode(&buf, DecodeMode::Lenient).unwrap(), bundle);
+    let (packet, consumed) = decode_packet(&buf).unwrap();
+    assert_eq!(packet, bundle);
+    assert_eq!(consumed, len);
+
+    let message = all_types_message().into_osc_packet();
+    let mut buf = encode(&message).unwrap();
+    let len = buf.len();
+    buf.extend_from_slice(b"\0\0junk");
+    assert_eq!(decode_packet(&buf).unwrap(), (message, len));
+}
+
+#[test]
+fn lenient_decode_accepts_address_only_messages() {
+    let ping = OscMessage::new("/ping", ()).into_osc_packet();
+    let inputs: &[&[u8]] = &[
+        // Empty type tag string.
+        b"/ping\0\0\0,\0\0\0",
+        // Missing type tag string.
+        b"/ping\0\0\0",
+        b"/ping",
+        // Missing type tag string, followed by padding and junk.
+        b"/ping\0\0\0\0\0\0\0junk",
+    ];
+    for input in inputs {
+        assert_eq!(
+            decode(input, DecodeMode::Lenient).unwrap(),
+            ping,
+            "{:?}",
+            input
+        );
+    }
+    let (_, len) = decode_packet(b"/ping\0\0\0\0\0\0\0junk").unwrap();
+    assert_eq!(len, 8);
+}
+
+#[test]
+fn decode_safe_rejects_malformed_input() {
+    let mut deep = encode(&OscMessage::new("/deep", ()).into_osc_packet()).unwrap();
+    for _ in 0..64 {
+        let mut bundle = b"#bundle\0\0\0\0\0\0\0\0\x01".to_vec();
+        bundle.extend_from_slice(&(deep.len() as u32).to_be_bytes());
+        bundle.extend_from_slice(&deep);
+        deep = bundle;
+    }
+    let inputs: Vec<&[u8]> = vec![
+        // Truncated midi and color arguments.
+        b"/m\0\0,m\0\0\x01",
+        b"/r\0\0,r\0\0",
+        // Blob with a huge length.
+        b"/b\0\0,b\0\0\xff\xff\xff\xff",
+        // Arguments missing.
+        b"/i\0\0,ifs\0\0\0\0\0\0\0\x01",
+        // Unknown type tag and unbalanced array.
+        b"/x\0\0,x\0\0",
+        b"/a\0\0,]\0\0",
+        // Bundle with a truncated element.
+        b"#bundle\0\0\0\0\0\0\0\0\x01\0\0\0\x08/m\0\0,m\0\0",
+        b"#bund",
+        b"",
+        b"\xff\xfe",
+        &deep,
+    ];
+    for input in inputs {
+        assert!(decode_safe(input).is_err(), "input: {:?}", input);
+        assert!(decode(input, DecodeMode::Lenient).is_err() || input.starts_with(b"#bundle"));
+        let _ = decode_packet(input);
+    }
+}
+
+#[test]
+fn decode_safe_accepts_valid_input() {
+    for packet in test_packets() {
+        assert_eq!(decode_safe(&encode(&packet).unwrap()).unwrap(), packet);
+    }
+}
+
+#[test]
+fn decode_with_spans_points_at_args() {
+    let message = OscMessage::new("/spans", (7, "hello"));
+    let buf = encode(&message.clone().into_osc_packet()).unwrap();
+    let (decoded, spans) = decode_with_spans(&buf).unwrap();
+    assert_eq!(decoded, message);
+    assert_eq!(spans.len(), 2);
+    assert_eq!(&buf[spans[0].clone()], &7i32.to_be_bytes());
+    assert_eq!(&buf[spans[1].clone()], b"hello\0\0\0");
+
+    let message = all_types_message();
+    let buf = encode(&message.clone().into_osc_packet()).unwrap();
+    let (_, spans) = decode_with_spans(&buf).unwrap();
+    assert_eq!(spans.len(), message.args.len());
+    assert_eq!(spans.last().unwrap().end, buf.len());
+
+    let bundle = OscPacket::Bundle(OscBundle {
+        timetag: (0, 1),
+        content: vec![],
+    });
+    assert!(decode_with_spans(&encode(&bundle).unwrap()).is_err());
+}
+
+#[test]
+fn text_encodings_round_trip() {
+    for packet in test_packets() {
+        let text = encode_base64(&packet).unwrap();
+        assert_eq!(text.len() % 4, 0);
+        assert_eq!(decode_base64(&text).unwrap(), packet);
+        assert_eq!(decode_base64(text.trim_end_matches('=')).unwrap(), packet);
+        let text = encode_hex(&packet).unwrap();
+        assert_eq!(decode_hex(&text).unwrap(), packet);
+        assert_eq!(decode_hex(&text.to_uppercase()).unwrap(), packet);
+    }
+    assert!(decode_base64("L3Bp*mcAAAAALAAAAA==").is_err());
+    assert!(decode_base64("L").is_err());
+    assert!(decode_hex("2f6").is_err());
+    assert!(decode_hex("2g").is_err());
+    assert!(decode_hex("2fé").is_err());
+}
+
+#[test]
+fn compiled_message_matches_encode() {
+    let message = all_types_message();
+    let compiled = CompiledMessage::new(&message.addr, ",ifsbthdcrmTFNI[is]").unwrap();
+    let expected = encode(&message.clone().into_osc_packet()).unwrap();
+    assert_eq!(&compiled.encode(&message.args).unwrap()[..], &expected[..]);
+    assert!(compiled.encode(&message.args[1..]).is_err());
+    assert!(compiled.encode(&[]).is_err());
+    assert!(CompiledMessage::new("/a", ",ix").is_err());
+    assert!(CompiledMessage::new("/a", ",[i").is_err());
+}
+
+#[test]
+fn decode_header_matches_full_decode() {
+    let messages = vec![
+        all_types_message(),
+        OscMessage::new("/empty", ()),
+        OscMessage::new("/mixer/channel/1/volume", (0.8f32, 3, "label")),
+    ];
+    for message in messages {
+        let buf = encode(&message.clone().into_osc_packet()).unwrap();
+        let (addr, tags) = decode_header(&buf).unwrap();
+        let decoded = decode_safe(&buf).unwrap().into_message().unwrap();
+        assert_eq!(addr, decoded.addr);
+        assert!(decoded.matches(&addr, &tags), "{} {}", addr, tags);
+    }
+    assert_eq!(
+        decode_header(b"/a\0\0").unwrap(),
+        ("/a".to_string(), String::new())
+    );
+
+    let bundle = OscPacket::Bundle(OscBundle {
+        timetag: (0, 1),
+        content: vec![],
+    });
+    assert!(decode_header(&encode(&bundle).unwrap()).is_err());
+    assert!(decode_header(b"/unterminated").is_err());
+    assert!(decode_header(&[]).is_err());
+}
+
+#[test]
+fn decode_all_concatenated_packets() {
+    let packets = vec![
+        OscMessage::new("/a", (1,)).into_osc_packet(),
+        OscPacket::Bundle(OscBundle {
+            timetag: (0, 1),
+            content: vec![OscMessage::new("/b", ("x",)).into_osc_packet()],
+        }),
+        all_types_message().into_osc_packet(),
+    ];
+    let mut buf = Vec::new();
+    for packet in &packets {
+        encode_into(packet, &mut buf).unwrap();
+    }
+    let decoded = decode_all(&buf).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(decoded, packets);
+
+    assert_eq!(decode_all(&[]).count(), 0);
+    buf.extend_from_slice(b"junk");
+    let results: Vec<_> = decode_all(&buf).collect();
+    assert_eq!(results.len(), 4);
+    assert!(results[3].is_err());
+}
+
+#[test]
+fn hexdump_format() {
+    assert_eq!(hexdump(&[]), "");
+    let buf = encode(&OscMessage::new("/hello", ("wörld",)).into_osc_packet()).unwrap();
+    let expected = [
+        "0000  2f 68 65 6c  6c 6f 00 00  2c 73 00 00  77 c3 b6 72  |/hello..,s..w..r|",
+        "0010  6c 64 00 00                                         |ld..|",
+    ];
+    assert_eq!(hexdump(&buf), expected.join("\n"));
+}
@@ -0,0 +1,48 @@
+use async_osc::prelude::*;
+use async_osc::{Error, FromOscMessage, OscMessage, OscType};
+use std::convert::TryFrom;
+
+#[derive(Debug, PartialEq, FromOscMessage)]
+enum Command {
+    #[osc(addr = "/transport/play")]
+    Play,
+    #[osc(addr = "/mixer/volume")]
+    Volume(i32, f32),
+}
+
+#[derive(Debug, PartialEq, FromOscMessage)]
+enum Label {
+    #[osc(addr = "/label")]
+    Set { channel: i32, text: String },
+}
+
+#[test]
+fn derive_from_osc_message() {
+    let message = OscMessage::new("/transport/play", ());
+    assert_eq!(Command::try_from(&message).unwrap(), Command::Play);
+    let message = OscMessage::new("/mixer/volume", (3, 0.5f32));
+    assert_eq!(
+        Command::try_from(&message).unwrap(),
+        Command::Volume(3, 0.5)
+    );
+    let message = OscMessage::new("/label", (1, "drums"));
+    let label = Label::Set {
+        channel: 1,
+        text: "drums".to_string(),
+    };
+    assert_eq!(Label::try_from(&message).unwrap(), label);
+}
+
+#[test]
+fn derive_rejects_mismatches() {
+    let messages = vec![
+        OscMessage::new("/transport/stop", ()),
+        OscMessage::new("/transport/play", (1,)),
+        OscMessage::new("/mixer/volume", (3,)),
+        OscMessage::new("/mixer/volume", vec![OscType::Int(3), OscType::Double(0.5)]),
+    ];
+    for message in &messages {
+        let res = Command::try_from(message);
+        assert!(matches!(res, Err(Error::Osc(_))), "{:?}", message);
+    }
+}
@@ -0,0 +1,113 @@
+use async_osc::prelude::*;
+use async_osc::{Error, Framing, OscMessage, OscStream, Result};
+use async_std::stream::StreamExt;
+use futures_lite::io::Cursor;
+
+#[cfg(unix)]
+#[async_std::test]
+async fn duplex_round_trip() -> Result<()> {
+    use async_std::os::unix::net::UnixStream;
+
+    for framing in &[Framing::LengthPrefixed, Framing::Slip] {
+        let (a, b) = UnixStream::pair()?;
+        let mut a = OscStream::new(a, *framing);
+        let mut b = OscStream::new(b, *framing);
+
+        a.send(("/ping", (1, "one"))).await?;
+        let packet = b.next().await.unwrap()?;
+        assert_eq!(
+            packet,
+            OscMessage::new("/ping", (1, "one")).into_osc_packet()
+        );
+
+        b.send(("/pong", ())).await?;
+        let packet = a.next().await.unwrap()?;
+        assert_eq!(packet, OscMessage::new("/pong", ()).into_osc_packet());
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn slip_escapes_delimiters() -> Result<()> {
+    let message = OscMessage::new("/blob", (rosc::OscType::Blob(vec![0xC0, 0xDB, 0xDC]),));
+
+    let mut writer = OscStream::new(Cursor::new(Vec::new()), Framing::Slip);
+    writer.send(message.clone()).await?;
+    writer.send(message.clone()).await?;
+    let bytes = writer.into_inner().into_inner();
+    assert_eq!(bytes[0], 0xC0);
+
+    let mut reader = OscStream::new(Cursor::new(bytes), Framing::Slip);
+    for _ in 0..2 {
+        let packet = reader.next().await.unwrap()?;
+        assert_eq!(packet, message.clone().into_osc_packet());
+    }
+    assert!(reader.next().await.is_none());
+    Ok(())
+}
+
+#[async_std::test]
+async fn length_prefix_over_limit() -> Result<()> {
+    // A length prefix of 4 GiB - 1 with only a few bytes following.
+    let mut bytes = vec![0xff, 0xff, 0xff, 0xff];
+    bytes.extend_from_slice(b"/a\0\0");
+    let mut reader = OscStream::new(Cursor::new(bytes), Framing::LengthPrefixed);
+    reader.set_max_frame_len(1024);
+    let err = reader.next().await.unwrap().unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::InvalidData));
+    // The next frame cannot be found, so the stream ends.
+    assert!(reader.next().await.is_none());
+    Ok(())
+}
+
+#[async_std::test]
+async fn slip_frame_over_limit() -> Result<()> {
+    let message = OscMessage::new("/ok", (1,));
+    let mut writer = OscStream::new(Cursor::new(Vec::new()), Framing::Slip);
+    writer.send(message.clone()).await?;
+    let frame = writer.into_inner().into_inner();
+
+    // An unterminated frame longer than the limit, followed by a valid frame.
+    let mut bytes = vec![0xC0];
+    bytes.resize(1 + 10_000, b'x');
+    bytes.extend_from_slice(&frame);
+    let mut reader = OscStream::new(Cursor::new(bytes), Framing::Slip);
+    reader.set_max_frame_len(1024);
+    assert_eq!(reader.max_frame_len(), 1024);
+    match reader.next().await.unwrap() {
+        Err(err @ Error::Io(_)) => {
+            assert_eq!(err.io_kind(), Some(std::io::ErrorKind::InvalidData))
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+    // The rest of the oversized frame is dropped and the next frame is received.
+    let packet = reader.next().await.unwrap()?;
+    assert_eq!(packet, message.into_osc_packet());
+    assert!(reader.next().await.is_none());
+    Ok(())
+}
+
+#[async_std::test]
+async fn slip_terminated_frame_over_limit() -> Result<()> {
+    let message = OscMessage::new("/ok", (1,));
+    let mut writer = OscStream::new(Cursor::new(Vec::new()), Framing::Slip);
+    writer.send(message.clone()).await?;
+    writer.send(message.clone()).await?;
+    let frames = writer.into_inner().into_inner();
+
+    // A terminated frame longer than the limit, followed by two valid frames, all read at once.
+    let mut bytes = vec![0xC0];
+    bytes.resize(1 + 2_000, b'x');
+    bytes.extend_from_slice(&frames);
+    let mut reader = OscStream::new(Cursor::new(bytes), Framing::Slip);
+    reader.set_max_frame_len(1024);
+    let err = reader.next().await.unwrap().unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::InvalidData));
+    // The frames after the oversized frame are kept.
+    for _ in 0..2 {
+        let packet = reader.next().await.unwrap()?;
+        assert_eq!(packet, message.clone().into_osc_packet());
+    }
+    assert!(reader.next().await.is_none());
+    Ok(())
+}
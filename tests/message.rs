@@ -0,0 +1,604 @@
+use async_osc::prelude::*;
+use async_osc::rosc::{OscArray, OscColor, OscMidiMessage};
+use async_osc::{
+    format_message, format_message_with, is_pattern, AddressPolicy, Error, FormatOptions,
+    LogSampling, OscAddressPattern, OscBundle, OscBundleBuilder, OscMessage, OscNumber, OscPacket,
+    OscRouter, OscTime, OscType, OscValue, Time,
+};
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[test]
+fn bundle_try_from_iter() {
+    let messages: Vec<Result<OscMessage, Error>> = vec![
+        Ok(OscMessage::new("/a", (1,))),
+        Ok(OscMessage::new("/b", (2,))),
+    ];
+    let bundle = OscBundle::try_from_iter(messages).unwrap();
+    assert_eq!(
+        bundle.content,
+        vec![
+            OscPacket::Message(OscMessage::new("/a", (1,))),
+            OscPacket::Message(OscMessage::new("/b", (2,))),
+        ]
+    );
+
+    let messages: Vec<Result<OscMessage, Error>> = vec![
+        Ok(OscMessage::new("/a", (1,))),
+        Err(Error::Osc(rosc::OscError::BadMessage("invalid"))),
+        Ok(OscMessage::new("/b", (2,))),
+    ];
+    let err = OscBundle::try_from_iter(messages).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Osc(rosc::OscError::BadMessage("invalid"))
+    ));
+}
+
+#[test]
+fn numeric_args() {
+    let cases = vec![
+        (OscType::Int(-3), OscNumber::Int(-3), -3.0, Some(-3)),
+        (
+            OscType::Long(1 << 40),
+            OscNumber::Long(1 << 40),
+            (1u64 << 40) as f64,
+            Some(1 << 40),
+        ),
+        (OscType::Float(0.5), OscNumber::Float(0.5), 0.5, None),
+        (
+            OscType::Double(-2.25),
+            OscNumber::Double(-2.25),
+            -2.25,
+            None,
+        ),
+    ];
+    for (arg, number, float, int) in cases {
+        assert_eq!(arg.as_number(), Some(number));
+        assert_eq!(arg.as_f64(), Some(float));
+        assert_eq!(arg.as_i64(), int);
+    }
+
+    let arg = OscType::String("1".into());
+    assert_eq!(arg.as_number(), None);
+    assert_eq!(arg.as_f64(), None);
+    assert_eq!(arg.as_i64(), None);
+}
+
+#[test]
+fn address_pattern_matching() {
+    let cases = vec![
+        ("/foo/bar", "/foo/bar", true),
+        ("/foo/bar", "/foo/baz", false),
+        ("/foo/bar", "/foo/bar/baz", false),
+        ("/foo/?ar", "/foo/bar", true),
+        ("/foo/*", "/foo/bar", true),
+        ("/foo/*", "/foo/bar/baz", false),
+        ("/*/bar", "/foo/bar", true),
+        ("/foo/b*r", "/foo/bazaar", true),
+        ("/foo/b*r", "/foo/baz", false),
+        ("/foo/[abc]ar", "/foo/bar", true),
+        ("/foo/[!abc]ar", "/foo/bar", false),
+        ("/foo/[!abc]ar", "/foo/far", true),
+        ("/ch[0-9]", "/ch7", true),
+        ("/ch[0-9]", "/chx", false),
+        ("/{play,stop}", "/stop", true),
+        ("/{play,stop}", "/pause", false),
+        ("/{play,stop}*", "/playing", true),
+    ];
+    for (pattern, addr, expected) in cases {
+        let matched = OscAddressPattern::new(pattern).matches(addr);
+        assert_eq!(matched, expected, "pattern {} address {}", pattern, addr);
+    }
+}
+
+#[test]
+fn address_pattern_prefix_matching() {
+    let cases = vec![
+        ("/a/*/c", "/", true, false),
+        ("/a/*/c", "/a", true, false),
+        ("/a/*/c", "/a/", true, false),
+        ("/a/*/c", "/a/b", true, false),
+        ("/a/*/c", "/a/b/c", true, true),
+        ("/a/*/c", "/a/b/d", false, false),
+        ("/a/*/c", "/a/b/c/d", false, false),
+        ("/a/*/c", "/b", false, false),
+        ("/{a,b}/c", "/b", true, false),
+        ("/ch[0-9]/level", "/chx", false, false),
+    ];
+    for (pattern, addr, prefix, full) in cases {
+        let pattern = OscAddressPattern::new(pattern);
+        assert_eq!(
+            pattern.matches_prefix(addr),
+            prefix,
+            "{} under {}",
+            pattern,
+            addr
+        );
+        assert_eq!(pattern.matches(addr), full, "{} matches {}", pattern, addr);
+    }
+}
+
+#[test]
+fn match_address_and_type_tags() {
+    let args = vec![
+        OscType::Int(1),
+        OscType::Float(0.5),
+        OscType::String("main".into()),
+        OscType::Bool(true),
+    ];
+    let message = OscMessage::new("/mixer/label", args);
+    assert!(message.matches("/mixer/label", "ifsT"));
+    assert!(message.matches("/mixer/label/", ",ifsT"));
+    assert!(!message.matches("/mixer/label", "ifsF"));
+    assert!(!message.matches("/mixer/label", "ifs"));
+    assert!(!message.matches("/mixer/name", "ifsT"));
+    let array = OscType::Array(OscArray {
+        content: vec![OscType::Int(1), OscType::Nil],
+    });
+    assert!(OscMessage::new("/list", vec![array]).matches("/list", "[iN]"));
+    assert!(OscMessage::new("/empty", ()).matches("/empty", ""));
+}
+
+#[test]
+fn validate_signature() {
+    let message = OscMessage::new("/pan", (0.5f32, 0.25f32));
+    assert!(message.validate_signature("ff").is_ok());
+    assert!(message.validate_signature(",ff").is_ok());
+    let err = message.validate_signature("fs").unwrap_err();
+    match &err {
+        Error::SignatureMismatch {
+            addr,
+            expected,
+            actual,
+        } => {
+            assert_eq!(addr, "/pan");
+            assert_eq!(expected, "fs");
+            assert_eq!(actual, "ff");
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+    assert_eq!(
+        err.to_string(),
+        "argument types of /pan do not match: expected \",fs\", got \",ff\""
+    );
+}
+
+#[test]
+fn detect_patterns() {
+    for addr in &["/", "/foo", "/foo/bar", "/foo-bar/1.0", "/a,b/!"] {
+        assert!(!is_pattern(addr), "{} is literal", addr);
+    }
+    for pattern in &[
+        "/foo/?ar",
+        "/foo/*",
+        "/ch[0-9]",
+        "/{play,stop}",
+        "/foo]",
+        "/}",
+    ] {
+        assert!(is_pattern(pattern), "{} is a pattern", pattern);
+    }
+}
+
+#[test]
+fn normalized_addresses() {
+    let message = OscMessage::new(" /Mixer/Main Volume", (1,));
+    assert_eq!(message.addr, " /Mixer/Main Volume");
+    let message = OscMessage::new_normalized(" /Mixer/Main Volume", (1,), AddressPolicy::default());
+    assert_eq!(message.addr, " /Mixer/Main Volume");
+
+    let policy = AddressPolicy {
+        lowercase: true,
+        strip_whitespace: true,
+    };
+    let message = OscMessage::new_normalized(" /Mixer/Main Volume\t", (1,), policy);
+    assert_eq!(message.addr, "/mixer/mainvolume");
+    assert_eq!(message.args, vec![OscType::Int(1)]);
+    let policy = AddressPolicy {
+        lowercase: true,
+        ..Default::default()
+    };
+    assert_eq!(policy.apply("/A B"), "/a b");
+}
+
+#[test]
+fn pairs_round_trip() {
+    let message = OscMessage::new(
+        "/all",
+        vec![
+            OscType::Int(1),
+            OscType::Float(2.5),
+            OscType::String("three".into()),
+            OscType::Blob(vec![4, 5]),
+            OscType::Time((6, 7)),
+            OscType::Long(8),
+            OscType::Double(9.5),
+            OscType::Char('x'),
+            OscType::Color(OscColor {
+                red: 1,
+                green: 2,
+                blue: 3,
+                alpha: 4,
+            }),
+            OscType::Midi(OscMidiMessage {
+                port: 1,
+                status: 0x90,
+                data1: 60,
+                data2: 127,
+            }),
+            OscType::Bool(true),
+            OscType::Array(OscArray {
+                content: vec![OscType::Int(1), OscType::Nil],
+            }),
+            OscType::Nil,
+            OscType::Inf,
+        ],
+    );
+    let (addr, values) = message.to_pairs();
+    assert_eq!(addr, "/all");
+    assert_eq!(
+        values[4],
+        OscValue::Time {
+            seconds: 6,
+            fractional: 7
+        }
+    );
+    assert_eq!(
+        values[11],
+        OscValue::Array(vec![OscValue::Int(1), OscValue::Nil])
+    );
+    assert_eq!(OscMessage::from_pairs((addr, values)), message);
+}
+
+#[test]
+fn time_args() {
+    // 2021-01-01T00:00:00.5Z
+    let time = UNIX_EPOCH + Duration::from_millis(1_609_459_200_500);
+    let message = OscMessage::new("/at", (Time(time),));
+    assert_eq!(message.args, vec![OscType::Time((3_818_448_000, 1 << 31))]);
+    let arg = message.args.into_iter().next().unwrap();
+    assert_eq!(Time::try_from(arg), Ok(Time(time)));
+
+    for nanos in &[0, 1, 123_456_789, 999_999_999] {
+        let time = UNIX_EPOCH + Duration::new(1_700_000_000, *nanos);
+        assert_eq!(Time::from_osc_time(Time(time).to_osc_time()), Time(time));
+    }
+    let before_unix = UNIX_EPOCH - Duration::new(1, 250_000_000);
+    assert_eq!(
+        Time::from_osc_time(Time(before_unix).to_osc_time()),
+        Time(before_unix)
+    );
+    assert_eq!(Time::try_from(OscType::Int(1)), Err(OscType::Int(1)));
+}
+
+#[test]
+fn router_passes_matched_pattern() {
+    let matched = Arc::new(Mutex::new(Vec::new()));
+    let mut router = OscRouter::new();
+    for pattern in &["/mixer/*/volume", "/mixer/1/*", "/transport/play"] {
+        let matched = matched.clone();
+        router.route(*pattern, move |message, cx| {
+            let entry = (message.addr.clone(), cx.pattern().to_string());
+            matched.lock().unwrap().push(entry);
+        });
+    }
+    let peer_addr = "127.0.0.1:9000".parse().unwrap();
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: (0, 1),
+        content: vec![
+            OscMessage::new("/mixer/1/volume", (0.5f32,)).into_osc_packet(),
+            OscMessage::new("/transport/stop", ()).into_osc_packet(),
+        ],
+    });
+    assert_eq!(router.dispatch(packet, peer_addr), 2);
+    assert_eq!(
+        *matched.lock().unwrap(),
+        vec![
+            ("/mixer/1/volume".to_string(), "/mixer/*/volume".to_string()),
+            ("/mixer/1/volume".to_string(), "/mixer/1/*".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn router_literal_routes() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let mut router = OscRouter::new();
+    for i in 0..500 {
+        let calls = calls.clone();
+        router.route(format!("/channel/{}/volume", i), move |message, cx| {
+            assert_eq!(cx.pattern().as_str(), message.addr);
+            calls.lock().unwrap().push(i);
+        });
+    }
+    let fallback = calls.clone();
+    router.route("/channel/*/volume", move |_, _| {
+        fallback.lock().unwrap().push(-1);
+    });
+    let peer_addr = "127.0.0.1:9000".parse().unwrap();
+
+    for i in &[0, 123, 499] {
+        let packet = OscMessage::new(format!("/channel/{}/volume", i), ()).into_osc_packet();
+        assert_eq!(router.dispatch(packet, peer_addr), 1);
+    }
+    // Patterns are only tried when no literal route exists for the address.
+    let packet = OscMessage::new("/channel/500/volume", ()).into_osc_packet();
+    assert_eq!(router.dispatch(packet, peer_addr), 1);
+    let packet = OscMessage::new("/channel/1/pan", ()).into_osc_packet();
+    assert_eq!(router.dispatch(packet, peer_addr), 0);
+    assert_eq!(*calls.lock().unwrap(), vec![0, 123, 499, -1]);
+}
+
+#[test]
+fn router_fallback() {
+    let unmatched = Arc::new(Mutex::new(Vec::new()));
+    let mut router = OscRouter::new();
+    router.route("/known", |_, _| {});
+    router.route("/mixer/*", |_, _| {});
+    let fallback = unmatched.clone();
+    router.fallback(move |message, peer_addr| {
+        fallback.lock().unwrap().push((message.clone(), peer_addr));
+    });
+    let peer_addr = "127.0.0.1:9000".parse().unwrap();
+
+    for addr in &["/known", "/mixer/1"] {
+        let packet = OscMessage::new(*addr, ()).into_osc_packet();
+        assert_eq!(router.dispatch(packet, peer_addr), 1);
+    }
+    let unknown = OscMessage::new("/unknown", (1,));
+    assert_eq!(
+        router.dispatch(unknown.clone().into_osc_packet(), peer_addr),
+        0
+    );
+    assert_eq!(*unmatched.lock().unwrap(), vec![(unknown, peer_addr)]);
+}
+
+#[test]
+fn format_all_arg_types() {
+    let color = OscColor {
+        red: 255,
+        green: 128,
+        blue: 0,
+        alpha: 64,
+    };
+    let midi = OscMidiMessage {
+        port: 1,
+        status: 0x90,
+        data1: 60,
+        data2: 127,
+    };
+    let nested = OscType::Array(OscArray {
+        content: vec![OscType::Int(2), OscType::String("x".into())],
+    });
+    let array = OscType::Array(OscArray {
+        content: vec![OscType::Int(1), nested, OscType::Nil],
+    });
+    // 2024-05-01T12:00:00.250Z
+    let time = OscType::Time((3_923_553_600, 1 << 30));
+    let message = OscMessage::new(
+        "/all",
+        vec![
+            OscType::Blob((0..20).collect()),
+            array,
+            OscType::Color(color),
+            OscType::Midi(midi),
+            time,
+            OscType::Time((0, 1)),
+        ],
+    );
+    let expected = concat!(
+        "/all blob[20] [1 [2 \"x\"] nil] rgba(255,128,0,64) midi(1,0x90,60,127) ",
+        "time(2024-05-01T12:00:00.250Z) time(immediately)"
+    );
+    assert_eq!(format_message(&message), expected);
+
+    let options = FormatOptions { blob_hex_len: 4 };
+    let blobs = OscMessage::new("/blobs", (vec![0xde, 0xad], (0..20).collect::<Vec<u8>>()));
+    assert_eq!(
+        format_message_with(&blobs, options),
+        "/blobs blob[2]:dead blob[20]:00010203.."
+    );
+}
+
+/// Counts the log records of the router.
+struct RouterLogCounter(AtomicUsize);
+
+impl log::Log for RouterLogCounter {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.target().starts_with("async_osc::router")
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.enabled(record.metadata()) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static ROUTER_LOGS: RouterLogCounter = RouterLogCounter(AtomicUsize::new(0));
+
+#[test]
+fn router_log_sampling() {
+    log::set_logger(&ROUTER_LOGS).unwrap();
+    log::set_max_level(log::LevelFilter::Debug);
+    let peer_addr = "127.0.0.1:9000".parse().unwrap();
+    let mut router = OscRouter::new();
+    router.route("/meter/*", |_, _| {});
+
+    router.log_messages(LogSampling::OneIn(10));
+    for i in 0..100 {
+        let packet = OscMessage::new(format!("/meter/{}", i % 4), (i,)).into_osc_packet();
+        router.dispatch(packet, peer_addr);
+    }
+    assert_eq!(ROUTER_LOGS.0.swap(0, Ordering::SeqCst), 10);
+
+    router.log_messages(LogSampling::PerAddress(Duration::from_secs(3600)));
+    for i in 0..100 {
+        let packet = OscMessage::new(format!("/meter/{}", i % 4), (i,)).into_osc_packet();
+        router.dispatch(packet, peer_addr);
+    }
+    assert_eq!(ROUTER_LOGS.0.swap(0, Ordering::SeqCst), 4);
+}
+
+#[test]
+fn typed_arg_transforms() {
+    let mut message = OscMessage::new(
+        "/relay",
+        vec![
+            OscType::Float(0.25),
+            OscType::Int(3),
+            OscType::Double(1.5),
+            OscType::Long(10),
+        ],
+    );
+    assert!(message.map_f32(0, |value| value * 4.0));
+    assert!(message.map_i32(1, |value| value + 1));
+    assert!(message.map_f64(2, |value| -value));
+    assert!(message.map_i64(3, |value| value * 10));
+    // Type mismatches and missing args are ignored.
+    assert!(!message.map_f32(1, |_| unreachable!()));
+    assert!(!message.map_i32(10, |_| unreachable!()));
+    assert_eq!(
+        message.args,
+        vec![
+            OscType::Float(1.0),
+            OscType::Int(4),
+            OscType::Double(-1.5),
+            OscType::Long(100),
+        ]
+    );
+}
+
+#[test]
+fn trailing_slashes() {
+    for pattern in &["/a", "/a/"] {
+        let pattern = OscAddressPattern::new(*pattern);
+        assert_eq!(pattern.as_str(), "/a");
+        assert!(pattern.matches("/a"));
+        assert!(pattern.matches("/a/"));
+        assert!(!pattern.matches("/a//"));
+        assert!(!pattern.matches("/a/b"));
+    }
+    let pattern = OscAddressPattern::new("/a/*/");
+    assert!(pattern.matches("/a/b"));
+    assert!(pattern.matches("/a/b/"));
+    assert_eq!(OscAddressPattern::new("/").as_str(), "/");
+    assert!(OscAddressPattern::new("/").matches("/"));
+
+    assert!(OscMessage::new("/a", ()).starts_with("/a/"));
+    assert!(OscMessage::new("/a/", ()).starts_with("/a"));
+    assert!(OscMessage::new("/a/b", ()).starts_with("/a/"));
+    assert!(OscMessage::new("/foobar", ()).starts_with("/foo"));
+    assert!(!OscMessage::new("/foobar", ()).starts_with("/foo/"));
+    assert!(OscMessage::new("/foo", ()).starts_with("/"));
+}
+
+#[test]
+fn nested_bundle_builder() {
+    let lights = OscBundleBuilder::at((101, 0))
+        .push(OscMessage::new("/lights/go", ()))
+        .build()
+        .unwrap();
+    let sound = OscBundleBuilder::at((102, 1 << 31))
+        .push(OscMessage::new("/sound/go", ()))
+        .build()
+        .unwrap();
+    let cue = OscBundleBuilder::at((100, 0))
+        .push(OscMessage::new("/cue/start", ()))
+        .push(lights.clone())
+        .push(sound.clone())
+        .build()
+        .unwrap();
+    assert_eq!(cue.timetag, (100, 0));
+    assert_eq!(
+        cue.content,
+        vec![
+            OscMessage::new("/cue/start", ()).into_osc_packet(),
+            OscPacket::Bundle(lights.clone()),
+            OscPacket::Bundle(sound),
+        ]
+    );
+
+    // A child scheduled before its parent is rejected, also when nested deeper.
+    let err = OscBundleBuilder::at((200, 0)).push(lights.clone()).build();
+    assert!(matches!(err, Err(Error::Osc(rosc::OscError::BadBundle(_)))));
+    let outer = OscBundleBuilder::new().push(OscBundle {
+        timetag: (200, 0),
+        content: vec![OscPacket::Bundle(lights)],
+    });
+    assert!(outer.build().is_err());
+}
+
+#[test]
+fn structurally_eq_ignores_timetags() {
+    let bundle = |timetag, inner_timetag| {
+        OscPacket::Bundle(OscBundle {
+            timetag,
+            content: vec![
+                OscMessage::new("/a", (1,)).into_osc_packet(),
+                OscPacket::Bundle(OscBundle {
+                    timetag: inner_timetag,
+                    content: vec![OscMessage::new("/b", (2,)).into_osc_packet()],
+                }),
+            ],
+        })
+    };
+    let a = bundle((1, 0), (2, 0));
+    let b = bundle((5, 0), (6, 0));
+    assert!(a.structurally_eq(&b, true));
+    assert!(!a.structurally_eq(&b, false));
+    assert!(a.structurally_eq(&a.clone(), false));
+
+    let message = OscMessage::new("/a", (1,)).into_osc_packet();
+    assert!(!a.structurally_eq(&message, true));
+    assert!(!message.structurally_eq(&OscMessage::new("/a", (2,)).into_osc_packet(), true));
+}
+
+#[test]
+fn relative_timetags() {
+    let check = |timetag, expected: Duration| {
+        let time = Time::from_osc_time(timetag).0;
+        let ahead = time.duration_since(SystemTime::now()).unwrap();
+        assert!(ahead <= expected && ahead > expected - Duration::from_millis(20));
+    };
+    check(
+        OscTime::after(Duration::from_millis(50)),
+        Duration::from_millis(50),
+    );
+    check(OscTime::in_seconds(0.05), Duration::from_millis(50));
+    let past = Time::from_osc_time(OscTime::in_seconds(-1.0)).0;
+    assert!(past < SystemTime::now() - Duration::from_millis(900));
+
+    // Out of range values are clamped instead of panicking.
+    let now = Time::from_osc_time(OscTime::in_seconds(f64::NAN)).0;
+    assert!(now <= SystemTime::now() + Duration::from_millis(1));
+    assert!(now > SystemTime::now() - Duration::from_millis(20));
+    assert_eq!(
+        OscTime::in_seconds(f64::INFINITY).0,
+        OscTime::in_seconds(1e300).0
+    );
+    assert_eq!(
+        OscTime::in_seconds(f64::NEG_INFINITY).0,
+        OscTime::in_seconds(-1e300).0
+    );
+}
+
+#[test]
+fn numeric_normalized_equality() {
+    let int = OscMessage::new("/level", (1, 0.5f32, "x"));
+    let long = OscMessage::new("/level", (1i64, 0.5f64, "x"));
+    assert!(int.eq_numeric_normalized(&long));
+    assert!(long.eq_numeric_normalized(&int));
+    assert!(!int.eq_numeric_normalized(&OscMessage::new("/level", (2i64, 0.5f64, "x"))));
+    // Integers and floats are not mixed.
+    assert!(!int.eq_numeric_normalized(&OscMessage::new("/level", (1.0f32, 0.5f32, "x"))));
+    assert!(!int.eq_numeric_normalized(&OscMessage::new("/other", (1, 0.5f32, "x"))));
+
+    let array = |arg| OscMessage::new("/a", vec![OscType::Array(OscArray { content: vec![arg] })]);
+    assert!(array(OscType::Int(3)).eq_numeric_normalized(&array(OscType::Long(3))));
+}
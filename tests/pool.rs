@@ -0,0 +1,48 @@
+use async_osc::{pool, OscSocket, Result};
+use async_std::stream::StreamExt;
+use futures_lite::future::poll_once;
+
+#[test]
+fn pool_reuses_buffers() -> Result<()> {
+    pool::set_enabled(true);
+    async_std::task::block_on(async {
+        let before = pool::stats();
+        for _ in 0..10 {
+            let socket = OscSocket::bind("127.0.0.1:0").await?;
+            drop(socket);
+        }
+        let after = pool::stats();
+        assert!(after.allocated - before.allocated <= 1);
+        assert!(after.reused - before.reused >= 9);
+        Ok(())
+    })
+}
+
+#[test]
+fn pool_reuses_buffers_of_polled_sockets() -> Result<()> {
+    pool::set_enabled(true);
+    async_std::task::block_on(async {
+        let before = pool::stats();
+        for _ in 0..10 {
+            let mut socket = OscSocket::bind("127.0.0.1:0").await?;
+            // Start a receive that is still pending when the socket is dropped.
+            assert!(poll_once(socket.next()).await.is_none());
+            drop(socket);
+        }
+        let after = pool::stats();
+        assert!(after.allocated - before.allocated <= 1);
+        assert!(after.reused - before.reused >= 9);
+        Ok(())
+    })
+}
+
+#[test]
+fn send_once_takes_no_receive_buffer() -> Result<()> {
+    async_std::task::block_on(async {
+        let (_receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+        let before = pool::stats();
+        OscSocket::send_once("127.0.0.1:0", addr, ("/once", ())).await?;
+        assert_eq!(pool::stats(), before);
+        Ok(())
+    })
+}
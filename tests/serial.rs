@@ -0,0 +1,33 @@
+#![cfg(unix)]
+
+use async_io::Async;
+use async_osc::{prelude::*, Framing, OscMessage, OscSerial, OscStream, Result};
+use async_std::stream::StreamExt;
+use nix::pty::openpty;
+use nix::unistd::ttyname;
+use std::fs::File;
+
+#[async_std::test]
+async fn pty_round_trip() -> Result<()> {
+    // The pty's slave side stands in for the serial port of a device.
+    let pty = openpty(None, None).map_err(std::io::Error::from)?;
+    let path = ttyname(&pty.slave).map_err(std::io::Error::from)?;
+    let mut serial = OscSerial::open(&path, 115_200, Framing::Slip)?;
+    assert_eq!(serial.framing(), Framing::Slip);
+    let mut device = OscStream::new(Async::new(File::from(pty.master))?, Framing::Slip);
+
+    let message = OscMessage::new("/led", (1, 0.5f32));
+    device.send(message.clone()).await?;
+    assert_eq!(
+        serial.next().await.unwrap()?,
+        message.clone().into_osc_packet()
+    );
+
+    // The SLIP END and ESC bytes in the payload are escaped on the wire.
+    let reply = OscMessage::new("/ack", (vec![0xc0u8, 0xdb],));
+    serial.send(reply.clone()).await?;
+    assert_eq!(device.next().await.unwrap()?, reply.into_osc_packet());
+
+    assert!(OscSerial::open(&path, 12345, Framing::Slip).is_err());
+    Ok(())
+}
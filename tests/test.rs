@@ -1,7 +1,14 @@
+use async_osc::codec::{self, DecodeMode};
 use async_osc::prelude::*;
-use async_osc::{OscMessage, OscPacket, OscSocket, OscType, Result};
+use async_osc::{
+    CancellationToken, CoalesceWindow, Error, Keepalive, OscBridge, OscBundle, OscColor,
+    OscMessage, OscMidiMessage, OscPacket, OscReceiver, OscRouter, OscSenderPool, OscSocket,
+    OscState, OscType, Port, Result, Scheduler, SequenceStatus, SequencedSender, SharedReceiver,
+    TargetedSender, TeeSender,
+};
 use async_std::stream::StreamExt;
 use async_std::task::{self, JoinHandle};
+use std::time::Duration;
 
 #[async_std::test]
 async fn connect_send_recv() -> Result<()> {
@@ -38,3 +45,1767 @@ async fn connect_send_recv() -> Result<()> {
 
     Ok(())
 }
+
+#[async_std::test]
+async fn send_recv_bool() -> Result<()> {
+    let mut socket1 = OscSocket::bind("localhost:0").await?;
+    let socket2 = OscSocket::bind("localhost:0").await?;
+    let addr1 = socket1.local_addr()?;
+
+    socket2.send_to(("/mute", (true, false)), addr1).await?;
+
+    let (packet, _peer_addr) = socket1.next().await.unwrap()?;
+    let message = packet.into_message().unwrap();
+    assert_eq!(
+        message.args,
+        vec![OscType::Bool(true), OscType::Bool(false)]
+    );
+    assert_eq!(message.get_bool(0), Some(true));
+    assert_eq!(message.get_bool(1), Some(false));
+    assert_eq!(message.get_bool(2), None);
+    Ok(())
+}
+
+#[async_std::test]
+async fn receiver_recv() -> Result<()> {
+    let mut receiver = OscReceiver::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    let addr = receiver.local_addr()?;
+
+    socket.send_to(("/foo", (1,)), addr).await?;
+
+    let (packet, peer_addr) = receiver.next().await.unwrap()?;
+    assert_eq!(peer_addr, socket.local_addr()?);
+    assert_eq!(packet, OscMessage::new("/foo", (1,)).into_osc_packet());
+
+    Ok(())
+}
+
+#[async_std::test]
+async fn keepalive() -> Result<()> {
+    let mut receiver = OscReceiver::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let interval = Duration::from_millis(20);
+    let keepalive = Keepalive::spawn(socket.sender(), ("/ping", ()), interval);
+
+    let recv_two = async {
+        for _ in 0..2 {
+            let (packet, _) = receiver.next().await.unwrap()?;
+            assert_eq!(packet, OscMessage::new("/ping", ()).into_osc_packet());
+        }
+        Ok::<(), Error>(())
+    };
+    async_std::future::timeout(interval * 10, recv_two)
+        .await
+        .expect("two heartbeats within the window")?;
+
+    keepalive.cancel().await;
+    Ok(())
+}
+
+#[async_std::test]
+async fn recv_lenient() -> Result<()> {
+    let mut receiver = OscReceiver::bind("localhost:0").await?;
+    receiver.set_decode_mode(DecodeMode::Lenient);
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let bundle = OscPacket::Bundle(OscBundle {
+        timetag: (1, 0),
+        content: vec![OscMessage::new("/foo", (1,)).into_osc_packet()],
+    });
+    let mut buf = async_osc::codec::encode(&bundle).unwrap();
+    buf.extend_from_slice(&[0, 0, 0, 4, 0, 0, 0, 0]);
+    socket.socket().send(&buf).await?;
+
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, bundle);
+    Ok(())
+}
+
+#[async_std::test]
+async fn bind_local() -> Result<()> {
+    let probe = OscSocket::bind("127.0.0.1:0").await?;
+    let port = probe.local_addr()?.port();
+    drop(probe);
+
+    let socket = OscSocket::bind_local(Port(port)).await?;
+    let addr = socket.local_addr()?;
+    assert_eq!(addr.port(), port);
+    assert!(addr.ip().is_loopback());
+    Ok(())
+}
+
+#[async_std::test]
+async fn rebind() -> Result<()> {
+    let mut socket = OscSocket::builder()
+        .reuse_address(true)
+        .bind("127.0.0.1:0")
+        .await?;
+    let old_addr = socket.local_addr()?;
+    let (mut peer, peer_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let old_sender = socket.sender();
+    let mut subscription = socket.subscribe("/sub");
+
+    socket.rebind("127.0.0.1:0").await?;
+    let new_addr = socket.local_addr()?;
+    assert_ne!(new_addr.port(), old_addr.port());
+    // Options set when binding are carried over.
+    assert!(socket2::SockRef::from(&*socket.current_socket()).reuse_address()?);
+    // Subscriptions from before the rebind end with an error.
+    let err = subscription.next().await.unwrap().unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::NotConnected));
+    assert!(subscription.next().await.is_none());
+
+    // Received on the new port. Until then, `socket` returns the old socket.
+    assert_eq!(socket.socket().local_addr()?, old_addr);
+    peer.send_to(("/new", ()), new_addr).await?;
+    let (packet, _) = socket.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/new", ()).into_osc_packet());
+    assert_eq!(socket.socket().local_addr()?, new_addr);
+
+    // Sent from the new port, while old senders stay on the old port.
+    socket.send_to(("/from", ()), peer_addr).await?;
+    assert_eq!(peer.next().await.unwrap()?.1, new_addr);
+    old_sender.send_to(("/from", ()), peer_addr).await?;
+    assert_eq!(peer.next().await.unwrap()?.1, old_addr);
+    Ok(())
+}
+
+#[async_std::test]
+async fn rebind_while_sending() -> Result<()> {
+    let (socket, old_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (mut peer, peer_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let socket = std::sync::Arc::new(socket);
+    let sending = socket.clone();
+    let task: JoinHandle<Result<()>> = task::spawn(async move {
+        loop {
+            sending.send_to(("/tick", ()), peer_addr).await?;
+            task::sleep(Duration::from_millis(5)).await;
+        }
+    });
+
+    assert_eq!(peer.next().await.unwrap()?.1, old_addr);
+    socket.rebind("127.0.0.1:0").await?;
+    let new_addr = socket.local_addr()?;
+    assert_ne!(new_addr, old_addr);
+    // Ticks that were sent before the rebind may still be queued.
+    loop {
+        let (_, from) = peer.next().await.unwrap()?;
+        if from == new_addr {
+            break;
+        }
+        assert_eq!(from, old_addr);
+    }
+    assert!(task.cancel().await.is_none());
+    Ok(())
+}
+
+#[async_std::test]
+async fn bind_loopback_v4() -> Result<()> {
+    let socket = OscSocket::bind_loopback_v4(Port(0)).await?;
+    let addr = socket.local_addr()?;
+    assert!(addr.is_ipv4());
+    assert!(addr.ip().is_loopback());
+    assert_ne!(addr.port(), 0);
+    Ok(())
+}
+
+#[async_std::test]
+async fn bind_loopback_v6() -> Result<()> {
+    let socket = match OscSocket::bind_loopback_v6(Port(0)).await {
+        Ok(socket) => socket,
+        Err(_) => {
+            eprintln!("skipping bind_loopback_v6: IPv6 is not available");
+            return Ok(());
+        }
+    };
+    let addr = socket.local_addr()?;
+    assert!(addr.is_ipv6());
+    assert!(addr.ip().is_loopback());
+    assert_ne!(addr.port(), 0);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[async_std::test]
+async fn scoped_ipv6_addr() -> Result<()> {
+    use async_osc::parse_scoped_addr;
+    use std::net::SocketAddr;
+
+    let lo_index: u32 = std::fs::read_to_string("/sys/class/net/lo/ifindex")?
+        .trim()
+        .parse()
+        .unwrap();
+    match parse_scoped_addr("[fe80::1%lo]:9000")? {
+        SocketAddr::V6(addr) => {
+            assert_eq!(addr.scope_id(), lo_index);
+            assert_eq!(addr.port(), 9000);
+        }
+        SocketAddr::V4(_) => panic!("expected an IPv6 address"),
+    }
+    assert!(parse_scoped_addr("[fe80::1%does-not-exist]:9000").is_err());
+
+    // Bind and send on the scoped loopback address.
+    let addr = parse_scoped_addr("[::1%lo]:0")?;
+    let mut receiver = match OscReceiver::bind(addr).await {
+        Ok(receiver) => receiver,
+        Err(_) => {
+            eprintln!("skipping scoped_ipv6_addr: IPv6 is not available");
+            return Ok(());
+        }
+    };
+    let socket = OscSocket::bind(addr).await?;
+    socket
+        .send_to(("/scoped", (1,)), receiver.local_addr()?)
+        .await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/scoped", (1,)).into_osc_packet());
+    Ok(())
+}
+
+#[async_std::test]
+async fn map_into_events() -> Result<()> {
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Fire(i32),
+    }
+
+    let receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let mut events =
+        Box::pin(
+            receiver.map_into(|packet, _peer_addr| match packet.message()?.as_tuple() {
+                ("/fire", &[OscType::Int(id)]) => Some(Event::Fire(id)),
+                _ => None,
+            }),
+        );
+
+    socket.send(("/other", (1,))).await?;
+    socket.send(("/fire", (2,))).await?;
+    socket.send(("/fire", (3,))).await?;
+
+    assert_eq!(events.next().await.unwrap()?, Event::Fire(2));
+    assert_eq!(events.next().await.unwrap()?, Event::Fire(3));
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[async_std::test]
+async fn connection_refused() -> Result<()> {
+    let closed = OscSocket::bind("127.0.0.1:0").await?;
+    let closed_addr = closed.local_addr()?;
+    drop(closed);
+
+    let mut socket = OscSocket::bind("127.0.0.1:0").await?;
+    socket.connect(closed_addr).await?;
+    socket.send(("/hello", ())).await?;
+
+    let res = async_std::future::timeout(Duration::from_secs(1), socket.next())
+        .await
+        .expect("error reported before timeout")
+        .unwrap();
+    let err = res.unwrap_err();
+    assert!(err.is_connection_refused());
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::ConnectionRefused));
+    assert_eq!(Error::Unsupported("test").io_kind(), None);
+    Ok(())
+}
+
+#[async_std::test]
+async fn dedup() -> Result<()> {
+    let receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+    let mut receiver = receiver.dedup(Duration::from_secs(10));
+
+    socket.send(("/retransmit", (1,))).await?;
+    socket.send(("/retransmit", (1,))).await?;
+    socket.send(("/retransmit", (2,))).await?;
+
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(
+        packet,
+        OscMessage::new("/retransmit", (1,)).into_osc_packet()
+    );
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(
+        packet,
+        OscMessage::new("/retransmit", (2,)).into_osc_packet()
+    );
+    Ok(())
+}
+
+#[async_std::test]
+async fn dedup_forgets_after_window() -> Result<()> {
+    let receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+    let mut receiver = receiver.dedup(Duration::from_millis(50));
+
+    socket.send(("/retransmit", (1,))).await?;
+    receiver.next().await.unwrap()?;
+    task::sleep(Duration::from_millis(100)).await;
+    // The packet is yielded again once the window has passed.
+    socket.send(("/retransmit", (1,))).await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(
+        packet,
+        OscMessage::new("/retransmit", (1,)).into_osc_packet()
+    );
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[async_std::test]
+async fn dont_fragment() -> Result<()> {
+    let socket = OscSocket::bind("127.0.0.1:0").await?;
+    match socket.set_dont_fragment(true) {
+        Err(Error::Unsupported(_)) => {}
+        res => panic!("expected unsupported error, got {:?}", res),
+    }
+
+    let receiver = match OscSocket::bind("[::1]:0").await {
+        Ok(receiver) => receiver,
+        Err(_) => {
+            eprintln!("skipping dont_fragment: IPv6 is not available");
+            return Ok(());
+        }
+    };
+    let socket = OscSocket::bind("[::1]:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    // Larger than the MTU of the loopback interface when including the IPv6 header.
+    let blob = OscType::Blob(vec![0u8; 65_480]);
+    socket.send(("/large", vec![blob.clone()])).await?;
+    socket.set_dont_fragment(true)?;
+    assert!(socket.send(("/large", vec![blob])).await.is_err());
+    socket.send(("/small", (1,))).await?;
+    Ok(())
+}
+
+#[async_std::test]
+async fn send_reliable() -> Result<()> {
+    let mut server = OscSocket::bind("localhost:0").await?;
+    let client = OscSocket::bind("localhost:0").await?;
+    client.connect(server.local_addr()?).await?;
+
+    let task: JoinHandle<Result<usize>> = task::spawn(async move {
+        // Drop the first attempt, acknowledge the second.
+        let mut attempts = 0;
+        while let Some(packet) = server.next().await {
+            let (packet, peer_addr) = packet?;
+            let mut message = packet.into_message().unwrap();
+            attempts += 1;
+            if attempts == 1 {
+                // A trailing int64 without the marker is not an acknowledgement id.
+                let mut plain = OscMessage::new("/plain", (7i64,));
+                assert!(!server.acknowledge(&mut plain, peer_addr, "/ack").await?);
+                assert_eq!(plain, OscMessage::new("/plain", (7i64,)));
+            }
+            if attempts == 2 {
+                // An acknowledgement of another message is ignored.
+                server.send_to(("/ack", (-1i64,)), peer_addr).await?;
+                assert!(server.acknowledge(&mut message, peer_addr, "/ack").await?);
+                assert_eq!(message, OscMessage::new("/cue/go", (1,)));
+                break;
+            }
+        }
+        Ok(attempts)
+    });
+
+    let sender = client.sender();
+    let timeout = Duration::from_millis(50);
+    let ack = sender
+        .send_reliable(("/cue/go", (1,)), "/ack", 3, timeout)
+        .await?;
+    assert_eq!(ack.addr, "/ack");
+    assert!(matches!(ack.args[..], [OscType::Long(id)] if id >= 0));
+    assert_eq!(task.await?, 2);
+
+    let bundle = OscBundle {
+        timetag: (0, 1),
+        content: vec![],
+    };
+    let err = sender
+        .send_reliable(bundle, "/ack", 0, timeout)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Unsupported(_)));
+    Ok(())
+}
+
+#[async_std::test]
+async fn raw_stream() -> Result<()> {
+    let receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    let mut raw = receiver.raw_stream();
+
+    socket
+        .send_to(("/raw", (1.5f32, "bytes")), receiver.local_addr()?)
+        .await?;
+
+    let (bytes, peer_addr) = raw.next().await.unwrap()?;
+    assert_eq!(peer_addr, socket.local_addr()?);
+    let packet = async_osc::codec::decode(&bytes, DecodeMode::Strict)?;
+    assert_eq!(
+        packet,
+        OscMessage::new("/raw", (1.5f32, "bytes")).into_osc_packet()
+    );
+    assert_eq!(bytes, async_osc::codec::encode(&packet).unwrap());
+    Ok(())
+}
+
+#[async_std::test]
+async fn send_all() -> Result<()> {
+    let mut receiver = OscReceiver::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let packets = async_std::stream::from_iter((0..3).map(|i| ("/count", (i,))));
+    socket.sender().send_all(packets).await?;
+
+    for i in 0..3 {
+        let (packet, _) = receiver.next().await.unwrap()?;
+        assert_eq!(packet, OscMessage::new("/count", (i,)).into_osc_packet());
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn send_once() -> Result<()> {
+    let mut receiver = OscReceiver::bind("127.0.0.1:0").await?;
+    let addr = receiver.local_addr()?;
+
+    OscSocket::send_once("127.0.0.1:0", addr, ("/once", (1,))).await?;
+
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/once", (1,)).into_osc_packet());
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[async_std::test]
+async fn dual_stack() -> Result<()> {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    let mut receiver = match OscSocket::builder().only_v6(false).bind("[::]:0").await {
+        Ok(receiver) => receiver,
+        Err(_) => {
+            eprintln!("skipping dual_stack: IPv6 is not available");
+            return Ok(());
+        }
+    };
+    let port = receiver.local_addr()?.port();
+
+    let socket = OscSocket::bind("127.0.0.1:0").await?;
+    socket.send_to(("/v4", (1,)), ("127.0.0.1", port)).await?;
+
+    let (packet, peer_addr) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/v4", (1,)).into_osc_packet());
+    match peer_addr.ip() {
+        IpAddr::V6(ip) => assert_eq!(ip.to_ipv4(), Some(Ipv4Addr::LOCALHOST)),
+        IpAddr::V4(_) => panic!("expected an IPv4-mapped IPv6 address"),
+    }
+
+    let v6_only = OscSocket::builder().only_v6(true).bind("[::]:0").await?;
+    let port = v6_only.local_addr()?.port();
+    // An IPv4 socket can bind the same port when the IPv6 socket is v6-only.
+    OscSocket::bind(("0.0.0.0", port)).await?;
+    Ok(())
+}
+
+#[async_std::test]
+async fn subscribe() -> Result<()> {
+    let receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let mut mixer = receiver.subscribe("/mixer/*/volume");
+    let mut transport = receiver.subscribe("/transport/{play,stop}");
+
+    socket.send(("/mixer/1/volume", (0.5f32,))).await?;
+    socket.send(("/other", (1,))).await?;
+    socket.send(("/transport/play", ())).await?;
+    socket.send(("/mixer/2/volume", (0.7f32,))).await?;
+
+    let (message, _) = mixer.next().await.unwrap()?;
+    assert_eq!(message, OscMessage::new("/mixer/1/volume", (0.5f32,)));
+    let (message, _) = mixer.next().await.unwrap()?;
+    assert_eq!(message, OscMessage::new("/mixer/2/volume", (0.7f32,)));
+    let (message, _) = transport.next().await.unwrap()?;
+    assert_eq!(message, OscMessage::new("/transport/play", ()));
+
+    drop(receiver);
+    assert!(transport.next().await.is_none());
+    Ok(())
+}
+
+#[async_std::test]
+async fn subscribe_drops_newest_when_full() -> Result<()> {
+    let receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let mut slow = receiver.subscribe("/n");
+    let mut fast = receiver.subscribe("/n");
+    // Each subscription queues 1024 messages. Wait for every message on the fast subscription,
+    // so that none is lost on the way.
+    for i in 0..1100 {
+        socket.send(("/n", (i,))).await?;
+        fast.next().await.unwrap()?;
+    }
+    for i in 0..1024 {
+        let (message, _) = slow.next().await.unwrap()?;
+        assert_eq!(message, OscMessage::new("/n", (i,)));
+    }
+    // The slow subscription caught up and receives again.
+    socket.send(("/n", (1100,))).await?;
+    fast.next().await.unwrap()?;
+    let (message, _) = slow.next().await.unwrap()?;
+    assert_eq!(message, OscMessage::new("/n", (1100,)));
+    Ok(())
+}
+
+#[async_std::test]
+async fn subscribe_after_persistent_error() -> Result<()> {
+    // Receiving on an unconnected stream socket fails with the same error every time.
+    let socket = socket2::Socket::new(socket2::Domain::IPV4, socket2::Type::STREAM, None)?;
+    socket.set_nonblocking(true)?;
+    let socket = async_std::net::UdpSocket::from(std::net::UdpSocket::from(socket));
+    let socket = OscSocket::new(socket);
+    let timeout = Duration::from_secs(1);
+    for _ in 0..3 {
+        // Each subscription gets the error instead of waiting forever.
+        let mut subscription = socket.subscribe("/sub");
+        let item = async_std::future::timeout(timeout, subscription.next()).await;
+        assert!(matches!(item, Ok(Some(Err(Error::Io(_))))));
+        let item = async_std::future::timeout(timeout, subscription.next()).await;
+        assert!(matches!(item, Ok(None)));
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn recv_matches_rosc_decode() -> Result<()> {
+    let mut receiver = OscSocket::bind("localhost:0").await?;
+    let mut raw = receiver.raw_stream();
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let packets = vec![
+        OscMessage::new("/a", ()).into_osc_packet(),
+        OscMessage::new("/b", (1, 2.5f32, "three")).into_osc_packet(),
+        OscMessage::new("/c", vec![OscType::Blob(vec![1, 2, 3]), OscType::Nil]).into_osc_packet(),
+        OscPacket::Bundle(OscBundle {
+            timetag: (10, 20),
+            content: vec![OscMessage::new("/d", (true,)).into_osc_packet()],
+        }),
+    ];
+    for packet in packets {
+        let bytes = rosc::encoder::encode(&packet).unwrap();
+        socket.socket().send(&bytes).await?;
+        let (received, _) = receiver.next().await.unwrap()?;
+        assert_eq!(received, rosc::decoder::decode(&bytes).unwrap());
+
+        socket.socket().send(&bytes).await?;
+        let (received, _) = raw.next().await.unwrap()?;
+        assert_eq!(received, bytes);
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn map_args_and_resend() -> Result<()> {
+    let mut proxy = OscSocket::bind("localhost:0").await?;
+    let mut receiver = OscSocket::bind("localhost:0").await?;
+    let sender = OscSocket::bind("localhost:0").await?;
+    sender.connect(proxy.local_addr()?).await?;
+    let target = receiver.local_addr()?;
+
+    sender.send(("/volume", (0.25f32, "master"))).await?;
+    let (packet, _) = proxy.next().await.unwrap()?;
+    let message = packet.into_message().unwrap();
+    let doubled = message.clone().map_args(|args| {
+        args.into_iter()
+            .map(|arg| match arg {
+                OscType::Float(value) => OscType::Float(value * 2.0),
+                arg => arg,
+            })
+            .collect()
+    });
+    proxy.send_to(doubled, target).await?;
+    proxy
+        .send_to(message.with_args(&message.args[1..]), target)
+        .await?;
+
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(
+        packet.into_message().unwrap(),
+        OscMessage::new("/volume", (0.5f32, "master"))
+    );
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(
+        packet.into_message().unwrap(),
+        OscMessage::new("/volume", ("master",))
+    );
+    Ok(())
+}
+
+#[async_std::test]
+async fn coalesce_window() -> Result<()> {
+    let mut receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let coalesce = CoalesceWindow::new(socket.sender(), Duration::from_millis(50));
+    assert_eq!(coalesce.window(), Duration::from_millis(50));
+    coalesce.send(("/a", (1,)));
+    coalesce.send(("/b", (2,)));
+    coalesce.send(("/c", (3,)));
+
+    let (packet, _) = receiver.next().await.unwrap()?;
+    match packet {
+        OscPacket::Bundle(bundle) => assert_eq!(
+            bundle.content,
+            vec![
+                OscMessage::new("/a", (1,)).into_osc_packet(),
+                OscMessage::new("/b", (2,)).into_osc_packet(),
+                OscMessage::new("/c", (3,)).into_osc_packet(),
+            ]
+        ),
+        packet => panic!("expected a bundle, got {:?}", packet),
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn coalesce_window_drops_when_full() -> Result<()> {
+    let mut receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let coalesce = CoalesceWindow::with_capacity(socket.sender(), Duration::from_secs(10), 2);
+    for i in 0..5 {
+        coalesce.send(("/a", (i,)));
+    }
+    assert_eq!(coalesce.queue_len(), 2);
+    assert_eq!(coalesce.dropped_count(), 3);
+
+    coalesce.flush().await?;
+    assert_eq!(coalesce.queue_len(), 0);
+    let (packet, _) = receiver.next().await.unwrap()?;
+    match packet {
+        OscPacket::Bundle(bundle) => assert_eq!(bundle.content.len(), 2),
+        packet => panic!("expected a bundle, got {:?}", packet),
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn coalesce_window_after_manual_flush() -> Result<()> {
+    use std::time::Instant;
+
+    let mut receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let window = Duration::from_millis(100);
+    let coalesce = CoalesceWindow::new(socket.sender(), window);
+    coalesce.send(("/a", (1,)));
+    coalesce.flush().await?;
+    receiver.next().await.unwrap()?;
+
+    // The timer of the flushed window does not flush the next window early.
+    task::sleep(Duration::from_millis(50)).await;
+    let start = Instant::now();
+    coalesce.send(("/b", (2,)));
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/b", (2,)).into_osc_packet());
+    assert!(start.elapsed() >= Duration::from_millis(90));
+    Ok(())
+}
+
+#[async_std::test]
+async fn coalesce_window_splits_large_bundles() -> Result<()> {
+    let mut receiver = OscSocket::bind("127.0.0.1:0").await?;
+    let socket = OscSocket::bind("127.0.0.1:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let coalesce = CoalesceWindow::new(socket.sender(), Duration::from_secs(10));
+    let blob = || OscMessage::new("/blob", (OscType::Blob(vec![0; 30_000]),));
+    for _ in 0..3 {
+        coalesce.send(blob());
+    }
+    coalesce.flush().await?;
+    // Two blobs fit into one datagram, the third is sent on its own.
+    let (packet, _) = receiver.next().await.unwrap()?;
+    match packet {
+        OscPacket::Bundle(bundle) => assert_eq!(bundle.content.len(), 2),
+        packet => panic!("expected a bundle, got {:?}", packet),
+    }
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, blob().into_osc_packet());
+    Ok(())
+}
+
+#[async_std::test]
+async fn sequence_gap() -> Result<()> {
+    let receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+    let mut receiver = receiver.sequenced();
+
+    let sender = SequencedSender::new(socket.sender());
+    sender.send(("/a", (1,))).await?;
+    // Simulate a lost packet by sending a message with a sequence number but dropping it.
+    sender
+        .send_to(("/lost", ()), "127.0.0.1:9".parse().unwrap())
+        .await?;
+    sender.send(("/b", (2,))).await?;
+    socket.send(("/plain", ())).await?;
+    // A trailing int without the marker is an ordinary argument.
+    socket.send(("/plain", (7,))).await?;
+
+    let (packet, _, status) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/a", (1,)).into_osc_packet());
+    assert_eq!(status, SequenceStatus::InOrder);
+    let (packet, _, status) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/b", (2,)).into_osc_packet());
+    assert_eq!(status, SequenceStatus::Gap { missing: 1 });
+    let (_, _, status) = receiver.next().await.unwrap()?;
+    assert_eq!(status, SequenceStatus::Unsequenced);
+    let (packet, _, status) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/plain", (7,)).into_osc_packet());
+    assert_eq!(status, SequenceStatus::Unsequenced);
+    Ok(())
+}
+
+#[async_std::test]
+async fn with_cancellation() -> Result<()> {
+    let token = CancellationToken::new();
+    let mut receiver = OscSocket::bind("localhost:0")
+        .await?
+        .with_cancellation(token.clone());
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.get_ref().local_addr()?).await?;
+
+    let task: JoinHandle<Result<usize>> = task::spawn(async move {
+        let mut count = 0;
+        while let Some(packet) = receiver.next().await {
+            packet?;
+            count += 1;
+        }
+        Ok(count)
+    });
+    socket.send(("/a", ())).await?;
+    task::sleep(Duration::from_millis(50)).await;
+    token.cancel();
+    assert!(token.is_cancelled());
+    let count = async_std::future::timeout(Duration::from_secs(1), task)
+        .await
+        .expect("receive task did not end after cancellation")?;
+    assert_eq!(count, 1);
+    Ok(())
+}
+
+#[async_std::test]
+async fn with_cancellation_token_by_value() -> Result<()> {
+    // Without other clones of the token, the stream is never cancelled.
+    let (receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let mut receiver = receiver.with_cancellation(CancellationToken::new());
+    let socket = OscSocket::bind("127.0.0.1:0").await?;
+    socket.send_to(("/a", ()), addr).await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/a", ()).into_osc_packet());
+
+    // Dropping all clones of a token does not cancel it either.
+    let token = CancellationToken::new();
+    let (receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let mut receiver = receiver.with_cancellation(token.clone());
+    drop(token);
+    socket.send_to(("/b", ()), addr).await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/b", ()).into_osc_packet());
+    Ok(())
+}
+
+#[async_std::test]
+async fn state_keeps_latest_value() -> Result<()> {
+    let receiver = OscSocket::bind("localhost:0").await?;
+    let socket = OscSocket::bind("localhost:0").await?;
+    socket.connect(receiver.local_addr()?).await?;
+
+    let state = OscState::new();
+    let token = CancellationToken::new();
+    let task = task::spawn(
+        state
+            .clone()
+            .track(receiver.with_cancellation(token.clone())),
+    );
+    socket.send(("/volume", (0.5f32,))).await?;
+    socket.send(("/pan", (0.0f32,))).await?;
+    // A malformed packet does not stop tracking.
+    socket.send_raw(b"junk").await?;
+    socket.send(("/volume", (0.8f32,))).await?;
+    task::sleep(Duration::from_millis(50)).await;
+    token.cancel();
+    task.await?;
+
+    assert_eq!(
+        state.get("/volume"),
+        Some(OscMessage::new("/volume", (0.8f32,)))
+    );
+    assert_eq!(state.get("/pan"), Some(OscMessage::new("/pan", (0.0f32,))));
+    assert_eq!(state.get("/mute"), None);
+    assert_eq!(state.snapshot().len(), 2);
+    Ok(())
+}
+
+#[async_std::test]
+async fn bind_ephemeral() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    assert_ne!(addr.port(), 0);
+    assert_eq!(addr, receiver.local_addr()?);
+
+    let (socket, _) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    socket.send_to(("/hello", ()), addr).await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/hello", ()).into_osc_packet());
+    Ok(())
+}
+
+#[async_std::test]
+async fn bridge() -> Result<()> {
+    let (bridge_in, bridge_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (bridge_out, _) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (mut receiver, receiver_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    bridge_out.connect(receiver_addr).await?;
+    task::spawn(OscBridge::run(bridge_in, bridge_out.sender()));
+
+    let (socket, _) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    socket.connect(bridge_addr).await?;
+    socket.send(("/a", (1,))).await?;
+    socket.send_raw(b"not osc").await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/a", (1,)).into_osc_packet());
+    assert!(receiver.next().await.unwrap().is_err());
+
+    let (bridge_in, bridge_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let bridge = OscBridge::with_rewrite(|addr| format!("/remote{}", addr));
+    task::spawn(bridge.forward(bridge_in, bridge_out.sender()));
+    socket.send_to(("/b", (2,)), bridge_addr).await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/remote/b", (2,)).into_osc_packet());
+    Ok(())
+}
+
+#[async_std::test]
+async fn bridge_survives_closed_target() -> Result<()> {
+    let (bridge_in, bridge_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (bridge_out, _) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (receiver, receiver_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    bridge_out.connect(receiver_addr).await?;
+    task::spawn(OscBridge::run(bridge_in, bridge_out.sender()));
+
+    // Sends to the closed target fail with ConnectionRefused.
+    drop(receiver);
+    let (socket, _) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    socket.connect(bridge_addr).await?;
+    for _ in 0..3 {
+        socket.send(("/lost", ())).await?;
+        task::sleep(Duration::from_millis(10)).await;
+    }
+
+    // The error of an earlier send can be reported by the next one, which drops its datagram.
+    let mut receiver = OscSocket::bind(receiver_addr).await?;
+    for _ in 0..5 {
+        socket.send(("/a", (1,))).await?;
+        let timeout = Duration::from_millis(100);
+        if let Ok(item) = async_std::future::timeout(timeout, receiver.next()).await {
+            let (packet, _) = item.unwrap()?;
+            assert_eq!(packet, OscMessage::new("/a", (1,)).into_osc_packet());
+            return Ok(());
+        }
+    }
+    panic!("the bridge stopped forwarding");
+}
+
+#[async_std::test]
+async fn suppress_own_multicast() -> Result<()> {
+    let group = std::net::Ipv4Addr::new(239, 255, 42, 99);
+    let mut socket = OscSocket::bind("0.0.0.0:0").await?;
+    let port = socket.local_addr()?.port();
+    if socket
+        .socket()
+        .join_multicast_v4(group, std::net::Ipv4Addr::UNSPECIFIED)
+        .is_err()
+    {
+        // Multicast is not available.
+        return Ok(());
+    }
+    socket.socket().set_multicast_loop_v4(true)?;
+
+    socket.send_to(("/echo", ()), (group, port)).await?;
+    let packet = match async_std::future::timeout(Duration::from_secs(1), socket.next()).await {
+        Ok(packet) => packet.unwrap()?.0,
+        // Multicast packets are not looped back on this host.
+        Err(_) => return Ok(()),
+    };
+    assert_eq!(packet, OscMessage::new("/echo", ()).into_osc_packet());
+
+    socket.set_suppress_own(true)?;
+    socket.send_to(("/echo", ()), (group, port)).await?;
+    let other = OscSocket::bind("127.0.0.1:0").await?;
+    other.send_to(("/other", ()), ("127.0.0.1", port)).await?;
+    let (packet, _) = socket.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/other", ()).into_osc_packet());
+    Ok(())
+}
+
+#[async_std::test]
+async fn bind_multicast_v4() -> Result<()> {
+    use std::net::Ipv4Addr;
+
+    let group = Ipv4Addr::new(239, 255, 42, 98);
+    let iface = Ipv4Addr::UNSPECIFIED;
+    if !multicast_loopback_available(group).await {
+        eprintln!("skipping bind_multicast_v4: multicast loopback is not available");
+        return Ok(());
+    }
+    let mut first = OscSocket::bind_multicast_v4(group, Port(0), iface).await?;
+    let port = Port(first.local_addr()?.port());
+    // Address reuse lets a second listener share the port.
+    let mut second = OscSocket::bind_multicast_v4(group, port, iface).await?;
+    let err = OscSocket::bind_multicast_v4(Ipv4Addr::LOCALHOST, port, iface)
+        .await
+        .unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::InvalidInput));
+
+    let sender = OscSocket::bind("0.0.0.0:0").await?;
+    sender
+        .send_to(("/group", (1,)), (group, port.get()))
+        .await?;
+    let expected = OscMessage::new("/group", (1,)).into_osc_packet();
+    for listener in [&mut first, &mut second].iter_mut() {
+        let timeout = Duration::from_secs(1);
+        let packet = async_std::future::timeout(timeout, listener.next())
+            .await
+            .expect("multicast datagram was not received");
+        assert_eq!(packet.unwrap()?.0, expected);
+    }
+    Ok(())
+}
+
+/// Returns `true` if a datagram sent to `group` is looped back to a member on this host.
+async fn multicast_loopback_available(group: std::net::Ipv4Addr) -> bool {
+    let probe = async {
+        let socket = async_std::net::UdpSocket::bind("0.0.0.0:0").await?;
+        socket.join_multicast_v4(group, std::net::Ipv4Addr::UNSPECIFIED)?;
+        socket.set_multicast_loop_v4(true)?;
+        let port = socket.local_addr()?.port();
+        socket.send_to(b"probe", (group, port)).await?;
+        let mut buf = [0u8; 16];
+        socket.recv_from(&mut buf).await
+    };
+    let timeout = Duration::from_secs(1);
+    matches!(async_std::future::timeout(timeout, probe).await, Ok(Ok(_)))
+}
+
+#[async_std::test]
+async fn ready() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (socket, _) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    socket.connect(addr).await?;
+    async_std::future::timeout(Duration::from_secs(1), socket.ready())
+        .await
+        .expect("socket did not become writable")?;
+    socket.send(("/first", ())).await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/first", ()).into_osc_packet());
+    Ok(())
+}
+
+#[async_std::test]
+async fn targeted_sender() -> Result<()> {
+    let (mut first, first_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (mut second, second_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (socket, _) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+
+    let sender = TargetedSender::new(socket.sender(), first_addr);
+    sender.send(("/a", ())).await?;
+    sender.clone().set_target(second_addr);
+    assert_eq!(sender.target(), second_addr);
+    sender.send(("/b", ())).await?;
+
+    let (packet, _) = first.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/a", ()).into_osc_packet());
+    let (packet, _) = second.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/b", ()).into_osc_packet());
+    Ok(())
+}
+
+#[async_std::test]
+async fn pipe_to_channel() -> Result<()> {
+    let (receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (socket, _) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (tx, mut rx) = futures_channel::mpsc::channel(4);
+    let task = task::spawn(receiver.pipe_to(tx));
+
+    socket.send_to(("/a", (1,)), addr).await?;
+    socket.send_to(("/b", (2,)), addr).await?;
+    let (packet, peer_addr) = rx.next().await.unwrap();
+    assert_eq!(packet, OscMessage::new("/a", (1,)).into_osc_packet());
+    assert_eq!(peer_addr, socket.local_addr()?);
+    let (packet, _) = rx.next().await.unwrap();
+    assert_eq!(packet, OscMessage::new("/b", (2,)).into_osc_packet());
+
+    // Dropping the receiving end stops the pipe with an error on the next packet.
+    drop(rx);
+    socket.send_to(("/c", (3,)), addr).await?;
+    let err = task.await.unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::BrokenPipe));
+    Ok(())
+}
+
+#[async_std::test]
+async fn sender_pool() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let pool = OscSenderPool::new();
+    let first = pool.get(addr).await?;
+    let second = pool.get(addr).await?;
+    assert_eq!(pool.len(), 1);
+    assert_eq!(first.socket().local_addr()?, second.socket().local_addr()?);
+
+    second.send(("/pooled", (1,))).await?;
+    let (packet, peer_addr) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/pooled", (1,)).into_osc_packet());
+    assert_eq!(peer_addr.port(), first.socket().local_addr()?.port());
+
+    assert!(pool.remove(addr).is_some());
+    assert!(pool.is_empty());
+    Ok(())
+}
+
+#[async_std::test]
+async fn tee_sender() -> Result<()> {
+    let (mut first, first_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (mut second, second_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let mut tee = TeeSender::default();
+    for addr in &[first_addr, second_addr] {
+        let socket = OscSocket::bind("127.0.0.1:0").await?;
+        socket.connect(addr).await?;
+        tee.push(socket.sender());
+    }
+    // A sender that is not connected fails without affecting the others.
+    tee.push(OscSocket::bind("127.0.0.1:0").await?.sender());
+    assert_eq!(tee.len(), 3);
+
+    let results = tee.send(("/tee", (1,))).await;
+    assert!(results[0].is_ok());
+    assert!(results[1].is_ok());
+    assert!(results[2].is_err());
+    let expected = OscMessage::new("/tee", (1,)).into_osc_packet();
+    assert_eq!(first.next().await.unwrap()?.0, expected);
+    assert_eq!(second.next().await.unwrap()?.0, expected);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[async_std::test]
+async fn send_from_source_address() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let sender = OscSocket::bind("0.0.0.0:0").await?;
+    // All of 127.0.0.0/8 is local on Linux.
+    let src = "127.0.0.2".parse().unwrap();
+    sender.send_from(("/from", (1,)), src, addr).await?;
+    let (packet, peer_addr) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/from", (1,)).into_osc_packet());
+    assert_eq!(peer_addr.ip(), src);
+    assert_eq!(peer_addr.port(), sender.local_addr()?.port());
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[async_std::test]
+async fn set_dscp() -> Result<()> {
+    let v4 = OscSocket::bind("127.0.0.1:0").await?;
+    v4.set_dscp(46)?;
+    assert_eq!(socket2::SockRef::from(v4.socket()).tos_v4()?, 46 << 2);
+    let err = v4.set_dscp(64).unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::InvalidInput));
+
+    if let Ok(v6) = OscSocket::bind("[::1]:0").await {
+        use nix::sys::socket::{getsockopt, sockopt::Ipv6TClass};
+        v6.set_dscp(10)?;
+        assert_eq!(getsockopt(v6.socket(), Ipv6TClass).unwrap(), 10 << 2);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[async_std::test]
+async fn dropped_packets() -> Result<()> {
+    let (receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    assert_eq!(receiver.dropped_packets()?, 0);
+    // Overflow a small receive buffer without reading from it.
+    socket2::SockRef::from(receiver.socket()).set_recv_buffer_size(1024)?;
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    for i in 0..200 {
+        sender.send_to(("/flood", (i,)), addr).await?;
+    }
+    assert!(receiver.dropped_packets()? > 0);
+    Ok(())
+}
+
+#[async_std::test]
+async fn color_and_midi_args() -> Result<()> {
+    let (mut socket, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let color = OscColor {
+        red: 255,
+        green: 128,
+        blue: 0,
+        alpha: 64,
+    };
+    let midi = OscMidiMessage {
+        port: 1,
+        status: 0x90,
+        data1: 60,
+        data2: 127,
+    };
+    let args = vec![OscType::Color(color), OscType::Midi(midi), OscType::Int(1)];
+    socket.send_to(("/light", args), addr).await?;
+    let (packet, _) = socket.next().await.unwrap()?;
+    let message = packet.message().unwrap();
+    assert_eq!(message.get_color(0), Some((255, 128, 0, 64)));
+    assert_eq!(message.get_midi(1), Some([1, 0x90, 60, 127]));
+    assert_eq!(message.get_color(1), None);
+    assert_eq!(message.get_midi(2), None);
+    assert_eq!(message.get_midi(3), None);
+    Ok(())
+}
+
+#[async_std::test]
+async fn nested_array_args() -> Result<()> {
+    use async_osc::OscArray;
+
+    let (mut socket, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let inner: OscArray = vec![1, 2].into_iter().collect();
+    let outer: OscArray = vec![OscType::from("pos"), inner.clone().into(), OscType::Nil]
+        .into_iter()
+        .collect();
+    socket
+        .send_to(("/grouped", (outer.clone(), 0.5f32)), addr)
+        .await?;
+    let (packet, _) = socket.next().await.unwrap()?;
+    let message = packet.message().unwrap();
+    assert!(message.matches("/grouped", "[s[ii]N]f"));
+    let array = message.get_array(0).unwrap();
+    assert_eq!(array, &outer.content[..]);
+    assert_eq!(array[1], OscType::Array(inner));
+    assert_eq!(message.get_array(1), None);
+    assert_eq!(message.get_array(2), None);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[async_std::test]
+async fn recv_ends_after_persistent_error() -> Result<()> {
+    // Receiving on an unconnected stream socket fails with the same error every time.
+    let socket = socket2::Socket::new(socket2::Domain::IPV4, socket2::Type::STREAM, None)?;
+    socket.set_nonblocking(true)?;
+    let socket = async_std::net::UdpSocket::from(std::net::UdpSocket::from(socket));
+    let mut receiver = OscReceiver::new(socket);
+    let timeout = Duration::from_secs(1);
+    let first = async_std::future::timeout(timeout, receiver.next()).await;
+    assert!(matches!(first, Ok(Some(Err(Error::Io(_))))));
+    for _ in 0..3 {
+        let item = async_std::future::timeout(timeout, receiver.next()).await;
+        assert!(matches!(item, Ok(None)));
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn bind_and_connect_retry() -> Result<()> {
+    let delay = Duration::from_millis(10);
+    let mut receiver = OscSocket::bind_retry("127.0.0.1:0", 3, delay).await?;
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    let receiver_addr = receiver.local_addr()?;
+    sender.connect_retry(receiver_addr, 3, delay).await?;
+    sender.send(("/resolved", ())).await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/resolved", ()).into_osc_packet());
+
+    // An address without a port is malformed, so it is not retried.
+    let start = std::time::Instant::now();
+    let res = OscSocket::bind_retry("no-port", 3, delay).await;
+    assert!(
+        matches!(res, Err(Error::Io(ref err)) if err.kind() == std::io::ErrorKind::InvalidInput)
+    );
+    assert!(start.elapsed() < delay);
+    Ok(())
+}
+
+#[async_std::test]
+async fn shared_receiver() -> Result<()> {
+    let (socket, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let mut first = SharedReceiver::new(socket);
+    let mut second = first.clone();
+    assert_eq!(second.local_addr()?, addr);
+
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    sender.send_to(("/shared", (1,)), addr).await?;
+    let expected = OscMessage::new("/shared", (1,)).into_osc_packet();
+    for receiver in &mut [&mut first, &mut second] {
+        let (packet, peer_addr) = receiver.next().await.unwrap()?;
+        assert_eq!(packet, expected);
+        assert_eq!(peer_addr, sender.local_addr()?);
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn shared_receiver_drops_newest() -> Result<()> {
+    let (socket, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let mut idle = SharedReceiver::with_capacity(socket, 2);
+    // Packets are passed to the handles in the order they were created, so once the clone has a
+    // packet, the idle handle has either queued or dropped it.
+    let mut polled = idle.clone();
+
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    for i in 0..5 {
+        sender.send_to(("/shared", (i,)), addr).await?;
+        let (packet, _) = polled.next().await.unwrap()?;
+        assert_eq!(packet, OscMessage::new("/shared", (i,)).into_osc_packet());
+    }
+    // The idle handle only kept the packets that fit into its queue.
+    for i in 0..2 {
+        let (packet, _) = idle.next().await.unwrap()?;
+        assert_eq!(packet, OscMessage::new("/shared", (i,)).into_osc_packet());
+    }
+    sender.send_to(("/shared", (5,)), addr).await?;
+    let (packet, _) = idle.next().await.unwrap()?;
+    assert_eq!(packet, OscMessage::new("/shared", (5,)).into_osc_packet());
+    Ok(())
+}
+
+#[async_std::test]
+async fn push_arg_and_resend() -> Result<()> {
+    let (mut proxy, proxy_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (mut receiver, target) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    sender.send_to(("/volume", (0.5f32,)), proxy_addr).await?;
+
+    let (packet, _) = proxy.next().await.unwrap()?;
+    let mut message = packet.into_message().unwrap();
+    message.push_arg("source-1");
+    message.prepend_arg(7);
+    proxy.send_to(message, target).await?;
+
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(
+        packet.into_message().unwrap(),
+        OscMessage::new("/volume", (7, 0.5f32, "source-1"))
+    );
+    Ok(())
+}
+
+#[async_std::test]
+async fn try_send() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let socket = OscSocket::bind("127.0.0.1:0").await?;
+    socket.connect(addr).await?;
+    let sender = socket.sender();
+    let mut sent = 0;
+    for i in 0..10 {
+        if sender.try_send(("/try", (i,)))?.is_some() {
+            sent += 1;
+        }
+    }
+    assert!(sent > 0);
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet.message().unwrap().addr, "/try");
+    Ok(())
+}
+
+#[async_std::test]
+async fn auto_flatten_bundles() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    receiver.set_auto_flatten_bundles(true);
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    let nested = OscBundle {
+        timetag: (0, 1),
+        content: vec![OscMessage::new("/c", (3,)).into_osc_packet()],
+    };
+    let bundle = OscBundle {
+        timetag: (0, 1),
+        content: vec![
+            OscMessage::new("/a", (1,)).into_osc_packet(),
+            OscPacket::Bundle(nested),
+            OscMessage::new("/b", (2,)).into_osc_packet(),
+        ],
+    };
+    sender.send_to(OscPacket::Bundle(bundle), addr).await?;
+    sender.send_to(("/d", (4,)), addr).await?;
+
+    for expected in &["/a", "/c", "/b", "/d"] {
+        let (packet, peer_addr) = receiver.next().await.unwrap()?;
+        assert_eq!(packet.message().unwrap().addr, *expected);
+        assert_eq!(peer_addr, sender.local_addr()?);
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn send_packet_with_optional_target() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (mut other, other_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let socket = OscSocket::bind("127.0.0.1:0").await?;
+
+    let err = socket.send_packet(("/a", ()), None).await.unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::NotConnected));
+
+    socket
+        .send_packet(("/explicit", ()), Some(other_addr))
+        .await?;
+    let (packet, _) = other.next().await.unwrap()?;
+    assert_eq!(packet.message().unwrap().addr, "/explicit");
+
+    socket.connect(addr).await?;
+    socket
+        .sender()
+        .send_packet(("/connected", ()), None)
+        .await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet.message().unwrap().addr, "/connected");
+    Ok(())
+}
+
+#[async_std::test]
+async fn drop_with_pending_recv() -> Result<()> {
+    let (mut socket, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    // Cancelling `next` keeps the pending receive in the socket.
+    let pending = async_std::future::timeout(Duration::from_millis(10), socket.next()).await;
+    assert!(pending.is_err());
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    sender.send_to(("/kept", ()), addr).await?;
+    let (packet, _) = socket.next().await.unwrap()?;
+    assert_eq!(packet.message().unwrap().addr, "/kept");
+
+    let pending = async_std::future::timeout(Duration::from_millis(10), socket.next()).await;
+    assert!(pending.is_err());
+    drop(socket);
+
+    // The port is free again once the socket with the pending receive is dropped.
+    let mut socket = OscSocket::bind(addr).await?;
+    sender.send_to(("/again", ()), addr).await?;
+    let (packet, _) = socket.next().await.unwrap()?;
+    assert_eq!(packet.message().unwrap().addr, "/again");
+    Ok(())
+}
+
+#[async_std::test]
+async fn ping_pong() -> Result<()> {
+    let (mut server, server_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    task::spawn(async move {
+        while let Some(Ok((packet, peer_addr))) = server.next().await {
+            server.respond_to_ping(&packet, peer_addr).await.unwrap();
+        }
+    });
+    let mut client = OscSocket::bind("127.0.0.1:0").await?;
+    let timeout = Duration::from_secs(1);
+    // A malformed packet that arrives while waiting for the pong is skipped.
+    let junk = OscSocket::bind("127.0.0.1:0").await?;
+    junk.connect(client.local_addr()?).await?;
+    junk.send_raw(b"junk").await?;
+    for _ in 0..3 {
+        let rtt = client.ping(server_addr, timeout).await?;
+        assert!(rtt < timeout);
+    }
+
+    // Nobody answers pings on this socket.
+    let (_silent, silent_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let err = client
+        .ping(silent_addr, Duration::from_millis(50))
+        .await
+        .unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::TimedOut));
+    Ok(())
+}
+
+#[async_std::test]
+async fn send_compiled() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    sender.connect(addr).await?;
+    let compiled = codec::CompiledMessage::new("/mixer/volume", "if")?;
+    for i in 0..3 {
+        let args = [OscType::Int(i), OscType::Float(0.5)];
+        sender.send_compiled(&compiled, &args).await?;
+        let (packet, _) = receiver.next().await.unwrap()?;
+        assert_eq!(
+            packet.message().unwrap(),
+            &OscMessage::new("/mixer/volume", (i, 0.5f32))
+        );
+    }
+    let err = sender
+        .send_compiled(&compiled, &[OscType::Float(0.5)])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Osc(_)));
+    Ok(())
+}
+
+#[test]
+fn bind_without_runtime() -> Result<()> {
+    // No async-std runtime is running on this thread; the reactor is started on demand.
+    std::thread::spawn(|| {
+        futures_lite::future::block_on(async {
+            let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+            let sender = OscSocket::bind("127.0.0.1:0").await?;
+            sender.send_to(("/hello", ()), addr).await?;
+            let (packet, _) = receiver.next().await.unwrap()?;
+            assert_eq!(packet.message().unwrap().addr, "/hello");
+            Ok(())
+        })
+    })
+    .join()
+    .unwrap()
+}
+
+#[async_std::test]
+async fn recv_one() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    sender.send_to(("/one", (1,)), addr).await?;
+    let (packet, peer_addr) = receiver.recv_one().await?;
+    assert_eq!(packet.message().unwrap(), &OscMessage::new("/one", (1,)));
+    assert_eq!(peer_addr, sender.local_addr()?);
+    Ok(())
+}
+
+#[async_std::test]
+async fn reply_bundle() -> Result<()> {
+    let (mut server, server_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let (mut client, _) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let err = server.reply(("/early", ())).await.unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::NotConnected));
+
+    client.send_to(("/query", ()), server_addr).await?;
+    let (packet, peer_addr) = server.recv_one().await?;
+    assert_eq!(packet.message().unwrap().addr, "/query");
+    assert_eq!(server.last_peer(), Some(peer_addr));
+    let messages = vec![
+        OscMessage::new("/volume", (0.8f32,)),
+        OscMessage::new("/mute", (false,)),
+    ];
+    server.reply_bundle(messages.clone(), (1, 0)).await?;
+
+    let (packet, peer_addr) = client.recv_one().await?;
+    assert_eq!(peer_addr, server_addr);
+    let expected = OscBundle {
+        timetag: (1, 0),
+        content: messages.into_iter().map(OscPacket::Message).collect(),
+    };
+    assert_eq!(packet, OscPacket::Bundle(expected));
+    Ok(())
+}
+
+#[async_std::test]
+async fn address_family_mismatch() -> Result<()> {
+    let v6_only = match OscSocket::builder().only_v6(true).bind("[::1]:0").await {
+        Ok(socket) => socket,
+        Err(_) => {
+            eprintln!("skipping address_family_mismatch: IPv6 is not available");
+            return Ok(());
+        }
+    };
+    let (_receiver, v4_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let err = v6_only.send_to(("/a", ()), v4_addr).await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::AddressFamilyMismatch { target, .. } if target == v4_addr
+    ));
+    let err = v6_only
+        .send_packet(("/a", ()), Some(v4_addr))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::AddressFamilyMismatch { .. }));
+
+    let v4 = OscSocket::bind("127.0.0.1:0").await?;
+    let v6_addr = v6_only.local_addr()?;
+    let err = v4.send_to(("/a", ()), v6_addr).await.unwrap_err();
+    assert!(matches!(err, Error::AddressFamilyMismatch { .. }));
+    Ok(())
+}
+
+#[async_std::test]
+async fn send_auto_compatible_families() -> Result<()> {
+    let (mut receiver, v4_addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let expected = OscMessage::new("/auto", ()).into_osc_packet();
+
+    let v4 = OscSocket::bind("127.0.0.1:0").await?;
+    v4.send_auto(("/auto", ()), v4_addr).await?;
+    assert_eq!(receiver.recv_one().await?.0, expected);
+
+    // An IPv4-mapped target is sent to as IPv4 from an IPv4 socket.
+    let ip = match v4_addr.ip() {
+        std::net::IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        _ => unreachable!(),
+    };
+    let mapped = std::net::SocketAddr::new(ip.into(), v4_addr.port());
+    v4.sender().send_auto(("/auto", ()), mapped).await?;
+    assert_eq!(receiver.recv_one().await?.0, expected);
+
+    // An IPv4 target is sent to as IPv4-mapped from a dual-stack socket.
+    if let Ok(dual) = OscSocket::builder().only_v6(false).bind("[::]:0").await {
+        dual.send_auto(("/auto", ()), v4_addr).await?;
+        assert_eq!(receiver.recv_one().await?.0, expected);
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn send_auto_incompatible_families() -> Result<()> {
+    let v6_only = match OscSocket::builder().only_v6(true).bind("[::1]:0").await {
+        Ok(socket) => socket,
+        Err(_) => {
+            eprintln!("skipping send_auto_incompatible_families: IPv6 is not available");
+            return Ok(());
+        }
+    };
+    let v6_addr = v6_only.local_addr()?;
+    let v4 = OscSocket::bind("127.0.0.1:0").await?;
+    let v4_addr = v4.local_addr()?;
+
+    let err = v6_only.send_auto(("/a", ()), v4_addr).await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::AddressFamilyMismatch { local, target } if local == v6_addr && target == v4_addr
+    ));
+    let err = v4.send_auto(("/a", ()), v6_addr).await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::AddressFamilyMismatch { local, target } if local == v4_addr && target == v6_addr
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[async_std::test]
+async fn send_json_args() -> Result<()> {
+    use async_osc::OscArray;
+
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    let json: serde_json::Value = serde_json::from_str(
+        r#"[1, 4294967296, 0.1, 18446744073709551615, "main", true, null, [2, "x"]]"#,
+    )
+    .unwrap();
+    sender
+        .send_to(OscMessage::new("/json", json.clone()), addr)
+        .await?;
+    let (packet, _) = receiver.next().await.unwrap()?;
+    let expected = vec![
+        OscType::Int(1),
+        OscType::Long(1 << 32),
+        OscType::Double(0.1),
+        OscType::Double(u64::MAX as f64),
+        OscType::String("main".into()),
+        OscType::Bool(true),
+        OscType::Nil,
+        OscType::Array(OscArray {
+            content: vec![OscType::Int(2), OscType::String("x".into())],
+        }),
+    ];
+    assert_eq!(packet.message().unwrap().args, expected);
+
+    let values = json.as_array().unwrap();
+    let message = OscMessage::new("/json", &values[..]);
+    assert_eq!(message.args, expected);
+    Ok(())
+}
+
+#[async_std::test]
+async fn scheduled_latency_compensation() -> Result<()> {
+    use async_osc::prelude::OscTimeExt;
+    use async_osc::{OscTime, Time};
+    use std::time::Instant;
+
+    let (receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    let compensation = Duration::from_millis(200);
+    let mut scheduled = receiver.scheduled().with_latency_compensation(compensation);
+
+    let timetag = OscTime::after(Duration::from_millis(300));
+    assert_eq!(
+        scheduled.fire_time(timetag),
+        Some(Time::from_osc_time(timetag).0 - compensation)
+    );
+    assert_eq!(scheduled.fire_time((0, 1)), None);
+
+    let start = Instant::now();
+    let bundle = OscBundle {
+        timetag,
+        content: vec![OscMessage::new("/cue", (1,)).into_osc_packet()],
+    };
+    sender.send_to(bundle.clone(), addr).await?;
+    sender.send_to(("/now", ()), addr).await?;
+
+    // Messages are not delayed.
+    let (packet, _) = scheduled.next().await.unwrap()?;
+    assert_eq!(packet.message().unwrap().addr, "/now");
+    assert_eq!(scheduled.pending_len(), 1);
+    let (packet, _) = scheduled.next().await.unwrap()?;
+    let elapsed = start.elapsed();
+    assert_eq!(packet, OscPacket::Bundle(bundle));
+    assert!(elapsed >= Duration::from_millis(80), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_millis(250), "{:?}", elapsed);
+    Ok(())
+}
+
+#[async_std::test]
+async fn scheduled_drops_newest_when_full() -> Result<()> {
+    use async_osc::prelude::OscTimeExt;
+    use async_osc::OscTime;
+
+    let (receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    let mut scheduled = receiver.scheduled().with_capacity(2);
+
+    for i in 0..3 {
+        let bundle = OscBundle {
+            timetag: OscTime::after(Duration::from_secs(3600)),
+            content: vec![OscMessage::new("/later", (i,)).into_osc_packet()],
+        };
+        sender.send_to(bundle, addr).await?;
+    }
+    sender.send_to(("/now", ()), addr).await?;
+
+    let (packet, _) = scheduled.next().await.unwrap()?;
+    assert_eq!(packet.message().unwrap().addr, "/now");
+    assert_eq!(scheduled.pending_len(), 2);
+    assert_eq!(scheduled.dropped_count(), 1);
+    Ok(())
+}
+
+#[async_std::test]
+async fn cancel_scheduled_send() -> Result<()> {
+    use std::time::SystemTime;
+
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let socket = OscSocket::bind("127.0.0.1:0").await?;
+    socket.connect(addr).await?;
+    let sender = socket.sender();
+
+    let at = SystemTime::now() + Duration::from_millis(100);
+    let recalled = sender.send_at(("/cue/recalled", ()), at);
+    let kept = sender.send_at(("/cue/kept", ()), at + Duration::from_millis(50));
+    recalled.cancel();
+    assert!(recalled.is_cancelled());
+    assert!(!recalled.wait().await?);
+    assert!(kept.wait().await?);
+
+    // Only the cue that was not cancelled arrives.
+    let (packet, _) = receiver.next().await.unwrap()?;
+    assert_eq!(packet.message().unwrap().addr, "/cue/kept");
+    let next = async_std::future::timeout(Duration::from_millis(200), receiver.next()).await;
+    assert!(next.is_err());
+    Ok(())
+}
+
+#[async_std::test]
+async fn scheduler_cues() -> Result<()> {
+    use std::time::Instant;
+
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let socket = OscSocket::bind("127.0.0.1:0").await?;
+    socket.connect(addr).await?;
+    // The cues are sorted by their offsets.
+    let cues = vec![
+        (Duration::from_millis(40), OscMessage::new("/cue/2", ())),
+        (Duration::from_millis(20), OscMessage::new("/cue/1", ())),
+    ];
+    let start = Instant::now();
+    let playback = Scheduler::new(socket.sender(), cues).start();
+    let mut arrivals = Vec::new();
+    for _ in 0..2 {
+        let (packet, _) = receiver.next().await.unwrap()?;
+        arrivals.push((packet.message().unwrap().addr.clone(), start.elapsed()));
+    }
+    playback.wait().await?;
+    assert_eq!(arrivals[0].0, "/cue/1");
+    assert_eq!(arrivals[1].0, "/cue/2");
+    assert!(arrivals[0].1 >= Duration::from_millis(20));
+    assert!(arrivals[1].1 >= Duration::from_millis(40));
+    assert!(arrivals[1].1 < Duration::from_millis(500));
+    Ok(())
+}
+
+#[async_std::test]
+async fn scheduler_looping() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let socket = OscSocket::bind("127.0.0.1:0").await?;
+    socket.connect(addr).await?;
+    let cues = vec![(Duration::from_millis(5), OscMessage::new("/tick", ()))];
+    let playback = Scheduler::new(socket.sender(), cues)
+        .looping(Duration::from_millis(20))
+        .start();
+    for _ in 0..3 {
+        let (packet, _) = receiver.next().await.unwrap()?;
+        assert_eq!(packet.message().unwrap().addr, "/tick");
+    }
+    playback.stop();
+    assert!(playback.is_stopped());
+    playback.wait().await?;
+    Ok(())
+}
+
+#[async_std::test]
+async fn scheduler_looping_without_cues_in_period() -> Result<()> {
+    let socket = OscSocket::bind("127.0.0.1:0").await?;
+    socket.connect("127.0.0.1:9").await?;
+    let cues = vec![(Duration::from_millis(50), OscMessage::new("/never", ()))];
+    for cues in [vec![], cues] {
+        let playback = Scheduler::new(socket.sender(), cues)
+            .looping(Duration::from_millis(10))
+            .start();
+        task::sleep(Duration::from_millis(30)).await;
+        playback.stop();
+        async_std::future::timeout(Duration::from_secs(1), playback.wait())
+            .await
+            .expect("playback did not stop")?;
+    }
+    Ok(())
+}
+
+#[async_std::test]
+async fn router_run_skips_recoverable_errors() -> Result<()> {
+    use std::io::{Error as IoError, ErrorKind};
+    let peer_addr = "127.0.0.1:9000".parse().unwrap();
+    let items: Vec<Result<(OscPacket, std::net::SocketAddr)>> = vec![
+        Err(Error::Osc(async_osc::rosc::OscError::BadPacket("junk"))),
+        Err(IoError::from(ErrorKind::ConnectionRefused).into()),
+        Ok((OscMessage::new("/hit", (1,)).into_osc_packet(), peer_addr)),
+        Err(IoError::from(ErrorKind::InvalidInput).into()),
+        Ok((OscMessage::new("/hit", (2,)).into_osc_packet(), peer_addr)),
+    ];
+    let hits = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let mut router = OscRouter::new();
+    let counter = hits.clone();
+    router.route("/hit", move |_, _| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    });
+    let err = router
+        .run(async_std::stream::from_iter(items))
+        .await
+        .unwrap_err();
+    assert_eq!(err.io_kind(), Some(std::io::ErrorKind::InvalidInput));
+    assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    Ok(())
+}
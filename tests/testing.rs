@@ -0,0 +1,49 @@
+use async_osc::codec::{self, DecodeMode};
+use async_osc::prelude::*;
+use async_osc::testing::{assert_recv, decode_from_bytes};
+use async_osc::{OscBundle, OscMessage, OscPacket, OscSocket, OscType, Result};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+#[async_std::test]
+async fn assert_recv_round_trip() -> Result<()> {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await?;
+    let sender = OscSocket::bind("127.0.0.1:0").await?;
+    sender
+        .send_to(("/round/trip", (1, 2.5f32, "x")), addr)
+        .await?;
+    let peer_addr = assert_recv(&mut receiver, ("/round/trip", (1, 2.5f32, "x")), TIMEOUT).await;
+    assert_eq!(peer_addr, sender.local_addr()?);
+    Ok(())
+}
+
+#[async_std::test]
+#[should_panic(expected = "arg 1: Float(2.5) != Float(3.0)")]
+async fn assert_recv_shows_differing_args() {
+    let (mut receiver, addr) = OscSocket::bind_ephemeral("127.0.0.1").await.unwrap();
+    receiver.send_to(("/a", (1, 3.0f32)), addr).await.unwrap();
+    let expected = ("/a", vec![OscType::Int(1), OscType::Float(2.5)]);
+    assert_recv(&mut receiver, expected, TIMEOUT).await;
+}
+
+#[test]
+fn decode_from_bytes_modes() {
+    // A bundle followed by an element that does not decode, as sent by some devices.
+    let bundle = OscPacket::Bundle(OscBundle {
+        timetag: (0, 1),
+        content: vec![OscMessage::new("/ping", (1,)).into_osc_packet()],
+    });
+    let mut bytes = codec::encode(&bundle).unwrap();
+    bytes.extend_from_slice(&[0, 0, 0, 4, b'j', b'u', b'n', b'k']);
+    assert!(decode_from_bytes(&bytes, DecodeMode::Strict).is_err());
+    assert_eq!(
+        decode_from_bytes(&bytes, DecodeMode::Lenient).unwrap(),
+        bundle
+    );
+
+    // Arguments that do not fit into the datagram are rejected in both modes.
+    let truncated = b"/ping\0\0\0,i\0\0\0\0";
+    assert!(decode_from_bytes(truncated, DecodeMode::Strict).is_err());
+    assert!(decode_from_bytes(truncated, DecodeMode::Lenient).is_err());
+}